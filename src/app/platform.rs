@@ -153,6 +153,12 @@ async fn login1_session_active() -> Result<bool, String> {
     Ok(active)
 }
 
+/// Socket probes (2s apart) to wait for a restarting compositor before
+/// giving up; the Wayland service reconnects on its own in the meantime.
+/// 60s leaves room for five reconnect attempts (the last 31s in), plenty for
+/// a compositor restart; one that stays down longer is treated as gone.
+const SOCKET_GRACE_PROBES: u32 = 30;
+
 pub fn spawn_wayland_socket_watcher(shutdown_tx: tokio::sync::watch::Sender<bool>) {
     let sock = match wayland_socket_path() {
         Ok(p) => p,
@@ -175,12 +181,18 @@ pub fn spawn_wayland_socket_watcher(shutdown_tx: tokio::sync::watch::Sender<bool
 
             // 1) Wayland socket liveness (compositor/socket really gone)
             if UnixStream::connect(&sock).is_err() {
+                if socket_failures == 0 {
+                    eventline::warn!(
+                        "wayland socket not connectable ({}); waiting for the compositor",
+                        sock.display()
+                    );
+                }
                 socket_failures += 1;
             } else {
                 socket_failures = 0;
             }
 
-            if socket_failures >= 3 {
+            if socket_failures >= SOCKET_GRACE_PROBES {
                 eventline::info!(
                     "wayland socket not connectable ({}); shutting down",
                     sock.display()
@@ -61,19 +61,46 @@ default:
 
   monitor_media true
   ignore_remote_media true # ignore remote players (spotify/kdeconnect/etc.)
+  # Where playback comes from: "pactl" (audio streams), "mpris" (players on the
+  # session bus, event-driven) or "both"
+  #media_backend "pactl"
+
+  # Answer org.freedesktop.ScreenSaver Inhibit calls (browsers, video players,
+  # presentation tools) on the session bus
+  #screensaver_inhibit true
+
+  # Pause while a window holds a compositor idle inhibitor (needs
+  # ext-idle-notify v2)
+  #compositor_inhibit true
+
+  # logind "idle" inhibitors (systemd-inhibit --what=idle) always pause Stasis
+  # in block mode; set this to honor delay-mode locks as well
+  #logind_inhibit_delay true
 
   # Optional: ignore these media sources for media inhibit (case-insensitive)
   #media_blacklist ["spotify"]
 
+  # Inhibit while a microphone is recording or a camera is open (calls)
+  #monitor_capture true
+  #capture_blacklist ["easyeffects"]
+
   # Debounce window in seconds before starting the plan (default 0)
   #debounce_seconds 4
 
+  # Let the compositor time the steps (ext-idle-notify) instead of
+  # Stasis' own clock (default false)
+  #compositor_idle_timers true
+
   # Notify when resuming from IPC pause (e.g., `stasis pause 1h`)
   #notify_on_unpause true
 
   # Global gate: notifications before steps only happen if this is true
   #notify_before_action true
 
+  # Lock (or run pre_suspend_command) before the system sleeps, holding
+  # a login1 delay lock until the locker is up
+  #lock_before_sleep true
+
   # App/process inhibit patterns (strings or regex literals)
   inhibit_apps [
     "vlc"
@@ -81,6 +108,50 @@ default:
     r"steam_app_.*"
   ]
 
+  # Structured inhibit rules: every field set in a rule must match the same
+  # window (app_id, title, fullscreen, focused, workspace_visible)
+  #inhibit_rules:
+  #  youtube:
+  #    app_id "firefox"
+  #    title r"YouTube"
+  #  end
+  #  fullscreen_focused:
+  #    fullscreen true
+  #    focused true
+  #  end
+  #end
+
+  # Limit what an inhibitor holds back (default: the whole plan).
+  # A step name holds the plan at that step; a list skips only those steps.
+  # Sources: manual, apps, media, capture, logind, screensaver, compositor
+  #inhibit_scopes:
+  #  media "suspend"
+  #  screensaver [ "lock_screen" ]
+  #end
+
+  # Stop honoring an inhibitor that has stayed unchanged this long with no user
+  # activity (e.g. a paused player left open). A number applies to every source;
+  # a block sets per-source limits, 0 = never expire.
+  #max_inhibit_seconds 7200
+  #max_inhibit_seconds:
+  #  default 7200
+  #  logind 0
+  #end
+  #notify_on_inhibit_expiry true
+
+  # Last resort on low battery, even while inhibited or paused: notify, wait
+  # notify_seconds_before, then hibernate (or run `command`)
+  #critical_battery:
+  #  threshold 5
+  #  notify_seconds_before 30
+  #  #command "systemctl hibernate"
+  #end
+
+  # Closing the lid: "pause", "lock", "suspend" (the plan's sleep step), or
+  # "ignore_when_docked" (pause, unless external displays are connected;
+  # then `docked:` runs)
+  #lid_policy "ignore_when_docked"
+
   # Laptop plan: AC power (relaxed)
   ac:
     brightness:
@@ -135,6 +206,24 @@ default:
       command "systemctl suspend"
     end
   end
+
+  # Battery tier: below 20% these steps replace their battery counterparts.
+  #battery_low:
+  #  below 20
+  #  suspend:
+  #    timeout 30 # 30 second(s) after lock
+  #    command "systemctl suspend"
+  #  end
+  #end
+
+  # Laptop plan: lid closed on external displays (falls back to `ac:`)
+  #docked:
+  #  dpms:
+  #    timeout 600 # 10 minute(s)
+  #    command "niri msg action power-off-monitors"
+  #    resume_command "niri msg action power-on-monitors"
+  #  end
+  #end
 end
 "#
     .trim_start()
@@ -158,19 +247,46 @@ default:
 
   monitor_media true
   ignore_remote_media true # ignore remote players (spotify/kdeconnect/etc.)
+  # Where playback comes from: "pactl" (audio streams), "mpris" (players on the
+  # session bus, event-driven) or "both"
+  #media_backend "pactl"
+
+  # Answer org.freedesktop.ScreenSaver Inhibit calls (browsers, video players,
+  # presentation tools) on the session bus
+  #screensaver_inhibit true
+
+  # Pause while a window holds a compositor idle inhibitor (needs
+  # ext-idle-notify v2)
+  #compositor_inhibit true
+
+  # logind "idle" inhibitors (systemd-inhibit --what=idle) always pause Stasis
+  # in block mode; set this to honor delay-mode locks as well
+  #logind_inhibit_delay true
 
   # Optional: ignore these media sources for media inhibit (case-insensitive)
   #media_blacklist ["spotify"]
 
+  # Inhibit while a microphone is recording or a camera is open (calls)
+  #monitor_capture true
+  #capture_blacklist ["easyeffects"]
+
   # Debounce window in seconds before starting the plan (default 0)
   #debounce_seconds 4
 
+  # Let the compositor time the steps (ext-idle-notify) instead of
+  # Stasis' own clock (default false)
+  #compositor_idle_timers true
+
   # Notify when resuming from IPC pause (e.g., `stasis pause 1h`)
   #notify_on_unpause true
 
   # Global gate: notifications before steps only happen if this is true
   #notify_before_action true
 
+  # Lock (or run pre_suspend_command) before the system sleeps, holding
+  # a login1 delay lock until the locker is up
+  #lock_before_sleep true
+
   # App/process inhibit patterns (strings or regex literals)
   inhibit_apps [
     "vlc"
@@ -178,6 +294,37 @@ default:
     r"steam_app_.*"
   ]
 
+  # Structured inhibit rules: every field set in a rule must match the same
+  # window (app_id, title, fullscreen, focused, workspace_visible)
+  #inhibit_rules:
+  #  youtube:
+  #    app_id "firefox"
+  #    title r"YouTube"
+  #  end
+  #  fullscreen_focused:
+  #    fullscreen true
+  #    focused true
+  #  end
+  #end
+
+  # Limit what an inhibitor holds back (default: the whole plan).
+  # A step name holds the plan at that step; a list skips only those steps.
+  # Sources: manual, apps, media, capture, logind, screensaver, compositor
+  #inhibit_scopes:
+  #  media "suspend"
+  #  screensaver [ "lock_screen" ]
+  #end
+
+  # Stop honoring an inhibitor that has stayed unchanged this long with no user
+  # activity (e.g. a paused player left open). A number applies to every source;
+  # a block sets per-source limits, 0 = never expire.
+  #max_inhibit_seconds 7200
+  #max_inhibit_seconds:
+  #  default 7200
+  #  logind 0
+  #end
+  #notify_on_inhibit_expiry true
+
   lock_screen:
     timeout 300 # 5 minute(s)
     command "swaylock"
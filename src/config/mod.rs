@@ -4,13 +4,15 @@
 pub mod bootstrap;
 pub mod migrate;
 
+use std::collections::BTreeMap;
 use std::env;
 use std::path::{Path, PathBuf};
 
 use rune_cfg::{RuneConfig, Value};
 
 use crate::core::config::{
-    ActionBlock, Config, ConfigFile, LockBlock, PartialConfig, PlanSource, PlanStep, PlanStepKind,
+    ActionBlock, Config, ConfigFile, CriticalBattery, InhibitRule, InhibitScope, InhibitSource,
+    BatteryTier, LidPolicy, LockBlock, MediaBackend, PartialConfig, PlanSource, PlanStep, PlanStepKind,
     Profile, ProfileMode, Pattern,
 };
 
@@ -136,17 +138,40 @@ fn parse_config_file(rc: &RuneConfig) -> Result<ConfigFile, String> {
 
             cfg.monitor_media = rc.get_or("default.monitor_media", false);
             cfg.ignore_remote_media = rc.get_or("default.ignore_remote_media", false);
+            cfg.media_backend =
+                opt_media_backend(rc, "default.media_backend")?.unwrap_or_default();
+            cfg.lid_policy = opt_lid_policy(rc, "default.lid_policy")?.unwrap_or_default();
+
+            cfg.screensaver_inhibit = rc.get_or("default.screensaver_inhibit", false);
+            cfg.compositor_inhibit = rc.get_or("default.compositor_inhibit", false);
+            cfg.logind_inhibit_delay = rc.get_or("default.logind_inhibit_delay", false);
 
             // FIX: allow strings OR /regex/ entries (keep compiled regex)
             cfg.media_blacklist = get_vec_pattern(rc, "default.media_blacklist", Vec::new())?;
 
+            cfg.monitor_capture = rc.get_or("default.monitor_capture", false);
+            cfg.capture_blacklist =
+                get_vec_pattern(rc, "default.capture_blacklist", Vec::new())?;
+
             cfg.debounce_seconds = rc.get_or("default.debounce_seconds", 0u64);
+            cfg.compositor_idle_timers = rc.get_or("default.compositor_idle_timers", false);
 
             cfg.notify_on_unpause = rc.get_or("default.notify_on_unpause", false);
             cfg.notify_before_action = rc.get_or("default.notify_before_action", false);
 
+            cfg.lock_before_sleep = rc.get_or("default.lock_before_sleep", false);
+
             // FIX: allow strings OR /regex/ entries (keep compiled regex)
             cfg.inhibit_apps = get_vec_pattern(rc, "default.inhibit_apps", Vec::new())?;
+            cfg.inhibit_rules = opt_inhibit_rules(rc, "default.inhibit_rules")?.unwrap_or_default();
+            cfg.inhibit_scopes =
+                opt_inhibit_scopes(rc, "default.inhibit_scopes")?.unwrap_or_default();
+            let (max_inhibit, max_inhibit_overrides) =
+                opt_max_inhibit(rc, "default.max_inhibit_seconds")?;
+            cfg.max_inhibit_seconds = max_inhibit.unwrap_or(0);
+            cfg.max_inhibit_overrides = max_inhibit_overrides.unwrap_or_default();
+            cfg.notify_on_inhibit_expiry = rc.get_or("default.notify_on_inhibit_expiry", false);
+            cfg.critical_battery = opt_critical_battery(rc, "default.critical_battery")?;
 
             // legacy named blocks (optional)
             cfg.startup = ActionBlock::disabled();
@@ -156,17 +181,20 @@ fn parse_config_file(rc: &RuneConfig) -> Result<ConfigFile, String> {
             cfg.lock_screen = LockBlock::disabled();
 
             // ---- plans ----
-            // desktop plan: blocks directly under default (EXCEPT ac/battery containers + globals)
+            // desktop plan: blocks directly under default (EXCEPT ac/battery/docked containers + globals)
             let plan_desktop =
                 parse_plan_block(rc, "default", /*allow_ac_battery_containers=*/true, &mut cfg)?;
 
             // laptop plan sources
             let plan_ac = parse_named_plan(rc, "default.ac")?;
             let plan_battery = parse_named_plan(rc, "default.battery")?;
+            let plan_docked = parse_named_plan(rc, "default.docked")?;
 
             cfg.plan_desktop = plan_desktop;
             cfg.plan_ac = plan_ac;
             cfg.plan_battery = plan_battery;
+            cfg.plan_docked = plan_docked;
+            cfg.battery_tiers = parse_battery_tiers(rc, "default")?;
 
             // Do not select cfg.plan here; daemon chooses PlanSource at runtime.
             // But ensure some desktop fallback exists for very old shapes:
@@ -191,7 +219,7 @@ fn parse_config_file(rc: &RuneConfig) -> Result<ConfigFile, String> {
 }
 
 /// Parse plan steps directly under a block (`default` or a profile block),
-/// excluding global knob keys. Optionally treats `ac`/`battery` as containers.
+/// excluding global knob keys. Optionally treats `ac`/`battery`/`docked` as containers.
 fn parse_plan_block(
     rc: &RuneConfig,
     block_name: &str,
@@ -206,7 +234,7 @@ fn parse_plan_block(
     }
 
     fn is_non_step_key(norm: &str, allow_ac_battery_containers: bool) -> bool {
-        if allow_ac_battery_containers && (norm == "ac" || norm == "battery") {
+        if allow_ac_battery_containers && matches!(norm, "ac" | "battery" | "docked") {
             return true;
         }
 
@@ -216,11 +244,25 @@ fn parse_plan_block(
                 | "pre_suspend_command"
                 | "monitor_media"
                 | "ignore_remote_media"
+                | "media_backend"
+                | "lid_policy"
+                | "screensaver_inhibit"
+                | "compositor_inhibit"
+                | "logind_inhibit_delay"
                 | "media_blacklist"
+                | "monitor_capture"
+                | "capture_blacklist"
                 | "debounce_seconds"
+                | "compositor_idle_timers"
                 | "notify_on_unpause"
                 | "notify_before_action"
+                | "lock_before_sleep"
                 | "inhibit_apps"
+                | "inhibit_rules"
+                | "inhibit_scopes"
+                | "max_inhibit_seconds"
+                | "notify_on_inhibit_expiry"
+                | "critical_battery"
         )
     }
 
@@ -241,7 +283,7 @@ fn parse_plan_block(
         }
 
         let base = format!("{block_name}.{raw_k}");
-        if !looks_like_step(rc, &base) {
+        if !looks_like_step(rc, &base) || is_battery_tier(rc, &base) {
             continue;
         }
 
@@ -296,6 +338,18 @@ fn parse_plan_block(
                 legacy_out.suspend = ab.clone();
                 plan.push(step_from_action_block(PlanStepKind::Suspend, ab));
             }
+            "hibernate" => {
+                let ab = parse_action_block(rc, &base)?;
+                plan.push(step_from_action_block(PlanStepKind::Hibernate, ab));
+            }
+            "hybrid_sleep" => {
+                let ab = parse_action_block(rc, &base)?;
+                plan.push(step_from_action_block(PlanStepKind::HybridSleep, ab));
+            }
+            "suspend_then_hibernate" => {
+                let ab = parse_action_block(rc, &base)?;
+                plan.push(step_from_action_block(PlanStepKind::SuspendThenHibernate, ab));
+            }
             other => {
                 let ab = parse_action_block(rc, &base)?;
                 plan.push(step_from_action_block(
@@ -319,6 +373,57 @@ fn parse_named_plan(rc: &RuneConfig, base: &str) -> Result<Vec<PlanStep>, String
     parse_plan_block(rc, base, /*allow_ac_battery_containers=*/false, &mut dummy_cfg)
 }
 
+fn is_battery_tier(rc: &RuneConfig, base: &str) -> bool {
+    rc.has(&format!("{base}.below"))
+}
+
+/// Threshold plans next to `ac`/`battery`, e.g. `battery_low:` with `below 20`
+/// and the battery steps it retimes. Sorted tightest first.
+fn parse_battery_tiers(rc: &RuneConfig, block_name: &str) -> Result<Vec<BatteryTier>, String> {
+    let mut tiers: Vec<BatteryTier> = Vec::new();
+
+    for name in rc.get_keys(block_name).unwrap_or_default() {
+        let base = format!("{block_name}.{name}");
+        if !is_battery_tier(rc, &base) {
+            continue;
+        }
+
+        let norm = name.trim().replace('-', "_").to_lowercase();
+        if matches!(norm.as_str(), "ac" | "battery" | "docked") {
+            return Err(format!(
+                "config error at {base}.below: `{name}` is a plan source; give the tier its own name (e.g. battery_low)"
+            ));
+        }
+
+        let below = opt_u64(rc, format!("{base}.below"))?.unwrap_or(0);
+        if !(1..=100).contains(&below) {
+            return Err(format!(
+                "config error at {base}.below: expected a percentage (1-100), got {below}"
+            ));
+        }
+        if let Some(other) = tiers.iter().find(|t| t.below_percent as u64 == below) {
+            return Err(format!(
+                "config error at {base}.below: `{}` already uses below {below}",
+                other.name
+            ));
+        }
+
+        let plan = parse_named_plan(rc, &base)?;
+        if plan.is_empty() {
+            return Err(format!("config error at {base}: battery tier has no steps"));
+        }
+
+        tiers.push(BatteryTier {
+            name: norm,
+            below_percent: below as u8,
+            plan,
+        });
+    }
+
+    tiers.sort_by_key(|t| t.below_percent);
+    Ok(tiers)
+}
+
 /// Parse profiles (top-level blocks other than `default`).
 fn parse_profiles(rc: &RuneConfig) -> Result<Vec<Profile>, String> {
     let top = rc.get_keys("").unwrap_or_default();
@@ -359,17 +464,35 @@ fn parse_profiles(rc: &RuneConfig) -> Result<Vec<Profile>, String> {
 
         pc.monitor_media = opt_bool(rc, format!("{name}.monitor_media"))?;
         pc.ignore_remote_media = opt_bool(rc, format!("{name}.ignore_remote_media"))?;
+        pc.media_backend = opt_media_backend(rc, &format!("{name}.media_backend"))?;
+        pc.lid_policy = opt_lid_policy(rc, &format!("{name}.lid_policy"))?;
+
+        pc.screensaver_inhibit = opt_bool(rc, format!("{name}.screensaver_inhibit"))?;
+        pc.compositor_inhibit = opt_bool(rc, format!("{name}.compositor_inhibit"))?;
+        pc.logind_inhibit_delay = opt_bool(rc, format!("{name}.logind_inhibit_delay"))?;
 
         // FIX: allow strings OR /regex/ entries (keep compiled regex)
         pc.media_blacklist = opt_vec_pattern(rc, &format!("{name}.media_blacklist"))?;
 
+        pc.monitor_capture = opt_bool(rc, format!("{name}.monitor_capture"))?;
+        pc.capture_blacklist = opt_vec_pattern(rc, &format!("{name}.capture_blacklist"))?;
+
         pc.debounce_seconds = opt_u64(rc, format!("{name}.debounce_seconds"))?;
+        pc.compositor_idle_timers = opt_bool(rc, format!("{name}.compositor_idle_timers"))?;
 
         pc.notify_on_unpause = opt_bool(rc, format!("{name}.notify_on_unpause"))?;
         pc.notify_before_action = opt_bool(rc, format!("{name}.notify_before_action"))?;
 
+        pc.lock_before_sleep = opt_bool(rc, format!("{name}.lock_before_sleep"))?;
+
         // FIX: allow strings OR /regex/ entries (keep compiled regex)
         pc.inhibit_apps = opt_vec_pattern(rc, &format!("{name}.inhibit_apps"))?;
+        pc.inhibit_rules = opt_inhibit_rules(rc, &format!("{name}.inhibit_rules"))?;
+        pc.inhibit_scopes = opt_inhibit_scopes(rc, &format!("{name}.inhibit_scopes"))?;
+        (pc.max_inhibit_seconds, pc.max_inhibit_overrides) =
+            opt_max_inhibit(rc, &format!("{name}.max_inhibit_seconds"))?;
+        pc.notify_on_inhibit_expiry = opt_bool(rc, format!("{name}.notify_on_inhibit_expiry"))?;
+        pc.critical_battery = opt_critical_battery(rc, &format!("{name}.critical_battery"))?;
 
         // plan overrides
         let mut legacy_dummy = Config::disabled();
@@ -377,6 +500,7 @@ fn parse_profiles(rc: &RuneConfig) -> Result<Vec<Profile>, String> {
             parse_plan_block(rc, &name, /*allow_ac_battery_containers=*/true, &mut legacy_dummy)?;
         let plan_ac = parse_named_plan(rc, &format!("{name}.ac"))?;
         let plan_battery = parse_named_plan(rc, &format!("{name}.battery"))?;
+        let plan_docked = parse_named_plan(rc, &format!("{name}.docked"))?;
 
         if !plan_desktop.is_empty() {
             pc.plan_desktop = Some(plan_desktop);
@@ -387,6 +511,13 @@ fn parse_profiles(rc: &RuneConfig) -> Result<Vec<Profile>, String> {
         if !plan_battery.is_empty() {
             pc.plan_battery = Some(plan_battery);
         }
+        if !plan_docked.is_empty() {
+            pc.plan_docked = Some(plan_docked);
+        }
+        let battery_tiers = parse_battery_tiers(rc, &name)?;
+        if !battery_tiers.is_empty() {
+            pc.battery_tiers = Some(battery_tiers);
+        }
 
         profiles.push(Profile {
             name: name.clone(),
@@ -478,6 +609,32 @@ fn opt_vec_string(rc: &RuneConfig, path: impl AsRef<str>) -> Result<Option<Vec<S
         .map_err(|e| format!("config error at {}: {e}", p))
 }
 
+fn opt_media_backend(rc: &RuneConfig, path: &str) -> Result<Option<MediaBackend>, String> {
+    let Some(raw) = opt_string(rc, path)? else {
+        return Ok(None);
+    };
+
+    MediaBackend::parse(&raw).map(Some).ok_or_else(|| {
+        format!(
+            "config error at {}: expected \"pactl\", \"mpris\" or \"both\", got \"{}\"",
+            path, raw
+        )
+    })
+}
+
+fn opt_lid_policy(rc: &RuneConfig, path: &str) -> Result<Option<LidPolicy>, String> {
+    let Some(raw) = opt_string(rc, path)? else {
+        return Ok(None);
+    };
+
+    LidPolicy::parse(&raw).map(Some).ok_or_else(|| {
+        format!(
+            "config error at {}: expected \"pause\", \"lock\", \"suspend\" or \"ignore_when_docked\", got \"{}\"",
+            path, raw
+        )
+    })
+}
+
 fn opt_nullable_string(rc: &RuneConfig, path: impl AsRef<str>) -> Result<Option<String>, String> {
     let p = path.as_ref();
     rc.get_optional::<Option<String>>(p)
@@ -529,6 +686,244 @@ fn opt_vec_pattern(rc: &RuneConfig, path: &str) -> Result<Option<Vec<Pattern>>,
     }
 }
 
+/// Single `"string"` or `/regex/` value (same normalization as the lists).
+fn opt_pattern(rc: &RuneConfig, path: &str) -> Result<Option<Pattern>, String> {
+    if !rc.has(path) {
+        return Ok(None);
+    }
+
+    match rc
+        .get_value(path)
+        .map_err(|e| format!("config error at {}: {e}", path))?
+    {
+        Value::String(s) => {
+            let lit = s.trim().to_lowercase();
+            Ok((!lit.is_empty()).then_some(Pattern::Literal(lit)))
+        }
+        Value::Regex(r) => Ok(Some(Pattern::Regex(r))),
+        other => Err(format!(
+            "config error at {}: expected string or regex, got {:?}",
+            path, other
+        )),
+    }
+}
+
+/// Named rule blocks under `inhibit_rules:`, e.g.
+///
+/// ```text
+/// inhibit_rules:
+///   youtube:
+///     app_id "firefox"
+///     title r"YouTube"
+///   end
+/// end
+/// ```
+fn opt_inhibit_rules(rc: &RuneConfig, path: &str) -> Result<Option<Vec<InhibitRule>>, String> {
+    const FIELDS: &[&str] = &["app_id", "title", "fullscreen", "focused", "workspace_visible"];
+
+    if !rc.has(path) {
+        return Ok(None);
+    }
+
+    let mut rules = Vec::new();
+
+    for name in rc.get_keys(path).unwrap_or_default() {
+        let base = format!("{path}.{name}");
+
+        for key in rc.get_keys(&base).unwrap_or_default() {
+            if !FIELDS.contains(&key.replace('-', "_").as_str()) {
+                eventline::warn!(
+                    "config: `{}.{}` is not an inhibit rule field (expected one of {}); ignoring it",
+                    base,
+                    key,
+                    FIELDS.join(", ")
+                );
+            }
+        }
+
+        let rule = InhibitRule {
+            name: Some(name.clone()),
+            app_id: opt_pattern(rc, &format!("{base}.app_id"))?,
+            title: opt_pattern(rc, &format!("{base}.title"))?,
+            fullscreen: opt_bool(rc, format!("{base}.fullscreen"))?,
+            focused: opt_bool(rc, format!("{base}.focused"))?,
+            workspace_visible: opt_bool(rc, format!("{base}.workspace_visible"))?,
+        };
+
+        if rule.is_empty() {
+            return Err(format!(
+                "config error at {}: inhibit rule needs at least one of {}",
+                base,
+                FIELDS.join(", ")
+            ));
+        }
+
+        rules.push(rule);
+    }
+
+    Ok(Some(rules))
+}
+
+/// `inhibit_scopes:` block keyed by source:
+///
+/// ```text
+/// inhibit_scopes:
+///   media "suspend"           # hold the plan at suspend
+///   apps [ "lock_screen" ]    # skip only these steps
+///   manual "all"              # whole plan (the default)
+/// end
+/// ```
+fn opt_inhibit_scopes(
+    rc: &RuneConfig,
+    path: &str,
+) -> Result<Option<BTreeMap<InhibitSource, InhibitScope>>, String> {
+    if !rc.has(path) {
+        return Ok(None);
+    }
+
+    let mut scopes = BTreeMap::new();
+
+    for key in rc.get_keys(path).unwrap_or_default() {
+        let base = format!("{path}.{key}");
+        let Some(src) = InhibitSource::parse(&key) else {
+            return Err(format!(
+                "config error at {}: unknown inhibit source (expected manual, apps, media, capture, logind, screensaver or compositor)",
+                base
+            ));
+        };
+
+        let v = rc
+            .get_value(&base)
+            .map_err(|e| format!("config error at {}: {e}", base))?;
+
+        let scope = match v {
+            Value::String(s) if s.trim().eq_ignore_ascii_case("all") => InhibitScope::All,
+            Value::String(s) if !s.trim().is_empty() => InhibitScope::UpTo(s.trim().to_string()),
+            Value::Array(items) => {
+                let mut steps = Vec::with_capacity(items.len());
+                for item in items {
+                    match item {
+                        Value::String(s) if !s.trim().is_empty() => {
+                            steps.push(s.trim().to_string())
+                        }
+                        other => {
+                            return Err(format!(
+                                "config error at {}: expected step name, got {:?}",
+                                base, other
+                            ));
+                        }
+                    }
+                }
+                InhibitScope::Steps(steps)
+            }
+            other => {
+                return Err(format!(
+                    "config error at {}: expected \"all\", a step name or a list of step names, got {:?}",
+                    base, other
+                ));
+            }
+        };
+
+        scopes.insert(src, scope);
+    }
+
+    Ok(Some(scopes))
+}
+
+/// Global limit and per-source overrides, each `None` when not set.
+type MaxInhibit = (Option<u64>, Option<BTreeMap<InhibitSource, u64>>);
+
+/// `max_inhibit_seconds` as one number for every source, or a block keyed by
+/// source where `default` covers the sources not listed:
+///
+/// ```text
+/// max_inhibit_seconds:
+///   default 7200
+///   media 3600
+///   logind 0        # never expire
+/// end
+/// ```
+fn opt_max_inhibit(rc: &RuneConfig, path: &str) -> Result<MaxInhibit, String> {
+    if !rc.has(path) {
+        return Ok((None, None));
+    }
+
+    let v = rc
+        .get_value(path)
+        .map_err(|e| format!("config error at {}: {e}", path))?;
+    if !matches!(v, Value::Object(_)) {
+        return Ok((opt_u64(rc, path)?, None));
+    }
+
+    let mut default = None;
+    let mut overrides = BTreeMap::new();
+
+    for key in rc.get_keys(path).unwrap_or_default() {
+        let base = format!("{path}.{key}");
+        let secs = opt_u64(rc, &base)?;
+
+        if key.trim().eq_ignore_ascii_case("default") {
+            default = secs;
+            continue;
+        }
+
+        match InhibitSource::parse(&key) {
+            Some(InhibitSource::Manual) => {
+                return Err(format!(
+                    "config error at {}: manual pauses end on their own (`pause for`/`pause until`)",
+                    base
+                ));
+            }
+            Some(src) => {
+                if let Some(secs) = secs {
+                    overrides.insert(src, secs);
+                }
+            }
+            None => {
+                return Err(format!(
+                    "config error at {}: unknown inhibit source (expected default, apps, media, capture, logind, screensaver or compositor)",
+                    base
+                ));
+            }
+        }
+    }
+
+    Ok((default, Some(overrides)))
+}
+
+/// `critical_battery:` block; every key is optional, so an empty block enables
+/// the defaults (hibernate at 5%, notify 30s before).
+///
+/// ```text
+/// critical_battery:
+///   threshold 5
+///   command "systemctl hibernate"
+///   notification "Battery critical, hibernating in 30s"
+///   notify_seconds_before 30
+/// end
+/// ```
+fn opt_critical_battery(rc: &RuneConfig, path: &str) -> Result<Option<CriticalBattery>, String> {
+    if !rc.has(path) {
+        return Ok(None);
+    }
+
+    let threshold = opt_u64(rc, format!("{path}.threshold"))?
+        .unwrap_or(CriticalBattery::DEFAULT_THRESHOLD_PERCENT as u64);
+    if threshold > 100 {
+        return Err(format!(
+            "config error at {path}.threshold: expected a percentage (0-100), got {threshold}"
+        ));
+    }
+
+    Ok(Some(CriticalBattery {
+        threshold_percent: threshold as u8,
+        command: opt_string(rc, format!("{path}.command"))?.filter(|c| !c.trim().is_empty()),
+        notification: opt_string(rc, format!("{path}.notification"))?,
+        notify_seconds_before: opt_u64(rc, format!("{path}.notify_seconds_before"))?
+            .unwrap_or(CriticalBattery::DEFAULT_NOTIFY_SECONDS_BEFORE),
+    }))
+}
+
 fn get_vec_pattern(
     rc: &RuneConfig,
     path: &str,
@@ -558,19 +953,42 @@ fn log_config_debug(cfg_file: &ConfigFile) {
 
     eventline::debug!("  monitor_media = {:?}", cfg.monitor_media);
     eventline::debug!("  ignore_remote_media = {:?}", cfg.ignore_remote_media);
+    eventline::debug!("  media_backend = {}", cfg.media_backend.as_str());
+    eventline::debug!("  lid_policy = {}", cfg.lid_policy.as_str());
+    eventline::debug!("  screensaver_inhibit = {:?}", cfg.screensaver_inhibit);
+    eventline::debug!("  compositor_inhibit = {:?}", cfg.compositor_inhibit);
+    eventline::debug!("  logind_inhibit_delay = {:?}", cfg.logind_inhibit_delay);
     eventline::debug!("  media_blacklist = {:?}", cfg.media_blacklist);
+    eventline::debug!("  monitor_capture = {:?}", cfg.monitor_capture);
+    eventline::debug!("  capture_blacklist = {:?}", cfg.capture_blacklist);
 
     eventline::debug!("  debounce_seconds = {:?}", cfg.debounce_seconds);
+    eventline::debug!("  compositor_idle_timers = {:?}", cfg.compositor_idle_timers);
 
     eventline::debug!("  notify_on_unpause = {:?}", cfg.notify_on_unpause);
     eventline::debug!("  notify_before_action = {:?}", cfg.notify_before_action);
 
+    eventline::debug!("  lock_before_sleep = {:?}", cfg.lock_before_sleep);
+
     eventline::debug!("  inhibit_apps = {:?}", cfg.inhibit_apps);
+    for rule in &cfg.inhibit_rules {
+        eventline::debug!("  inhibit_rule {}", rule);
+    }
+    for (src, scope) in &cfg.inhibit_scopes {
+        eventline::debug!("  inhibit_scope {} = {}", src.as_str(), scope);
+    }
+    eventline::debug!("  max_inhibit_seconds = {:?}", cfg.max_inhibit_seconds);
+    for (src, secs) in &cfg.max_inhibit_overrides {
+        eventline::debug!("  max_inhibit_seconds {} = {}", src.as_str(), secs);
+    }
+    eventline::debug!("  notify_on_inhibit_expiry = {:?}", cfg.notify_on_inhibit_expiry);
+    eventline::debug!("  critical_battery = {:?}", cfg.critical_battery);
 
     eventline::debug!("Plan sources:");
     eventline::debug!("  desktop steps = {}", cfg.plan_desktop.len());
     eventline::debug!("  ac steps      = {}", cfg.plan_ac.len());
     eventline::debug!("  battery steps = {}", cfg.plan_battery.len());
+    eventline::debug!("  docked steps  = {}", cfg.plan_docked.len());
 
     eventline::debug!("Desktop plan (enabled steps):");
     dump_plan(&cfg.plan_desktop);
@@ -585,6 +1003,20 @@ fn log_config_debug(cfg_file: &ConfigFile) {
         dump_plan(&cfg.plan_battery);
     }
 
+    if !cfg.plan_docked.is_empty() {
+        eventline::debug!("Docked plan (enabled steps):");
+        dump_plan(&cfg.plan_docked);
+    }
+
+    for tier in &cfg.battery_tiers {
+        eventline::debug!(
+            "Battery tier {} (below {}%, overrides):",
+            tier.name,
+            tier.below_percent
+        );
+        dump_plan(&tier.plan);
+    }
+
     if !cfg_file.profiles.is_empty() {
         eventline::debug!("Profiles:");
         for p in &cfg_file.profiles {
@@ -603,6 +1035,7 @@ fn dump_plan(plan: &[PlanStep]) {
             && step.resume_command.is_none()
             && step.notification.is_none()
             && !(step.is_lock() && step.use_loginctl)
+            && !step.is_native_sleep()
         {
             continue;
         }
@@ -615,6 +1048,9 @@ fn dump_plan(plan: &[PlanStep]) {
             PlanStepKind::LockScreen => "lock_screen".into(),
             PlanStepKind::Dpms => "dpms".into(),
             PlanStepKind::Suspend => "suspend".into(),
+            PlanStepKind::Hibernate => "hibernate".into(),
+            PlanStepKind::HybridSleep => "hybrid_sleep".into(),
+            PlanStepKind::SuspendThenHibernate => "suspend_then_hibernate".into(),
             PlanStepKind::Custom(s) => s.clone(),
         };
 
@@ -633,6 +1069,9 @@ fn dump_plan(plan: &[PlanStep]) {
         if step.is_lock() && step.use_loginctl {
             line.push_str(", use_loginctl=true");
         }
+        if step.is_native_sleep() {
+            line.push_str(", native=login1");
+        }
         if let Some(notification) = &step.notification {
             line.push_str(&format!(", notification=\"{}\"", notification));
             if let Some(sec) = step.notify_seconds_before {
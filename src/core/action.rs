@@ -18,21 +18,30 @@ pub enum Action {
         message: String,
     },
 
-    /// Request lock via loginctl/login1 (optional integration).
+    /// Request lock via login1 (`org.freedesktop.login1.Session.Lock`).
     LockSession,
 
     /// Lock-screen action: run the locker command and (optionally) also lock-session.
     ///
     /// The daemon should run `command` BLOCKING and only consider the lock "ended"
-    /// once the process exits.
+    /// once the process exits. The session's LockedHint follows the locker's lifetime.
     RunLockScreen {
         command: String,
         use_loginctl: bool,
     },
 
-    /// Request system suspend (runtime decides command/system call).
+    /// Request system suspend via login1 (`org.freedesktop.login1.Manager.Suspend`).
     Suspend,
 
+    /// Request hibernate via login1 (falls back to suspend if unavailable).
+    Hibernate,
+
+    /// Request hybrid-sleep via login1 (falls back to suspend if unavailable).
+    HybridSleep,
+
+    /// Request suspend-then-hibernate via login1 (falls back to suspend if unavailable).
+    SuspendThenHibernate,
+
     /// For debugging / testing: no-op marker.
     #[cfg(test)]
     Noop,
@@ -1,6 +1,7 @@
 // Author: Dustin Pilgrim
 // License: MIT
 
+use std::collections::BTreeMap;
 use std::fmt;
 use regex::Regex;
 
@@ -19,12 +20,153 @@ impl Default for ProfileMode {
     }
 }
 
+/// Where media playback state comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MediaBackend {
+    /// Poll `pactl list sink-inputs`.
+    #[default]
+    Pactl,
+    /// Follow MPRIS players on the session bus.
+    Mpris,
+    /// Union of both (players are deduped by pid where possible).
+    Both,
+}
+
+impl MediaBackend {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "pactl" => Some(MediaBackend::Pactl),
+            "mpris" => Some(MediaBackend::Mpris),
+            "both" => Some(MediaBackend::Both),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MediaBackend::Pactl => "pactl",
+            MediaBackend::Mpris => "mpris",
+            MediaBackend::Both => "both",
+        }
+    }
+
+    pub fn uses_pactl(self) -> bool {
+        matches!(self, MediaBackend::Pactl | MediaBackend::Both)
+    }
+
+    pub fn uses_mpris(self) -> bool {
+        matches!(self, MediaBackend::Mpris | MediaBackend::Both)
+    }
+}
+
+/// What closing the lid does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LidPolicy {
+    /// Stop idle management until the lid opens.
+    Pause,
+    /// Lock right away; idle timing keeps running.
+    Lock,
+    Suspend,
+    /// Pause, unless external displays keep the laptop in use (docked plan).
+    #[default]
+    IgnoreWhenDocked,
+}
+
+impl LidPolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().replace('-', "_").to_ascii_lowercase().as_str() {
+            "pause" => Some(LidPolicy::Pause),
+            "lock" => Some(LidPolicy::Lock),
+            "suspend" => Some(LidPolicy::Suspend),
+            "ignore_when_docked" => Some(LidPolicy::IgnoreWhenDocked),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LidPolicy::Pause => "pause",
+            LidPolicy::Lock => "lock",
+            LidPolicy::Suspend => "suspend",
+            LidPolicy::IgnoreWhenDocked => "ignore_when_docked",
+        }
+    }
+}
+
+/// Something that can inhibit the plan; each one can carry its own scope.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InhibitSource {
+    Manual,
+    Apps,
+    Media,
+    Capture,
+    Logind,
+    /// org.freedesktop.ScreenSaver holders on the session bus.
+    ScreenSaver,
+    /// Wayland surfaces holding a compositor idle inhibitor (zwp_idle_inhibit).
+    Compositor,
+}
+
+impl InhibitSource {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().replace('-', "_").to_ascii_lowercase().as_str() {
+            "manual" => Some(InhibitSource::Manual),
+            "apps" => Some(InhibitSource::Apps),
+            "media" => Some(InhibitSource::Media),
+            "capture" => Some(InhibitSource::Capture),
+            "logind" => Some(InhibitSource::Logind),
+            "screensaver" => Some(InhibitSource::ScreenSaver),
+            "compositor" => Some(InhibitSource::Compositor),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            InhibitSource::Manual => "manual",
+            InhibitSource::Apps => "apps",
+            InhibitSource::Media => "media",
+            InhibitSource::Capture => "capture",
+            InhibitSource::Logind => "logind",
+            InhibitSource::ScreenSaver => "screensaver",
+            InhibitSource::Compositor => "compositor",
+        }
+    }
+}
+
+/// Which steps an active inhibitor holds back. Step names use trigger syntax
+/// (`suspend`, `lock_screen`, a custom block name, ...).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum InhibitScope {
+    /// Pause the whole plan.
+    #[default]
+    All,
+    /// Steps before this one still run; the plan waits here, timer frozen.
+    UpTo(String),
+    /// Skip these steps while active; the rest of the plan runs on.
+    Steps(Vec<String>),
+}
+
+impl fmt::Display for InhibitScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InhibitScope::All => write!(f, "all"),
+            InhibitScope::UpTo(step) => write!(f, "up to {step}"),
+            InhibitScope::Steps(steps) => write!(f, "[{}]", steps.join(", ")),
+        }
+    }
+}
+
 /// Which plan source should be active right now.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PlanSource {
     Desktop,
     Ac,
     Battery,
+    /// On battery below a tier's threshold; carries that tier's `below` percent.
+    BatteryBelow(u8),
+    /// Lid closed on external displays.
+    Docked,
 }
 
 /// What kind of step this is in the ordered execution plan.
@@ -35,6 +177,9 @@ pub enum PlanStepKind {
     LockScreen,
     Dpms,
     Suspend,
+    Hibernate,
+    HybridSleep,
+    SuspendThenHibernate,
 
     /// Future-proofing: arbitrary custom blocks.
     Custom(String),
@@ -69,11 +214,33 @@ pub struct PlanStep {
 }
 
 impl PlanStep {
-    /// Enabled if it has a command OR is a lock step using loginctl.
+    /// Enabled if it has a command, is a lock step using loginctl, or is a
+    /// command-less sleep step (native login1 suspend/hibernate/...).
     ///
     /// NOTE: `timeout_seconds == 0` is *not* disabled; it's an instant one-shot.
     pub fn enabled(&self) -> bool {
-        self.command.is_some() || (self.is_lock() && self.use_loginctl)
+        self.command.is_some() || (self.is_lock() && self.use_loginctl) || self.is_native_sleep()
+    }
+
+    /// Sleep step without a command: Stasis sleeps through login1 itself.
+    ///
+    /// A suspend step also needs a non-zero timeout, so the legacy default-order
+    /// plan (which always carries an empty suspend slot) never suspends
+    /// instantly on startup.
+    pub fn is_native_sleep(&self) -> bool {
+        let legacy_slot = self.kind == PlanStepKind::Suspend && self.timeout_seconds == 0;
+        self.is_sleep() && self.command.is_none() && !legacy_slot
+    }
+
+    /// Suspend, hibernate, hybrid-sleep or suspend-then-hibernate.
+    pub fn is_sleep(&self) -> bool {
+        matches!(
+            self.kind,
+            PlanStepKind::Suspend
+                | PlanStepKind::Hibernate
+                | PlanStepKind::HybridSleep
+                | PlanStepKind::SuspendThenHibernate
+        )
     }
 
     /// Instant one-shot step: fires immediately once when the plan starts.
@@ -122,6 +289,83 @@ impl Pattern {
     }
 }
 
+/// Structured app-inhibit rule. Every field that is set must hold for the same
+/// window (AND); a config inhibits if any rule matches (OR).
+///
+/// `inhibit_apps` entries are shorthand for rules with only `app_id` set.
+#[derive(Debug, Clone, Default)]
+pub struct InhibitRule {
+    /// Block name from `inhibit_rules:`; `None` for `inhibit_apps` shorthand.
+    pub name: Option<String>,
+    pub app_id: Option<Pattern>,
+    /// Literals match a case-insensitive substring; regex sees the raw title.
+    pub title: Option<Pattern>,
+    pub fullscreen: Option<bool>,
+    pub focused: Option<bool>,
+    pub workspace_visible: Option<bool>,
+}
+
+impl InhibitRule {
+    pub fn app(pattern: Pattern) -> Self {
+        Self {
+            app_id: Some(pattern),
+            ..Self::default()
+        }
+    }
+
+    /// `inhibit_apps` shorthand followed by the structured rules.
+    pub fn combined(apps: &[Pattern], rules: &[InhibitRule]) -> Vec<InhibitRule> {
+        apps.iter()
+            .cloned()
+            .map(InhibitRule::app)
+            .chain(rules.iter().cloned())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.app_id.is_none()
+            && self.title.is_none()
+            && self.fullscreen.is_none()
+            && self.focused.is_none()
+            && self.workspace_visible.is_none()
+    }
+
+    /// Only constrains app_id, so it can be checked without window properties.
+    pub fn app_only(&self) -> Option<&Pattern> {
+        let props = self.title.is_some()
+            || self.fullscreen.is_some()
+            || self.focused.is_some()
+            || self.workspace_visible.is_some();
+        if props { None } else { self.app_id.as_ref() }
+    }
+}
+
+impl fmt::Display for InhibitRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some(p) = &self.app_id {
+            parts.push(format!("app_id={p}"));
+        }
+        if let Some(p) = &self.title {
+            parts.push(format!("title={p}"));
+        }
+        if let Some(v) = self.fullscreen {
+            parts.push(format!("fullscreen={v}"));
+        }
+        if let Some(v) = self.focused {
+            parts.push(format!("focused={v}"));
+        }
+        if let Some(v) = self.workspace_visible {
+            parts.push(format!("workspace_visible={v}"));
+        }
+
+        match &self.name {
+            Some(name) => write!(f, "{name}({})", parts.join(" ")),
+            None => write!(f, "{}", parts.join(" ")),
+        }
+    }
+}
+
 // NOTE: We cannot derive Eq/PartialEq for Config/PartialConfig anymore because Regex
 // doesn't implement those traits. Keep Debug+Clone; this is enough for your daemon flow.
 #[derive(Debug, Clone)]
@@ -131,19 +375,58 @@ pub struct Config {
 
     pub monitor_media: bool,
     pub ignore_remote_media: bool,
+    pub media_backend: MediaBackend,
+    pub lid_policy: LidPolicy,
+
+    /// Own org.freedesktop.ScreenSaver on the session bus and honor its Inhibit calls.
+    pub screensaver_inhibit: bool,
+
+    /// Honor compositor idle inhibitors (seen through ext-idle-notify v2).
+    pub compositor_inhibit: bool,
+
+    /// Honor logind "idle" inhibitors in delay mode too (block mode is always honored).
+    pub logind_inhibit_delay: bool,
 
     /// Media sources/apps to ignore for media inhibit (case-insensitive; loader normalizes).
     pub media_blacklist: Vec<Pattern>,
 
+    /// Inhibit while something records from a microphone or opens a camera.
+    pub monitor_capture: bool,
+    /// Capturing apps to ignore (e.g. always-on voice assistants).
+    pub capture_blacklist: Vec<Pattern>,
+
     /// Debounce window in seconds.
     pub debounce_seconds: u64,
 
+    /// Time steps with the compositor's idle notifications (one per step)
+    /// instead of our own clock.
+    pub compositor_idle_timers: bool,
+
     pub notify_on_unpause: bool,
     pub notify_before_action: bool,
 
+    /// Hold a login1 sleep delay lock and run the lock step (or
+    /// `pre_suspend_command`) on PrepareForSleep before letting the system sleep.
+    pub lock_before_sleep: bool,
+
     /// Process/class patterns or names that should inhibit idle behavior.
     pub inhibit_apps: Vec<Pattern>,
 
+    /// Structured rules matching window title/fullscreen/focus/visibility too.
+    pub inhibit_rules: Vec<InhibitRule>,
+
+    /// Per-source scopes; sources not listed pause the whole plan.
+    pub inhibit_scopes: BTreeMap<InhibitSource, InhibitScope>,
+
+    /// Inhibitors left untouched this long with no user activity stop counting
+    /// (0 = never); overrides are per source.
+    pub max_inhibit_seconds: u64,
+    pub max_inhibit_overrides: BTreeMap<InhibitSource, u64>,
+    pub notify_on_inhibit_expiry: bool,
+
+    /// `None` unless a `critical_battery` block is configured.
+    pub critical_battery: Option<CriticalBattery>,
+
     // ---- legacy named blocks (still useful for config authoring) ----
     pub startup: ActionBlock,
     pub brightness: ActionBlock,
@@ -155,6 +438,9 @@ pub struct Config {
     pub plan_desktop: Vec<PlanStep>,
     pub plan_ac: Vec<PlanStep>,
     pub plan_battery: Vec<PlanStep>,
+    pub plan_docked: Vec<PlanStep>,
+    /// Sorted by threshold, tightest first.
+    pub battery_tiers: Vec<BatteryTier>,
 
     // ---- active plan consumed by manager ----
     pub plan: Vec<PlanStep>,
@@ -167,14 +453,31 @@ impl Config {
 
             monitor_media: false,
             ignore_remote_media: false,
+            media_backend: MediaBackend::Pactl,
+            lid_policy: LidPolicy::IgnoreWhenDocked,
+            screensaver_inhibit: false,
+            compositor_inhibit: false,
+            logind_inhibit_delay: false,
             media_blacklist: Vec::new(),
 
+            monitor_capture: false,
+            capture_blacklist: Vec::new(),
+
             debounce_seconds: 0,
+            compositor_idle_timers: false,
 
             notify_on_unpause: false,
             notify_before_action: false,
 
+            lock_before_sleep: false,
+
             inhibit_apps: Vec::new(),
+            inhibit_rules: Vec::new(),
+            inhibit_scopes: BTreeMap::new(),
+            max_inhibit_seconds: 0,
+            max_inhibit_overrides: BTreeMap::new(),
+            notify_on_inhibit_expiry: false,
+            critical_battery: None,
 
             startup: ActionBlock::disabled(),
             brightness: ActionBlock::disabled(),
@@ -185,11 +488,29 @@ impl Config {
             plan_desktop: Vec::new(),
             plan_ac: Vec::new(),
             plan_battery: Vec::new(),
+            plan_docked: Vec::new(),
+            battery_tiers: Vec::new(),
 
             plan: Vec::new(),
         }
     }
 
+    /// Scope `src` inhibits with; unlisted sources pause the whole plan.
+    pub fn inhibit_scope(&self, src: InhibitSource) -> &InhibitScope {
+        static ALL: InhibitScope = InhibitScope::All;
+        self.inhibit_scopes.get(&src).unwrap_or(&ALL)
+    }
+
+    /// How long `src` may inhibit without user activity; `None` means forever.
+    pub fn max_inhibit_ms(&self, src: InhibitSource) -> Option<u64> {
+        let secs = self
+            .max_inhibit_overrides
+            .get(&src)
+            .copied()
+            .unwrap_or(self.max_inhibit_seconds);
+        (secs > 0).then(|| secs.saturating_mul(1000))
+    }
+
     /// Back-compat: if we have no explicit plans, build desktop plan from legacy blocks.
     pub fn rebuild_plan_default_order(&mut self) {
         if !self.plan_desktop.is_empty() {
@@ -251,16 +572,39 @@ impl Config {
         self.plan_desktop = plan;
     }
 
+    /// Plan source while discharging at `percent`: the tightest tier whose
+    /// threshold is above it, else plain battery.
+    pub fn battery_source(&self, percent: Option<u8>) -> PlanSource {
+        percent
+            .and_then(|p| self.battery_tiers.iter().find(|t| p < t.below_percent))
+            .map_or(PlanSource::Battery, |t| PlanSource::BatteryBelow(t.below_percent))
+    }
+
+    pub fn battery_tier(&self, below_percent: u8) -> Option<&BatteryTier> {
+        self.battery_tiers
+            .iter()
+            .find(|t| t.below_percent == below_percent)
+    }
+
     /// Select which plan source is active (after profile application).
     pub fn select_plan_source(&mut self, src: PlanSource) {
-        let selected = match src {
-            PlanSource::Desktop => &self.plan_desktop,
-            PlanSource::Ac => &self.plan_ac,
-            PlanSource::Battery => &self.plan_battery,
+        self.plan = match src {
+            PlanSource::Desktop => self.plan_desktop.clone(),
+            PlanSource::Ac => self.plan_ac.clone(),
+            PlanSource::Battery => self.plan_battery.clone(),
+            // A tier only overrides the battery steps it names.
+            PlanSource::BatteryBelow(below) => {
+                let mut plan = self.plan_battery.clone();
+                if let Some(tier) = self.battery_tier(below) {
+                    merge_plan(&mut plan, tier.plan.clone());
+                }
+                plan
+            }
+            // Docked laptops are usually plugged in: no docked plan means the AC one.
+            PlanSource::Docked if self.plan_docked.is_empty() => self.plan_ac.clone(),
+            PlanSource::Docked => self.plan_docked.clone(),
         };
 
-        self.plan = selected.clone();
-
         // If selected plan empty, fall back to desktop plan.
         if self.plan.is_empty() {
             self.plan = self.plan_desktop.clone();
@@ -332,6 +676,39 @@ impl LockBlock {
     }
 }
 
+/// Last-resort action on low battery; runs even while inhibited or paused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CriticalBattery {
+    /// Fire at or below this charge (percent) while discharging.
+    pub threshold_percent: u8,
+
+    /// Shell command to run instead of the default login1 hibernate.
+    pub command: Option<String>,
+
+    pub notification: Option<String>,
+
+    /// Grace period between the notification and the action.
+    pub notify_seconds_before: u64,
+}
+
+impl CriticalBattery {
+    pub const DEFAULT_THRESHOLD_PERCENT: u8 = 5;
+    pub const DEFAULT_NOTIFY_SECONDS_BEFORE: u64 = 30;
+
+    /// What firing does, for logs and `info`.
+    pub fn action_label(&self) -> &str {
+        self.command.as_deref().unwrap_or("hibernate")
+    }
+}
+
+/// Battery plan used below a charge threshold, e.g. `battery_low: below 20`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatteryTier {
+    pub name: String,
+    pub below_percent: u8,
+    pub plan: Vec<PlanStep>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Profile {
     pub name: String,
@@ -346,20 +723,40 @@ pub struct PartialConfig {
 
     pub monitor_media: Option<bool>,
     pub ignore_remote_media: Option<bool>,
+    pub media_backend: Option<MediaBackend>,
+    pub lid_policy: Option<LidPolicy>,
+
+    pub screensaver_inhibit: Option<bool>,
+    pub compositor_inhibit: Option<bool>,
+    pub logind_inhibit_delay: Option<bool>,
 
     pub media_blacklist: Option<Vec<Pattern>>,
 
+    pub monitor_capture: Option<bool>,
+    pub capture_blacklist: Option<Vec<Pattern>>,
+
     pub debounce_seconds: Option<u64>,
+    pub compositor_idle_timers: Option<bool>,
 
     pub notify_on_unpause: Option<bool>,
     pub notify_before_action: Option<bool>,
 
+    pub lock_before_sleep: Option<bool>,
+
     pub inhibit_apps: Option<Vec<Pattern>>,
+    pub inhibit_rules: Option<Vec<InhibitRule>>,
+    pub inhibit_scopes: Option<BTreeMap<InhibitSource, InhibitScope>>,
+    pub max_inhibit_seconds: Option<u64>,
+    pub max_inhibit_overrides: Option<BTreeMap<InhibitSource, u64>>,
+    pub notify_on_inhibit_expiry: Option<bool>,
+    pub critical_battery: Option<CriticalBattery>,
 
     // plan sources (NEW) — profiles can override/extend them
     pub plan_desktop: Option<Vec<PlanStep>>,
     pub plan_ac: Option<Vec<PlanStep>>,
     pub plan_battery: Option<Vec<PlanStep>>,
+    pub plan_docked: Option<Vec<PlanStep>>,
+    pub battery_tiers: Option<Vec<BatteryTier>>,
 
     // legacy blocks (optional)
     pub startup: Option<ActionBlock>,
@@ -387,6 +784,20 @@ fn merge_plan(base: &mut Vec<PlanStep>, overlay: Vec<PlanStep>) {
     }
 }
 
+/// Overlay merge by tier name; the result stays sorted by threshold.
+fn merge_battery_tiers(base: &mut Vec<BatteryTier>, overlay: &[BatteryTier]) {
+    for tier in overlay {
+        match base.iter_mut().find(|b| b.name == tier.name) {
+            Some(b) => {
+                b.below_percent = tier.below_percent;
+                merge_plan(&mut b.plan, tier.plan.clone());
+            }
+            None => base.push(tier.clone()),
+        }
+    }
+    base.sort_by_key(|t| t.below_percent);
+}
+
 impl PartialConfig {
     pub fn apply_to(&self, base: &mut Config, mode: ProfileMode) {
         // ---- globals ----
@@ -400,14 +811,39 @@ impl PartialConfig {
         if let Some(v) = self.ignore_remote_media {
             base.ignore_remote_media = v;
         }
+        if let Some(v) = self.media_backend {
+            base.media_backend = v;
+        }
+        if let Some(v) = self.lid_policy {
+            base.lid_policy = v;
+        }
+
+        if let Some(v) = self.screensaver_inhibit {
+            base.screensaver_inhibit = v;
+        }
+        if let Some(v) = self.compositor_inhibit {
+            base.compositor_inhibit = v;
+        }
+        if let Some(v) = self.logind_inhibit_delay {
+            base.logind_inhibit_delay = v;
+        }
 
         if let Some(v) = &self.media_blacklist {
             base.media_blacklist = v.clone();
         }
+        if let Some(v) = self.monitor_capture {
+            base.monitor_capture = v;
+        }
+        if let Some(v) = &self.capture_blacklist {
+            base.capture_blacklist = v.clone();
+        }
 
         if let Some(v) = self.debounce_seconds {
             base.debounce_seconds = v;
         }
+        if let Some(v) = self.compositor_idle_timers {
+            base.compositor_idle_timers = v;
+        }
 
         if let Some(v) = self.notify_on_unpause {
             base.notify_on_unpause = v;
@@ -416,9 +852,33 @@ impl PartialConfig {
             base.notify_before_action = v;
         }
 
+        if let Some(v) = self.lock_before_sleep {
+            base.lock_before_sleep = v;
+        }
+
         if let Some(v) = &self.inhibit_apps {
             base.inhibit_apps = v.clone();
         }
+        if let Some(v) = &self.inhibit_rules {
+            base.inhibit_rules = v.clone();
+        }
+        if let Some(v) = &self.inhibit_scopes {
+            // Per source, so a profile can rescope one inhibitor and keep the rest.
+            base.inhibit_scopes
+                .extend(v.iter().map(|(k, scope)| (*k, scope.clone())));
+        }
+        if let Some(v) = self.max_inhibit_seconds {
+            base.max_inhibit_seconds = v;
+        }
+        if let Some(v) = &self.max_inhibit_overrides {
+            base.max_inhibit_overrides.extend(v);
+        }
+        if let Some(v) = self.notify_on_inhibit_expiry {
+            base.notify_on_inhibit_expiry = v;
+        }
+        if let Some(v) = &self.critical_battery {
+            base.critical_battery = Some(v.clone());
+        }
 
         // ---- legacy blocks ----
         if let Some(v) = &self.startup {
@@ -449,6 +909,12 @@ impl PartialConfig {
                 if let Some(v) = &self.plan_battery {
                     base.plan_battery = v.clone();
                 }
+                if let Some(v) = &self.plan_docked {
+                    base.plan_docked = v.clone();
+                }
+                if let Some(v) = &self.battery_tiers {
+                    base.battery_tiers = v.clone();
+                }
             }
             ProfileMode::Overlay => {
                 if let Some(v) = &self.plan_desktop {
@@ -460,6 +926,12 @@ impl PartialConfig {
                 if let Some(v) = &self.plan_battery {
                     merge_plan(&mut base.plan_battery, v.clone());
                 }
+                if let Some(v) = &self.plan_docked {
+                    merge_plan(&mut base.plan_docked, v.clone());
+                }
+                if let Some(v) = &self.battery_tiers {
+                    merge_battery_tiers(&mut base.battery_tiers, v);
+                }
             }
         }
 
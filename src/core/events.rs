@@ -19,6 +19,22 @@ pub enum PowerState {
     OnBattery,
 }
 
+/// Charge of the system battery (UPower DisplayDevice or sysfs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatteryStatus {
+    pub percent: u8,
+    pub discharging: bool,
+    /// Seconds until empty while discharging, when the source can estimate it.
+    pub time_to_empty_secs: Option<u64>,
+}
+
+/// An application holding an idle inhibitor through a D-Bus API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InhibitHolder {
+    pub app: String,
+    pub reason: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Event {
     Tick {
@@ -30,6 +46,21 @@ pub enum Event {
         now_ms: u64,
     },
 
+    /// The compositor's idle timer for plan step `step` ran out
+    /// (`compositor_idle_timers`). Timers from an earlier cycle `epoch` are stale.
+    IdleReached {
+        epoch: u64,
+        step: usize,
+        now_ms: u64,
+    },
+    /// Whether the compositor's ext_idle_notifier_v1 is bound. Without it
+    /// there is no activity source and `compositor_idle_timers` falls back
+    /// to our own clock.
+    IdleNotifierChanged {
+        available: bool,
+        now_ms: u64,
+    },
+
     MediaStateChanged {
         state: MediaState,
         now_ms: u64,
@@ -40,12 +71,27 @@ pub enum Event {
         now_ms: u64,
     },
 
+    BatteryChanged {
+        battery: BatteryStatus,
+        now_ms: u64,
+    },
+
     LidClosed {
         now_ms: u64,
     },
     LidOpened {
         now_ms: u64,
     },
+    /// logind's `Docked`: a docking station or external display is attached.
+    DockChanged {
+        docked: bool,
+        now_ms: u64,
+    },
+    /// Connected `wl_output`s other than the built-in panel.
+    ExternalOutputs {
+        count: usize,
+        now_ms: u64,
+    },
 
     SessionLocked {
         now_ms: u64,
@@ -95,6 +141,28 @@ pub enum Event {
         count: u64,
         now_ms: u64,
     },
+    /// Processes currently recording audio or holding a camera open.
+    CaptureInhibitorCount {
+        count: u64,
+        now_ms: u64,
+    },
+    /// logind "idle" inhibitor locks currently honored.
+    LogindInhibitorCount {
+        count: u64,
+        now_ms: u64,
+    },
+
+    /// Current org.freedesktop.ScreenSaver inhibit holders (full snapshot).
+    ScreenSaverInhibitors {
+        holders: Vec<InhibitHolder>,
+        now_ms: u64,
+    },
+    /// A surface idle inhibitor is (or no longer is) keeping the compositor
+    /// from reporting idle.
+    CompositorInhibitor {
+        active: bool,
+        now_ms: u64,
+    },
 }
 
 impl Event {
@@ -102,10 +170,15 @@ impl Event {
         match self {
             Event::Tick { now_ms }
             | Event::UserActivity { now_ms, .. }
+            | Event::IdleReached { now_ms, .. }
+            | Event::IdleNotifierChanged { now_ms, .. }
             | Event::MediaStateChanged { now_ms, .. }
             | Event::PowerChanged { now_ms, .. }
+            | Event::BatteryChanged { now_ms, .. }
             | Event::LidClosed { now_ms }
             | Event::LidOpened { now_ms }
+            | Event::DockChanged { now_ms, .. }
+            | Event::ExternalOutputs { now_ms, .. }
             | Event::SessionLocked { now_ms }
             | Event::SessionUnlocked { now_ms }
             | Event::ManualPause { now_ms }
@@ -116,7 +189,11 @@ impl Event {
             | Event::PrepareForSleep { now_ms }
             | Event::ResumedFromSleep { now_ms }
             | Event::AppInhibitorCount { now_ms, .. }
-            | Event::MediaInhibitorCount { now_ms, .. } => *now_ms,
+            | Event::MediaInhibitorCount { now_ms, .. }
+            | Event::CaptureInhibitorCount { now_ms, .. }
+            | Event::LogindInhibitorCount { now_ms, .. }
+            | Event::ScreenSaverInhibitors { now_ms, .. }
+            | Event::CompositorInhibitor { now_ms, .. } => *now_ms,
         }
     }
 }
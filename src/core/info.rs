@@ -25,6 +25,31 @@ pub struct WaybarInfo {
     pub class: String,
     pub tooltip: String,
     pub profile: Option<String>,
+    /// Omitted on machines without a battery.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battery: Option<WaybarBattery>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WaybarBattery {
+    pub percent: u8,
+    pub discharging: bool,
+    pub time_to_empty: Option<u64>,
+    /// The critical-battery action is counting down or has fired.
+    pub critical: bool,
+}
+
+/// Daemon state exposed as D-Bus properties on `org.stasis.Daemon1`.
+///
+/// Only slow-changing fields belong here; every change becomes a PropertiesChanged signal.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ControlStatus {
+    pub paused: bool,
+    pub locked: bool,
+    pub active_profile: String,
+    pub plan_source: String,
+    /// Next enabled plan step, or empty at the end of the plan.
+    pub next_step: String,
 }
 
 impl InfoSnapshot {
@@ -0,0 +1,120 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::ops::Deref;
+use std::sync::Arc;
+
+use crate::core::{
+    config::{Config, ConfigFile, PlanSource, PlanStep, PlanStepKind},
+    error::{ConfigError, Error},
+    state::State,
+};
+
+use super::Manager;
+
+/// Which `mark_step_fired` groups a step belongs to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(super) struct StepGroup {
+    pub dpms: bool,
+    pub brightness: bool,
+    pub lock: bool,
+}
+
+impl StepGroup {
+    fn of(step: &PlanStep) -> Self {
+        let dpms = match &step.kind {
+            PlanStepKind::Dpms => true,
+            PlanStepKind::Custom(name) => Manager::normalize_trigger_name(name) == "early-dpms",
+            _ => false,
+        };
+        Self {
+            dpms,
+            brightness: matches!(step.kind, PlanStepKind::Brightness),
+            lock: matches!(step.kind, PlanStepKind::LockScreen),
+        }
+    }
+}
+
+/// The config for one profile and plan source, plus what the engine derives
+/// from its plan. Derefs to the `Config`.
+#[derive(Debug, Clone)]
+pub(super) struct Effective {
+    cfg: Config,
+    profile: Option<String>,
+    source: PlanSource,
+    /// One per plan step.
+    pub groups: Vec<StepGroup>,
+    /// First enabled lock step.
+    pub lock_index: Option<usize>,
+    /// First enabled step after the lock step, where the cycle restarts while
+    /// locked; `plan.len()` when there is none.
+    pub post_lock_start: usize,
+}
+
+impl Effective {
+    fn build(cfg_file: &ConfigFile, state: &State) -> Result<Self, Error> {
+        let cfg = cfg_file
+            .effective_for(state.active_profile(), state.plan_source())
+            .ok_or(Error::InvalidConfig(ConfigError::ProfileNotFound))?;
+
+        let groups: Vec<StepGroup> = cfg.plan.iter().map(StepGroup::of).collect();
+        let enabled = |i: &usize| cfg.plan[*i].enabled();
+
+        let lock_index = (0..cfg.plan.len())
+            .filter(enabled)
+            .find(|&i| groups[i].lock);
+        let post_lock_start = lock_index
+            .and_then(|lock| (lock + 1..cfg.plan.len()).find(enabled))
+            .unwrap_or(cfg.plan.len());
+
+        Ok(Self {
+            profile: state.active_profile().map(str::to_string),
+            source: state.plan_source(),
+            groups,
+            lock_index,
+            post_lock_start,
+            cfg,
+        })
+    }
+
+    fn is_for(&self, state: &State) -> bool {
+        self.source == state.plan_source() && self.profile.as_deref() == state.active_profile()
+    }
+}
+
+impl Deref for Effective {
+    type Target = Config;
+
+    fn deref(&self) -> &Config {
+        &self.cfg
+    }
+}
+
+impl Manager {
+    /// The effective config for `state`'s profile and plan source. It is
+    /// rebuilt only when one of those changes (or on `set_config`), not on
+    /// every event.
+    pub(super) fn effective_cfg(&mut self, state: &State) -> Result<Arc<Effective>, Error> {
+        if let Some(eff) = self.cached_effective(state) {
+            return Ok(eff);
+        }
+        let eff = Arc::new(Effective::build(&self.cfg_file, state)?);
+        self.effective = Some(Arc::clone(&eff));
+        Ok(eff)
+    }
+
+    /// Like `effective_cfg`, for callers that can't update the cache.
+    pub(super) fn peek_effective(&self, state: &State) -> Result<Arc<Effective>, Error> {
+        match self.cached_effective(state) {
+            Some(eff) => Ok(eff),
+            None => Effective::build(&self.cfg_file, state).map(Arc::new),
+        }
+    }
+
+    fn cached_effective(&self, state: &State) -> Option<Arc<Effective>> {
+        self.effective
+            .as_ref()
+            .filter(|eff| eff.is_for(state))
+            .cloned()
+    }
+}
@@ -3,25 +3,124 @@
 
 use crate::core::{
     action::Action,
-    config::{Config, PlanSource, PlanStep, PlanStepKind},
+    config::{Config, InhibitScope, InhibitSource, LidPolicy, PlanSource, PlanStep, PlanStepKind},
     error::{ConfigError, Error, StateError},
     events::{Event, MediaState, PowerState},
     state::State,
 };
 
-use super::Manager;
+use super::{Manager, effective::Effective};
+
+/// How a scoped inhibitor treats a plan step it covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum StepHold {
+    /// Wait at this step with its countdown frozen.
+    Hold,
+    /// Pass over it when it comes due.
+    Skip,
+}
+
+/// What handling one event produced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Handled {
+    pub actions: Vec<Action>,
+    /// When the manager next wants a `Tick`; see [`Manager::next_deadline`].
+    pub next_deadline_ms: Option<u64>,
+}
 
 impl Manager {
+    /// `handle_event`, plus the deadline the daemon should sleep until.
+    pub fn handle(&mut self, state: &mut State, event: Event) -> Result<Handled, Error> {
+        let actions = self.handle_event(state, event)?;
+        Ok(Handled {
+            actions,
+            next_deadline_ms: self.next_deadline(state),
+        })
+    }
+
+    /// Earliest time (ms since epoch) a `Tick` would change anything: the next
+    /// step, its notify wait, the critical-battery grace period or an
+    /// inhibitor running out. `None` means only events can move things on.
+    /// May already be past, e.g. a pending instant step.
+    pub fn next_deadline(&self, state: &State) -> Option<u64> {
+        let cfg = self.peek_effective(state).ok()?;
+
+        let inhibitor_expiry = state
+            .active_inhibit_sources()
+            .into_iter()
+            .filter_map(|src| {
+                let since = state.inhibit_since_ms(src)?;
+                Some(since.saturating_add(cfg.max_inhibit_ms(src)?))
+            })
+            .min();
+
+        let critical_battery = cfg
+            .critical_battery
+            .as_ref()
+            .filter(|_| !state.critical_battery_fired())
+            .and_then(|crit| match state.critical_battery_warned_ms() {
+                Some(warned) => {
+                    Some(warned.saturating_add(crit.notify_seconds_before.saturating_mul(1000)))
+                }
+                // Crossed the threshold on the last reading: warn right away.
+                None => state
+                    .battery()
+                    .filter(|b| b.discharging && b.percent <= crit.threshold_percent)
+                    .map(|_| state.battery_ms()),
+            });
+
+        [
+            self.plan_deadline(state, &cfg),
+            critical_battery,
+            inhibitor_expiry,
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+    }
+
+    /// Compositor idle timers for the current cycle when
+    /// `compositor_idle_timers` is on: `(step, idle ms)` per timed step, where
+    /// idle ms counts from the last input and adds up the steps (and notify
+    /// waits) before it. Empty otherwise.
+    pub fn idle_timers(&self, state: &State) -> Vec<(usize, u64)> {
+        let Ok(cfg) = self.peek_effective(state) else {
+            return Vec::new();
+        };
+        if !cfg.compositor_idle_timers {
+            return Vec::new();
+        }
+
+        let mut idle_ms = cfg.debounce_seconds.saturating_mul(1000);
+        let mut out = Vec::new();
+        for (idx, step) in cfg.plan.iter().enumerate().skip(state.cycle_start_idx()) {
+            if !step.enabled() || step.is_instant() {
+                continue;
+            }
+            idle_ms = idle_ms.saturating_add(step.timeout_seconds.saturating_mul(1000));
+            out.push((idx, idle_ms));
+
+            if cfg.notify_before_action && step.notification.is_some() {
+                let wait_ms = step.notify_seconds_before.unwrap_or(0).saturating_mul(1000);
+                idle_ms = idle_ms.saturating_add(wait_ms);
+            }
+        }
+        out
+    }
+
     pub fn handle_event(&mut self, state: &mut State, event: Event) -> Result<Vec<Action>, Error> {
         let now_ms = event.now_ms();
         let cfg = self.effective_cfg(state)?;
 
         state.ensure_plan_len(cfg.plan.len());
         state.set_debounce_seconds(cfg.debounce_seconds);
+        state.set_honor_compositor_inhibit(cfg.compositor_inhibit);
 
-        self.refresh_paused(state, now_ms);
+        let mut out = self.expire_stale_inhibitors(state, &cfg, now_ms);
 
-        let mut out = Vec::new();
+        self.refresh_paused(state, &cfg, now_ms);
+
+        out.extend(self.check_critical_battery(state, &cfg, now_ms));
 
         out.extend(self.maybe_fire_startup_instants(state, &cfg, now_ms));
         self.sync_step_index_after_startup_instants(state, &cfg);
@@ -36,18 +135,33 @@ impl Manager {
                 out.extend(self.maybe_fire_next_step(state, &cfg, now_ms));
             }
 
+            Event::IdleReached { epoch, step, .. } => {
+                // While paused the timers are restarted on resume anyway.
+                if epoch != state.cycle_epoch() || state.paused() {
+                    return Ok(out);
+                }
+                state.mark_idle_reached(step);
+
+                self.advance_past_lock_if_needed(state, &cfg);
+                out.extend(self.maybe_fire_next_step(state, &cfg, now_ms));
+            }
+
+            Event::IdleNotifierChanged { available, .. } => {
+                state.set_idle_notifier(available);
+            }
+
             Event::UserActivity { .. } => {
                 let was_paused = state.paused();
+                state.restart_inhibitor_clocks(now_ms);
 
                 out.extend(self.resume_commands_for_activity(state, &cfg));
 
                 if state.is_locked() {
                     self.advance_past_lock_if_needed(state, &cfg);
 
-                    let post_lock_start = self.first_enabled_step_after_lock(&cfg);
-                    state.restart_post_lock_segment(now_ms, post_lock_start);
+                    state.restart_post_lock_segment(now_ms, cfg.post_lock_start);
 
-                    self.refresh_paused(state, now_ms);
+                    self.refresh_paused(state, &cfg, now_ms);
 
                     if cfg.notify_on_unpause && was_paused && !state.paused() {
                         out.push(Action::Notify {
@@ -59,7 +173,7 @@ impl Manager {
                 }
 
                 state.reset_idle_cycle(now_ms);
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
 
                 self.sync_step_index_after_startup_instants(state, &cfg);
 
@@ -77,7 +191,7 @@ impl Manager {
                     return Err(Error::InvalidState(StateError::AlreadyPaused));
                 }
                 state.set_manually_paused(true);
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
             }
 
             Event::ManualResume { .. } => {
@@ -89,7 +203,7 @@ impl Manager {
                 out.extend(self.resume_commands_for_activity(state, &cfg));
                 state.reset_idle_cycle(now_ms);
 
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
                 self.sync_step_index_after_startup_instants(state, &cfg);
 
                 self.advance_past_lock_if_needed(state, &cfg);
@@ -102,7 +216,7 @@ impl Manager {
                     out.extend(self.resume_commands_for_activity(state, &cfg));
                     state.reset_idle_cycle(now_ms);
 
-                    self.refresh_paused(state, now_ms);
+                    self.refresh_paused(state, &cfg, now_ms);
                     self.sync_step_index_after_startup_instants(state, &cfg);
 
                     if cfg.notify_on_unpause {
@@ -128,18 +242,13 @@ impl Manager {
                         if step.is_instant() {
                             continue;
                         }
-                        if Self::is_lock_step(step) && state.is_locked() {
+                        if cfg.groups[idx].lock && state.is_locked() {
                             continue;
                         }
 
                         let emitted = self.actions_for_plan_step(state, step, &cfg);
                         if !emitted.is_empty() {
-                            let arms_resume = step.resume_command.is_some();
-                            let is_dpms = Self::is_dpms_group(step);
-                            let is_brightness = Self::is_brightness_group(step);
-                            let is_lock = Self::is_lock_step(step);
-
-                            state.mark_step_fired(idx, is_dpms, is_brightness, is_lock, arms_resume);
+                            Self::mark_fired(state, &cfg, idx);
                             emitted_any = true;
                         }
 
@@ -162,12 +271,7 @@ impl Manager {
 
                     let emitted = self.actions_for_plan_step(state, step, &cfg);
                     if !emitted.is_empty() {
-                        let arms_resume = step.resume_command.is_some();
-                        let is_dpms = Self::is_dpms_group(step);
-                        let is_brightness = Self::is_brightness_group(step);
-                        let is_lock = Self::is_lock_step(step);
-
-                        state.mark_step_fired(idx, is_dpms, is_brightness, is_lock, arms_resume);
+                        Self::mark_fired(state, &cfg, idx);
                         state.mark_action_fired(now_ms);
 
                         state.set_step_index(idx + 1);
@@ -200,7 +304,7 @@ impl Manager {
                     out.extend(self.resume_commands_for_activity(state, &cfg));
                     state.reset_idle_cycle(now_ms);
 
-                    self.refresh_paused(state, now_ms);
+                    self.refresh_paused(state, &cfg, now_ms);
                     self.sync_step_index_after_startup_instants(state, &cfg);
 
                     self.advance_past_lock_if_needed(state, &cfg);
@@ -209,46 +313,79 @@ impl Manager {
 
             Event::PrepareForSleep { .. } => {
                 state.set_system_paused(true);
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
+
+                if cfg.lock_before_sleep {
+                    out.extend(self.actions_before_sleep(state, &cfg));
+                }
             }
 
             Event::ResumedFromSleep { .. } => {
                 state.set_system_paused(false);
+                // Still critical after waking: warn and count down again.
+                state.reset_critical_battery();
 
                 out.extend(self.resume_commands_for_activity(state, &cfg));
 
                 if state.is_locked() {
                     self.advance_past_lock_if_needed(state, &cfg);
-                    let post_lock_start = self.first_enabled_step_after_lock(&cfg);
-                    state.restart_post_lock_segment(now_ms, post_lock_start);
+                    state.restart_post_lock_segment(now_ms, cfg.post_lock_start);
                 } else {
                     state.reset_idle_cycle(now_ms);
                     self.sync_step_index_after_startup_instants(state, &cfg);
                 }
 
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
+            }
+
+            Event::BatteryChanged { battery, .. } => {
+                state.set_battery(battery, now_ms);
+
+                // Tiers only tighten while discharging, so a reading that
+                // wobbles around a threshold can't keep restarting the cycle;
+                // the next PowerChanged picks afresh.
+                let src = cfg.battery_source(Some(battery.percent));
+                let tighter = match (state.plan_source(), src) {
+                    (PlanSource::Battery, src @ PlanSource::BatteryBelow(_)) => Some(src),
+                    (PlanSource::BatteryBelow(cur), src @ PlanSource::BatteryBelow(new))
+                        if new < cur =>
+                    {
+                        Some(src)
+                    }
+                    _ => None,
+                };
+                if let Some(src) = tighter {
+                    out.extend(self.switch_plan_source(state, src, now_ms)?);
+                }
             }
 
             Event::LidClosed { .. } => {
-                state.set_system_paused(true);
-                self.refresh_paused(state, now_ms);
+                state.set_lid_closed(true);
+                out.extend(self.sync_docked(state, &cfg, now_ms)?);
+
+                let cfg = self.effective_cfg(state)?;
+                out.extend(self.apply_lid_policy(state, &cfg));
+                self.refresh_paused(state, &cfg, now_ms);
             }
 
             Event::LidOpened { .. } => {
-                state.set_system_paused(false);
+                state.set_lid_closed(false);
+                out.extend(self.sync_docked(state, &cfg, now_ms)?);
+                let cfg = self.effective_cfg(state)?;
+
+                state.set_lid_paused(false);
 
                 out.extend(self.resume_commands_for_activity(state, &cfg));
 
                 if state.is_locked() {
                     self.advance_past_lock_if_needed(state, &cfg);
-                    let post_lock_start = self.first_enabled_step_after_lock(&cfg);
-                    state.restart_post_lock_segment(now_ms, post_lock_start);
+                    state.restart_post_lock_segment(now_ms, cfg.post_lock_start);
                 } else {
                     state.reset_idle_cycle(now_ms);
                     self.sync_step_index_after_startup_instants(state, &cfg);
                 }
 
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
             }
 
             Event::ProfileChanged { name, .. } => {
@@ -274,7 +411,8 @@ impl Manager {
 
                 state.set_app_inhibitor_count(0);
                 state.set_media_inhibitor_count(0);
-                self.refresh_paused(state, now_ms);
+                state.set_capture_inhibitor_count(0);
+                self.refresh_paused(state, &cfg, now_ms);
 
                 state.reset_idle_cycle(now_ms);
                 state.clear_one_shots();
@@ -282,8 +420,9 @@ impl Manager {
                 let cfg = self.effective_cfg(state)?;
                 state.ensure_plan_len(cfg.plan.len());
                 state.set_debounce_seconds(cfg.debounce_seconds);
+                state.set_honor_compositor_inhibit(cfg.compositor_inhibit);
 
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
                 self.sync_step_index_after_startup_instants(state, &cfg);
                 self.advance_past_lock_if_needed(state, &cfg);
 
@@ -291,45 +430,63 @@ impl Manager {
                 self.sync_step_index_after_startup_instants(state, &cfg);
             }
 
-            Event::PowerChanged { state: ps, .. } => {
-                state.set_power_state(ps);
-
-                let src = match ps {
-                    PowerState::OnAC => PlanSource::Ac,
-                    PowerState::OnBattery => PlanSource::Battery,
-                };
-                state.set_plan_source(src);
+            Event::DockChanged { docked, .. } => {
+                state.set_logind_docked(docked);
+                out.extend(self.dock_changed(state, &cfg, now_ms)?);
+            }
 
-                state.reset_idle_cycle(now_ms);
-                state.clear_one_shots();
+            Event::ExternalOutputs { count, .. } => {
+                state.set_external_outputs(count);
+                out.extend(self.dock_changed(state, &cfg, now_ms)?);
+            }
 
-                let cfg = self.effective_cfg(state)?;
-                state.ensure_plan_len(cfg.plan.len());
-                state.set_debounce_seconds(cfg.debounce_seconds);
+            Event::PowerChanged { state: ps, .. } => {
+                state.set_power_state(ps);
 
-                self.refresh_paused(state, now_ms);
-                self.sync_step_index_after_startup_instants(state, &cfg);
-                self.advance_past_lock_if_needed(state, &cfg);
+                // The docked plan outranks AC/battery until the lid opens.
+                if state.plan_source() == PlanSource::Docked {
+                    return Ok(out);
+                }
 
-                out.extend(self.maybe_fire_startup_instants(state, &cfg, now_ms));
-                self.sync_step_index_after_startup_instants(state, &cfg);
+                let src = self.power_plan_source(state, &cfg);
+                out.extend(self.switch_plan_source(state, src, now_ms)?);
             }
 
             Event::AppInhibitorCount { count, .. } => {
                 state.set_app_inhibitor_count(count);
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
             }
 
             Event::MediaInhibitorCount { count, .. } => {
                 state.set_media_inhibitor_count(count);
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
+            }
+
+            Event::CaptureInhibitorCount { count, .. } => {
+                state.set_capture_inhibitor_count(count);
+                self.refresh_paused(state, &cfg, now_ms);
+            }
+
+            Event::LogindInhibitorCount { count, .. } => {
+                state.set_logind_inhibitor_count(count);
+                self.refresh_paused(state, &cfg, now_ms);
+            }
+
+            Event::ScreenSaverInhibitors { holders, .. } => {
+                state.set_screensaver_inhibitors(holders);
+                self.refresh_paused(state, &cfg, now_ms);
+            }
+
+            Event::CompositorInhibitor { active, .. } => {
+                state.set_compositor_inhibitor(active);
+                self.refresh_paused(state, &cfg, now_ms);
             }
 
             Event::MediaStateChanged { state: m, .. } => {
                 let old = self.last_media;
                 self.last_media = m;
 
-                self.refresh_paused(state, now_ms);
+                self.refresh_paused(state, &cfg, now_ms);
 
                 if cfg.notify_on_unpause
                     && matches!(old, MediaState::PlayingLocal | MediaState::PlayingRemote)
@@ -344,17 +501,228 @@ impl Manager {
         Ok(out)
     }
 
-    fn effective_cfg(&self, state: &State) -> Result<Config, Error> {
-        self.cfg_file
-            .effective_for(state.active_profile(), state.plan_source())
-            .ok_or(Error::InvalidConfig(ConfigError::ProfileNotFound))
+    /// New plan, new cycle: restart idle timing and re-fire startup instants.
+    fn switch_plan_source(
+        &mut self,
+        state: &mut State,
+        src: PlanSource,
+        now_ms: u64,
+    ) -> Result<Vec<Action>, Error> {
+        state.set_plan_source(src);
+
+        state.reset_idle_cycle(now_ms);
+        state.clear_one_shots();
+
+        let cfg = self.effective_cfg(state)?;
+        state.ensure_plan_len(cfg.plan.len());
+        state.set_debounce_seconds(cfg.debounce_seconds);
+        state.set_honor_compositor_inhibit(cfg.compositor_inhibit);
+
+        self.refresh_paused(state, &cfg, now_ms);
+        self.sync_step_index_after_startup_instants(state, &cfg);
+        self.advance_past_lock_if_needed(state, &cfg);
+
+        let out = self.maybe_fire_startup_instants(state, &cfg, now_ms);
+        self.sync_step_index_after_startup_instants(state, &cfg);
+        Ok(out)
+    }
+
+    /// AC or battery (tier), whichever the last PowerChanged reported.
+    fn power_plan_source(&self, state: &State, cfg: &Config) -> PlanSource {
+        match state.power_state() {
+            Some(PowerState::OnBattery) => cfg.battery_source(state.battery().map(|b| b.percent)),
+            Some(PowerState::OnAC) | None => PlanSource::Ac,
+        }
+    }
+
+    /// Enter the docked plan when the lid shuts on external displays and
+    /// leave it when that stops being true. Desktops never dock.
+    fn sync_docked(
+        &mut self,
+        state: &mut State,
+        cfg: &Config,
+        now_ms: u64,
+    ) -> Result<Vec<Action>, Error> {
+        let current = state.plan_source();
+        if current == PlanSource::Desktop || state.docked() == (current == PlanSource::Docked) {
+            return Ok(Vec::new());
+        }
+
+        let src = if state.docked() {
+            PlanSource::Docked
+        } else {
+            self.power_plan_source(state, cfg)
+        };
+        eventline::info!("lid: plan source {:?} -> {:?}", current, src);
+        self.switch_plan_source(state, src, now_ms)
+    }
+
+    /// A display or dock came or went; with the lid shut that can flip
+    /// between the docked plan and an `ignore_when_docked` pause.
+    fn dock_changed(
+        &mut self,
+        state: &mut State,
+        cfg: &Config,
+        now_ms: u64,
+    ) -> Result<Vec<Action>, Error> {
+        let out = self.sync_docked(state, cfg, now_ms)?;
+
+        let cfg = self.effective_cfg(state)?;
+        if state.lid_closed() && cfg.lid_policy == LidPolicy::IgnoreWhenDocked {
+            state.set_lid_paused(!state.docked());
+        }
+        self.refresh_paused(state, &cfg, now_ms);
+
+        Ok(out)
+    }
+
+    fn apply_lid_policy(&self, state: &mut State, cfg: &Effective) -> Vec<Action> {
+        match cfg.lid_policy {
+            LidPolicy::Pause => {
+                state.set_lid_paused(true);
+                Vec::new()
+            }
+            LidPolicy::IgnoreWhenDocked => {
+                state.set_lid_paused(!state.docked());
+                Vec::new()
+            }
+            LidPolicy::Lock => {
+                if state.is_locked() {
+                    return Vec::new();
+                }
+                self.lock_step_now(state, cfg, "lid closed")
+                    .unwrap_or_else(|| vec![Action::LockSession])
+            }
+            // The plan's own sleep step, so its command (and pre_suspend_command)
+            // runs; logind's lid handling is blocked meanwhile (see the daemon).
+            LidPolicy::Suspend => cfg
+                .plan
+                .iter()
+                .find(|step| step.enabled() && step.is_sleep())
+                .map_or_else(
+                    || vec![Action::Suspend],
+                    |step| self.actions_for_plan_step(state, step, cfg),
+                ),
+        }
+    }
+
+    /// Stop honoring inhibitors that have outlived their `max_inhibit_seconds`
+    /// with no user activity, e.g. a paused player left open for days.
+    fn expire_stale_inhibitors(&self, state: &mut State, cfg: &Config, now_ms: u64) -> Vec<Action> {
+        let mut out = Vec::new();
+
+        for src in state.active_inhibit_sources() {
+            let (Some(max_ms), Some(since)) =
+                (cfg.max_inhibit_ms(src), state.inhibit_since_ms(src))
+            else {
+                continue;
+            };
+            let held_ms = now_ms.saturating_sub(since);
+            if held_ms < max_ms {
+                continue;
+            }
+
+            state.expire_inhibitor(src);
+            eventline::info!(
+                "inhibit: {} inhibitor held for {}s without user activity; ignoring it until it changes",
+                src.as_str(),
+                held_ms / 1000
+            );
+
+            if cfg.notify_on_inhibit_expiry {
+                out.push(Action::Notify {
+                    message: format!(
+                        "Ignoring the {} inhibitor after {} minutes without activity",
+                        src.as_str(),
+                        held_ms / 60_000
+                    ),
+                });
+            }
+        }
+
+        out
     }
 
-    fn refresh_paused(&self, state: &mut State, now_ms: u64) {
-        let new_paused =
-            state.manually_paused() || state.inhibitors_active() || state.system_paused();
+    /// Low battery beats every inhibitor and pause: notify, wait out the grace
+    /// period, then hibernate (or run the configured command) once per discharge.
+    fn check_critical_battery(&self, state: &mut State, cfg: &Config, now_ms: u64) -> Vec<Action> {
+        let Some(crit) = cfg.critical_battery.as_ref() else {
+            return Vec::new();
+        };
+        let Some(battery) = state
+            .battery()
+            .filter(|b| b.discharging && b.percent <= crit.threshold_percent)
+        else {
+            state.reset_critical_battery();
+            return Vec::new();
+        };
+        if state.critical_battery_fired() {
+            return Vec::new();
+        }
+
+        let mut out = Vec::new();
+
+        let warned_ms = match state.critical_battery_warned_ms() {
+            Some(t) => t,
+            None => {
+                eventline::warn!(
+                    "battery: {}% is at or below {}%; {} in {}s",
+                    battery.percent,
+                    crit.threshold_percent,
+                    crit.action_label(),
+                    crit.notify_seconds_before
+                );
+                state.set_critical_battery_warned_ms(now_ms);
+                out.push(Action::Notify {
+                    message: crit.notification.clone().unwrap_or_else(|| {
+                        format!(
+                            "Battery critical ({}%): {} in {}s",
+                            battery.percent,
+                            crit.action_label(),
+                            crit.notify_seconds_before
+                        )
+                    }),
+                });
+                now_ms
+            }
+        };
+
+        if now_ms < warned_ms.saturating_add(crit.notify_seconds_before.saturating_mul(1000)) {
+            return out;
+        }
+
+        eventline::warn!(
+            "battery: critical at {}%, running {}",
+            battery.percent,
+            crit.action_label()
+        );
+        state.mark_critical_battery_fired();
+        out.push(match &crit.command {
+            Some(command) => Action::RunCommand {
+                command: command.clone(),
+            },
+            None => Action::Hibernate,
+        });
+
+        out
+    }
+
+    fn refresh_paused(&self, state: &mut State, cfg: &Config, now_ms: u64) {
+        state.stamp_inhibitors(now_ms);
+
+        // Scoped sources only hold back their steps; see `step_hold`.
+        let whole_plan = state
+            .active_inhibit_sources()
+            .into_iter()
+            .any(|src| *cfg.inhibit_scope(src) == InhibitScope::All);
+        let new_paused = whole_plan || state.system_paused();
         let was_paused = state.paused();
 
+        if was_paused != new_paused {
+            // The pause freeze below covers any span a scoped hold was tracking.
+            state.replace_step_held_ms(None);
+        }
+
         if !was_paused && new_paused {
             // entering pause
             state.set_pause_started_ms(Some(now_ms));
@@ -369,9 +737,65 @@ impl Manager {
                     state.set_pre_action_notify_ms(state.pre_action_notify_ms().saturating_add(dt));
                 }
             }
+            // Compositor timers kept counting through the pause; start them
+            // over from the current step.
+            state.restart_idle_timers();
         }
 
         state.set_paused(new_paused);
+
+        if !new_paused {
+            self.sync_step_hold(state, cfg, now_ms);
+        }
+    }
+
+    /// Keep a held step's countdown frozen: shift its timers by the span since
+    /// the last check, and stop tracking once nothing holds it.
+    fn sync_step_hold(&self, state: &mut State, cfg: &Config, now_ms: u64) {
+        let held = (state.step_index()..cfg.plan.len())
+            .find(|&idx| cfg.plan[idx].enabled())
+            .and_then(|idx| Self::step_hold(state, cfg, idx))
+            .is_some_and(|(_, hold)| hold == StepHold::Hold);
+
+        if let Some(t0) = state.replace_step_held_ms(held.then_some(now_ms)) {
+            let dt = now_ms.saturating_sub(t0);
+            state.set_step_base_ms(state.step_base_ms().saturating_add(dt));
+            if state.pre_action_notify_sent() {
+                state.set_pre_action_notify_ms(state.pre_action_notify_ms().saturating_add(dt));
+            }
+        }
+    }
+
+    /// Scoped inhibitor holding back plan step `idx`, if any. Holds win over skips.
+    pub(super) fn step_hold(
+        state: &State,
+        cfg: &Config,
+        idx: usize,
+    ) -> Option<(InhibitSource, StepHold)> {
+        let step = cfg.plan.get(idx)?;
+        let mut skip = None;
+
+        for src in state.active_inhibit_sources() {
+            match cfg.inhibit_scope(src) {
+                InhibitScope::All => {}
+                InhibitScope::UpTo(name) => {
+                    let from = cfg
+                        .plan
+                        .iter()
+                        .position(|s| s.enabled() && Self::trigger_matches_step(name, s));
+                    if from.is_some_and(|from| idx >= from) {
+                        return Some((src, StepHold::Hold));
+                    }
+                }
+                InhibitScope::Steps(names) => {
+                    if skip.is_none() && names.iter().any(|n| Self::trigger_matches_step(n, step)) {
+                        skip = Some((src, StepHold::Skip));
+                    }
+                }
+            }
+        }
+
+        skip
     }
 
     pub(super) fn normalize_trigger_name(s: &str) -> String {
@@ -396,6 +820,9 @@ impl Manager {
             PlanStepKind::Brightness => n == "brightness",
             PlanStepKind::LockScreen => n == "lock-screen",
             PlanStepKind::Suspend => n == "suspend",
+            PlanStepKind::Hibernate => n == "hibernate",
+            PlanStepKind::HybridSleep => n == "hybrid-sleep",
+            PlanStepKind::SuspendThenHibernate => n == "suspend-then-hibernate",
             PlanStepKind::Custom(k) => {
                 let k_norm = Self::normalize_trigger_name(k);
                 n == k_norm || n == format!("custom:{k_norm}") || n == format!("custom-{k_norm}")
@@ -415,43 +842,21 @@ impl Manager {
         None
     }
 
-    fn is_lock_step(step: &PlanStep) -> bool {
-        matches!(step.kind, PlanStepKind::LockScreen)
-    }
-
-    fn is_dpms_group(step: &PlanStep) -> bool {
-        match &step.kind {
-            PlanStepKind::Dpms => true,
-            PlanStepKind::Custom(name) => Self::normalize_trigger_name(name) == "early-dpms",
-            _ => false,
-        }
-    }
-
-    fn is_brightness_group(step: &PlanStep) -> bool {
-        matches!(step.kind, PlanStepKind::Brightness)
+    /// `compositor_idle_timers`, while the compositor can actually provide them.
+    pub(super) fn compositor_timed(state: &State, cfg: &Config) -> bool {
+        cfg.compositor_idle_timers && state.idle_notifier()
     }
 
-    fn first_enabled_step_after_lock(&self, cfg: &Config) -> usize {
-        let mut seen_lock = false;
-        for (i, s) in cfg.plan.iter().enumerate() {
-            if !s.enabled() {
-                continue;
-            }
-            if !seen_lock && Self::is_lock_step(s) {
-                seen_lock = true;
-                continue;
-            }
-            if seen_lock {
-                return i;
-            }
-        }
-        cfg.plan.len()
+    fn mark_fired(state: &mut State, cfg: &Effective, idx: usize) {
+        let group = cfg.groups[idx];
+        let arms_resume = cfg.plan[idx].resume_command.is_some();
+        state.mark_step_fired(idx, group.dpms, group.brightness, group.lock, arms_resume);
     }
 
     fn maybe_fire_startup_instants(
         &self,
         state: &mut State,
-        cfg: &Config,
+        cfg: &Effective,
         now_ms: u64,
     ) -> Vec<Action> {
         let mut idx = 0usize;
@@ -478,12 +883,7 @@ impl Manager {
 
             let emitted = self.actions_for_plan_step(state, step, cfg);
             if !emitted.is_empty() {
-                let arms_resume = step.resume_command.is_some();
-                let is_dpms = Self::is_dpms_group(step);
-                let is_brightness = Self::is_brightness_group(step);
-                let is_lock = Self::is_lock_step(step);
-
-                state.mark_step_fired(idx, is_dpms, is_brightness, is_lock, arms_resume);
+                Self::mark_fired(state, cfg, idx);
 
                 state.mark_action_fired(now_ms);
                 state.set_pre_action_notify_sent(false);
@@ -528,7 +928,7 @@ impl Manager {
         }
     }
 
-    fn advance_past_lock_if_needed(&self, state: &mut State, cfg: &Config) {
+    fn advance_past_lock_if_needed(&self, state: &mut State, cfg: &Effective) {
         if !state.is_locked() {
             return;
         }
@@ -538,7 +938,7 @@ impl Manager {
             idx += 1;
         }
 
-        if idx < cfg.plan.len() && Self::is_lock_step(&cfg.plan[idx]) {
+        if idx < cfg.plan.len() && cfg.groups[idx].lock {
             idx += 1;
             while idx < cfg.plan.len() && !cfg.plan[idx].enabled() {
                 idx += 1;
@@ -548,7 +948,62 @@ impl Manager {
         }
     }
 
-    fn maybe_fire_next_step(&self, state: &mut State, cfg: &Config, now_ms: u64) -> Vec<Action> {
+    /// When `maybe_fire_next_step` would next act; mirrors its walk without
+    /// touching state.
+    fn plan_deadline(&self, state: &State, cfg: &Effective) -> Option<u64> {
+        if state.paused() {
+            return None;
+        }
+
+        let mut idx = state.step_index();
+        let step = loop {
+            let step = cfg.plan.get(idx)?;
+            let passed = !step.enabled()
+                || (cfg.groups[idx].lock && state.is_locked())
+                || (step.is_instant() && state.one_shot_has_fired_step(step));
+            if !passed {
+                break step;
+            }
+            idx += 1;
+        };
+
+        if matches!(Self::step_hold(state, cfg, idx), Some((_, StepHold::Hold))) {
+            return None;
+        }
+        // Instants (and skipped instants) go on the next tick.
+        if step.is_instant() {
+            return Some(state.step_base_ms());
+        }
+
+        let notify_sent =
+            cfg.notify_before_action && step.notification.is_some() && state.pre_action_notify_sent();
+        if notify_sent {
+            let wait_ms = step.notify_seconds_before.unwrap_or(0).saturating_mul(1000);
+            return Some(state.pre_action_notify_ms().saturating_add(wait_ms));
+        }
+
+        if Self::compositor_timed(state, cfg) {
+            // Waiting on the compositor; once it has fired, on the next tick.
+            return state
+                .idle_reached()
+                .is_some_and(|reached| reached >= idx)
+                .then_some(state.step_base_ms());
+        }
+
+        let debounce_ms = if state.debounce_pending() {
+            cfg.debounce_seconds.saturating_mul(1000)
+        } else {
+            0
+        };
+        Some(
+            state
+                .step_base_ms()
+                .saturating_add(debounce_ms)
+                .saturating_add(step.timeout_seconds.saturating_mul(1000)),
+        )
+    }
+
+    fn maybe_fire_next_step(&self, state: &mut State, cfg: &Effective, now_ms: u64) -> Vec<Action> {
         let mut out = Vec::new();
         let mut idx = state.step_index();
 
@@ -561,7 +1016,7 @@ impl Manager {
                 return out;
             }
 
-            if Self::is_lock_step(&cfg.plan[idx]) && state.is_locked() {
+            if cfg.groups[idx].lock && state.is_locked() {
                 idx += 1;
                 state.set_step_index(idx);
                 state.set_pre_action_notify_sent(false);
@@ -576,14 +1031,24 @@ impl Manager {
                     continue;
                 }
 
+                match Self::step_hold(state, cfg, idx) {
+                    Some((_, StepHold::Hold)) => return out,
+                    Some((src, StepHold::Skip)) => {
+                        eventline::info!(
+                            "plan: skipping {} (held back by {} inhibitor)",
+                            super::info::step_display_name(step),
+                            src.as_str()
+                        );
+                        idx += 1;
+                        state.set_step_index(idx);
+                        continue;
+                    }
+                    None => {}
+                }
+
                 let emitted = self.actions_for_plan_step(state, step, cfg);
                 if !emitted.is_empty() {
-                    let arms_resume = step.resume_command.is_some();
-                    let is_dpms = Self::is_dpms_group(step);
-                    let is_brightness = Self::is_brightness_group(step);
-                    let is_lock = Self::is_lock_step(step);
-
-                    state.mark_step_fired(idx, is_dpms, is_brightness, is_lock, arms_resume);
+                    Self::mark_fired(state, cfg, idx);
                 }
                 out.extend(emitted);
                 state.mark_one_shot_fired_step(step);
@@ -610,6 +1075,13 @@ impl Manager {
 
         let step = &cfg.plan[idx];
 
+        // refresh_paused() already froze the countdown up to now.
+        let hold = Self::step_hold(state, cfg, idx);
+        if matches!(hold, Some((_, StepHold::Hold))) {
+            state.replace_step_held_ms(Some(now_ms));
+            return out;
+        }
+
         let debounce_ms = if state.debounce_pending() {
             cfg.debounce_seconds.saturating_mul(1000)
         } else {
@@ -622,6 +1094,30 @@ impl Manager {
             .saturating_add(debounce_ms)
             .saturating_add(timeout_ms);
 
+        // With compositor timers our clock only runs the notify wait.
+        let due = if Self::compositor_timed(state, cfg) {
+            state.idle_reached().is_some_and(|reached| reached >= idx)
+        } else {
+            now_ms >= base_due_ms
+        };
+
+        if let Some((src, StepHold::Skip)) = hold {
+            if !due {
+                return out;
+            }
+
+            eventline::info!(
+                "plan: skipping {} (held back by {} inhibitor)",
+                super::info::step_display_name(step),
+                src.as_str()
+            );
+            state.set_step_index(idx + 1);
+            state.set_step_base_ms(now_ms);
+            state.set_pre_action_notify_sent(false);
+            state.set_debounce_pending(false);
+            return out;
+        }
+
         let has_notification = cfg.notify_before_action && step.notification.is_some();
         let notify_wait_ms = step
             .notify_seconds_before
@@ -629,7 +1125,7 @@ impl Manager {
             .saturating_mul(1000);
 
         if has_notification {
-            if now_ms < base_due_ms && !state.pre_action_notify_sent() {
+            if !due && !state.pre_action_notify_sent() {
                 return out;
             }
 
@@ -646,18 +1142,13 @@ impl Manager {
             if now_ms < due_after_notify_ms {
                 return out;
             }
-        } else if now_ms < base_due_ms {
+        } else if !due {
             return out;
         }
 
         let emitted = self.actions_for_plan_step(state, step, cfg);
         if !emitted.is_empty() {
-            let arms_resume = step.resume_command.is_some();
-            let is_dpms = Self::is_dpms_group(step);
-            let is_brightness = Self::is_brightness_group(step);
-            let is_lock = Self::is_lock_step(step);
-
-            state.mark_step_fired(idx, is_dpms, is_brightness, is_lock, arms_resume);
+            Self::mark_fired(state, cfg, idx);
         }
         out.extend(emitted);
 
@@ -671,6 +1162,37 @@ impl Manager {
         out
     }
 
+    /// Lock step (or `pre_suspend_command` when the plan has no lock step) to run
+    /// while the daemon holds the login1 sleep delay lock.
+    fn actions_before_sleep(&self, state: &mut State, cfg: &Effective) -> Vec<Action> {
+        if let Some(emitted) = self.lock_step_now(state, cfg, "prepare-for-sleep") {
+            return emitted;
+        }
+
+        // Our own sleep step already ran pre_suspend_command right before requesting sleep.
+        let sleep_from_plan = state
+            .last_fired_idx()
+            .and_then(|idx| cfg.plan.get(idx))
+            .is_some_and(|step| step.is_sleep());
+
+        match cfg.pre_suspend_command.clone() {
+            Some(command) if !sleep_from_plan => vec![Action::RunCommand { command }],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Fire the plan's lock step out of turn; `None` when the plan has none.
+    fn lock_step_now(&self, state: &mut State, cfg: &Effective, why: &str) -> Option<Vec<Action>> {
+        let idx = cfg.lock_index?;
+
+        let emitted = self.actions_for_plan_step(state, &cfg.plan[idx], cfg);
+        if !emitted.is_empty() {
+            eventline::info!("{why}: locking now");
+            Self::mark_fired(state, cfg, idx);
+        }
+        Some(emitted)
+    }
+
     fn actions_for_plan_step(&self, state: &State, step: &PlanStep, cfg: &Config) -> Vec<Action> {
         match &step.kind {
             PlanStepKind::LockScreen => {
@@ -692,7 +1214,10 @@ impl Manager {
                 Vec::new()
             }
 
-            PlanStepKind::Suspend => {
+            PlanStepKind::Suspend
+            | PlanStepKind::Hibernate
+            | PlanStepKind::HybridSleep
+            | PlanStepKind::SuspendThenHibernate => {
                 let mut out = Vec::new();
 
                 if let Some(cmd) = cfg.pre_suspend_command.clone() {
@@ -702,7 +1227,12 @@ impl Manager {
                 if let Some(cmd) = step.command.clone() {
                     out.push(Action::RunCommand { command: cmd });
                 } else {
-                    out.push(Action::Suspend);
+                    out.push(match step.kind {
+                        PlanStepKind::Hibernate => Action::Hibernate,
+                        PlanStepKind::HybridSleep => Action::HybridSleep,
+                        PlanStepKind::SuspendThenHibernate => Action::SuspendThenHibernate,
+                        _ => Action::Suspend,
+                    });
                 }
 
                 out
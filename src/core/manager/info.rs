@@ -1,8 +1,14 @@
 use crate::core::{
-    config::{Config, Pattern, PlanStepKind},
+    config::{Config, Pattern, PlanSource, PlanStep, PlanStepKind},
     state::State,
 };
 
+use super::{Manager, engine::StepHold};
+
+/// User input is only seen through the compositor's idle notifier; without
+/// it (disconnected, or never offered) the plan runs blind.
+const DEGRADED_LINE: &str = "Degraded: no activity source\n";
+
 pub struct RenderedInfo {
     pub pretty: String,
     pub tooltip: String,
@@ -31,10 +37,16 @@ fn render_status(state: &State, cfg_opt: Option<&Config>, now_ms: u64) -> String
     let mut out = String::new();
 
     out.push_str(&format!("Profile: {}\n", profile_label(state)));
-    out.push_str(&format!("Plan Source: {:?}\n", state.plan_source()));
+    out.push_str(&format!("Plan Source: {}\n", plan_source_label(cfg_opt, state)));
+    if state.lid_closed() {
+        let docked = if state.docked() { ", docked" } else { "" };
+        out.push_str(&format!("Lid: closed{docked}\n"));
+    }
 
     let paused_reason = if state.is_locked() {
         Some("locked")
+    } else if !state.paused() {
+        None
     } else if state.manually_paused() {
         Some("manual")
     } else if state.system_paused() {
@@ -50,6 +62,9 @@ fn render_status(state: &State, cfg_opt: Option<&Config>, now_ms: u64) -> String
         Some(r) => out.push_str(&format!("State: inhibited ({r})\n")),
         None => out.push_str("State: active\n"),
     }
+    if !state.idle_notifier() {
+        out.push_str(DEGRADED_LINE);
+    }
 
     let app = state.app_inhibitor_count();
     let media = state.media_inhibitor_count();
@@ -58,6 +73,42 @@ fn render_status(state: &State, cfg_opt: Option<&Config>, now_ms: u64) -> String
     out.push_str(&format!("Paused: {}\n", yesno(state.paused())));
     out.push_str(&format!("Apps Inhibiting: {}\n", app));
     out.push_str(&format!("Media Players Playing: {}\n", media));
+    out.push_str(&format!(
+        "Logind Inhibitors: {}\n",
+        state.logind_inhibitor_count()
+    ));
+    out.push_str(&format!(
+        "Capture Inhibitors: {}\n",
+        state.capture_inhibitor_count()
+    ));
+    out.push_str(&format!(
+        "ScreenSaver Inhibitors: {}\n",
+        state.screensaver_inhibitor_count()
+    ));
+    out.push_str(&format!(
+        "Compositor Inhibitor: {}\n",
+        compositor_inhibitor_label(state)
+    ));
+    for h in state.screensaver_inhibitors() {
+        out.push_str(&format!("  - {}: {}\n", h.app, h.reason));
+    }
+    if !state.expired_inhibitors().is_empty() {
+        out.push_str(&format!("Expired Inhibitors: {}\n", expired_list(state)));
+    }
+    for line in battery_lines(cfg_opt, state, now_ms) {
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    if let Some(cfg) = cfg_opt {
+        let held = held_back_steps(cfg, state);
+        if !held.is_empty() {
+            out.push_str("Held Back:\n");
+            for line in held {
+                out.push_str(&format!("  - {line}\n"));
+            }
+        }
+    }
 
     if let Some(cfg) = cfg_opt {
         if let Some(line) = next_step_line(cfg, state, now_ms) {
@@ -71,14 +122,24 @@ fn render_status(state: &State, cfg_opt: Option<&Config>, now_ms: u64) -> String
     out
 }
 
+fn compositor_inhibitor_label(state: &State) -> &'static str {
+    match (state.compositor_inhibitor(), state.honors_compositor_inhibit()) {
+        (false, _) => "no",
+        (true, true) => "yes",
+        (true, false) => "yes (ignored)",
+    }
+}
+
 fn render_tooltip_compact(state: &State, cfg_opt: Option<&Config>, now_ms: u64) -> String {
     let mut t = String::new();
 
     t.push_str(&format!("Profile: {}\n", profile_label(state)));
-    t.push_str(&format!("Plan Source: {:?}\n", state.plan_source()));
+    t.push_str(&format!("Plan Source: {}\n", plan_source_label(cfg_opt, state)));
 
     if state.is_locked() {
         t.push_str("State: locked\n");
+    } else if !state.paused() {
+        t.push_str("State: active\n");
     } else if state.manually_paused() {
         t.push_str("State: inhibited (manual)\n");
     } else if state.system_paused() {
@@ -88,6 +149,9 @@ fn render_tooltip_compact(state: &State, cfg_opt: Option<&Config>, now_ms: u64)
     } else {
         t.push_str("State: active\n");
     }
+    if !state.idle_notifier() {
+        t.push_str(DEGRADED_LINE);
+    }
 
     let app = state.app_inhibitor_count();
     let media = state.media_inhibitor_count();
@@ -97,6 +161,36 @@ fn render_tooltip_compact(state: &State, cfg_opt: Option<&Config>, now_ms: u64)
     t.push_str(&format!("Paused: {}\n", yesno(state.paused())));
     t.push_str(&format!("Apps Inhibiting: {}\n", app));
     t.push_str(&format!("Media Players Playing: {}\n", media));
+    t.push_str(&format!(
+        "Logind Inhibitors: {}\n",
+        state.logind_inhibitor_count()
+    ));
+    t.push_str(&format!(
+        "Capture Inhibitors: {}\n",
+        state.capture_inhibitor_count()
+    ));
+    t.push_str(&format!(
+        "ScreenSaver Inhibitors: {}\n",
+        state.screensaver_inhibitor_count()
+    ));
+    t.push_str(&format!(
+        "Compositor Inhibitor: {}\n",
+        compositor_inhibitor_label(state)
+    ));
+    if !state.expired_inhibitors().is_empty() {
+        t.push_str(&format!("Expired Inhibitors: {}\n", expired_list(state)));
+    }
+    for line in battery_lines(cfg_opt, state, now_ms) {
+        t.push_str(&line);
+        t.push('\n');
+    }
+
+    if let Some(cfg) = cfg_opt {
+        let held = held_back_steps(cfg, state);
+        if !held.is_empty() {
+            t.push_str(&format!("Held Back: {}\n", held.join(", ")));
+        }
+    }
 
     if let Some(cfg) = cfg_opt {
         if let Some(line) = next_step_line(cfg, state, now_ms) {
@@ -117,6 +211,16 @@ fn render_config(cfg_opt: Option<&Config>, state: &State) -> String {
     let mut out = String::new();
 
     out.push_str(&format!("Debounce: {}s\n", cfg.debounce_seconds));
+    let idle_timers = match (cfg.compositor_idle_timers, state.idle_notifier()) {
+        (false, _) => "stasis",
+        (true, true) => "compositor",
+        (true, false) => "stasis (no compositor idle notifier)",
+    };
+    out.push_str(&format!("IdleTimers: {idle_timers}\n"));
+    out.push_str(&format!(
+        "CompositorInhibit: {}\n",
+        yesno(cfg.compositor_inhibit)
+    ));
     out.push_str(&format!(
         "NotifyBeforeAction: {}\n",
         yesno(cfg.notify_before_action)
@@ -126,7 +230,22 @@ fn render_config(cfg_opt: Option<&Config>, state: &State) -> String {
         yesno(cfg.notify_on_unpause)
     ));
 
+    out.push_str(&format!(
+        "LockBeforeSleep: {}\n",
+        yesno(cfg.lock_before_sleep)
+    ));
+
     out.push_str(&format!("MonitorMedia: {}\n", yesno(cfg.monitor_media)));
+    out.push_str(&format!("MediaBackend: {}\n", cfg.media_backend.as_str()));
+    out.push_str(&format!("LidPolicy: {}\n", cfg.lid_policy.as_str()));
+    out.push_str(&format!(
+        "ScreenSaverInhibit: {}\n",
+        yesno(cfg.screensaver_inhibit)
+    ));
+    out.push_str(&format!(
+        "LogindInhibitDelay: {}\n",
+        yesno(cfg.logind_inhibit_delay)
+    ));
     out.push_str(&format!(
         "IgnoreRemoteMedia: {}\n",
         yesno(cfg.ignore_remote_media)
@@ -141,6 +260,40 @@ fn render_config(cfg_opt: Option<&Config>, state: &State) -> String {
         out.push_str("InhibitApps: none\n");
     }
 
+    if !cfg.inhibit_scopes.is_empty() {
+        let scopes: Vec<String> = cfg
+            .inhibit_scopes
+            .iter()
+            .map(|(src, scope)| format!("{}={}", src.as_str(), scope))
+            .collect();
+        out.push_str(&format!("InhibitScopes: {}\n", scopes.join(", ")));
+    } else {
+        out.push_str("InhibitScopes: all\n");
+    }
+
+    let mut max_inhibit = vec![if cfg.max_inhibit_seconds > 0 {
+        format!("{}s", cfg.max_inhibit_seconds)
+    } else {
+        "never".to_string()
+    }];
+    max_inhibit.extend(
+        cfg.max_inhibit_overrides
+            .iter()
+            .map(|(src, secs)| format!("{}={}s", src.as_str(), secs)),
+    );
+    out.push_str(&format!("MaxInhibit: {}\n", max_inhibit.join(", ")));
+    out.push_str(&format!(
+        "NotifyOnInhibitExpiry: {}\n",
+        yesno(cfg.notify_on_inhibit_expiry)
+    ));
+
+    if !cfg.inhibit_rules.is_empty() {
+        let rules: Vec<String> = cfg.inhibit_rules.iter().map(|r| r.to_string()).collect();
+        out.push_str(&format!("InhibitRules: {}\n", rules.join(", ")));
+    } else {
+        out.push_str("InhibitRules: none\n");
+    }
+
     if !cfg.media_blacklist.is_empty() {
         out.push_str(&format!(
             "MediaBlacklist: {}\n",
@@ -150,6 +303,38 @@ fn render_config(cfg_opt: Option<&Config>, state: &State) -> String {
         out.push_str("MediaBlacklist: none\n");
     }
 
+    out.push_str(&format!("MonitorCapture: {}\n", yesno(cfg.monitor_capture)));
+    if !cfg.capture_blacklist.is_empty() {
+        out.push_str(&format!(
+            "CaptureBlacklist: {}\n",
+            join_patterns(&cfg.capture_blacklist)
+        ));
+    } else {
+        out.push_str("CaptureBlacklist: none\n");
+    }
+
+    if !cfg.battery_tiers.is_empty() {
+        let tiers: Vec<String> = cfg
+            .battery_tiers
+            .iter()
+            .map(|t| format!("{} below {}%", t.name, t.below_percent))
+            .collect();
+        out.push_str(&format!("BatteryTiers: {}\n", tiers.join(", ")));
+    } else {
+        out.push_str("BatteryTiers: none\n");
+    }
+
+    if let Some(crit) = &cfg.critical_battery {
+        out.push_str(&format!(
+            "CriticalBattery: {} at {}% (notify {}s before)\n",
+            crit.action_label(),
+            crit.threshold_percent,
+            crit.notify_seconds_before
+        ));
+    } else {
+        out.push_str("CriticalBattery: off\n");
+    }
+
     if let Some(cmd) = cfg.pre_suspend_command.as_deref() {
         out.push_str(&format!("PreSuspendCommand: {cmd}\n"));
     } else {
@@ -172,8 +357,9 @@ fn render_plan(cfg: &Config, state: &State) -> String {
     }
 
     // Build enabled rows first so we can compute widths for alignment.
-    // (idx, name, timeout_seconds, notify_before_opt, has_cmd, loginctl_only)
-    let mut rows: Vec<(usize, String, u64, Option<u64>, bool, bool)> = Vec::new();
+    // (idx, name, timeout_seconds, notify_before_opt, runner_tag)
+    type Row = (usize, String, u64, Option<u64>, Option<&'static str>);
+    let mut rows: Vec<Row> = Vec::new();
 
     for (i, step) in cfg.plan.iter().enumerate() {
         if !step_enabled(cfg, i) {
@@ -191,11 +377,17 @@ fn render_plan(cfg: &Config, state: &State) -> String {
             None
         };
 
-        let has_cmd = step.command.is_some();
-        let loginctl_only =
-            !has_cmd && matches!(step.kind, PlanStepKind::LockScreen) && step.use_loginctl;
+        let tag = if step.command.is_some() {
+            Some("cmd")
+        } else if matches!(step.kind, PlanStepKind::LockScreen) && step.use_loginctl {
+            Some("loginctl")
+        } else if step.is_native_sleep() {
+            Some("login1")
+        } else {
+            None
+        };
 
-        rows.push((i, name, step.timeout_seconds, notify_before, has_cmd, loginctl_only));
+        rows.push((i, name, step.timeout_seconds, notify_before, tag));
     }
 
     if rows.is_empty() {
@@ -206,19 +398,19 @@ fn render_plan(cfg: &Config, state: &State) -> String {
     // Column widths
     let name_w = rows
         .iter()
-        .map(|(_, n, _, _, _, _)| n.len())
+        .map(|(_, n, _, _, _)| n.len())
         .max()
         .unwrap_or(0)
         .max(8);
 
     let secs_w = rows
         .iter()
-        .map(|(_, _, s, _, _, _)| s.to_string().len())
+        .map(|(_, _, s, _, _)| s.to_string().len())
         .max()
         .unwrap_or(1)
         .max(1);
 
-    for (i, name, timeout_seconds, notify_before, has_cmd, loginctl_only) in rows {
+    for (i, name, timeout_seconds, notify_before, tag) in rows {
         let marker = if i == cur { "→" } else { " " };
 
         out.push_str(&format!(
@@ -234,10 +426,8 @@ fn render_plan(cfg: &Config, state: &State) -> String {
             out.push_str(&format!("  notify+{n}s"));
         }
 
-        if has_cmd {
-            out.push_str("  cmd");
-        } else if loginctl_only {
-            out.push_str("  loginctl");
+        if let Some(tag) = tag {
+            out.push_str(&format!("  {tag}"));
         }
 
         out.push('\n');
@@ -250,6 +440,19 @@ fn profile_label(state: &State) -> &str {
     state.active_profile().unwrap_or("none")
 }
 
+fn plan_source_label(cfg_opt: Option<&Config>, state: &State) -> String {
+    match state.plan_source() {
+        PlanSource::BatteryBelow(below) => {
+            match cfg_opt.and_then(|cfg| cfg.battery_tier(below)) {
+                Some(tier) => format!("{} (battery below {below}%)", tier.name),
+                // Tier gone from this profile: the plain battery plan runs.
+                None => "Battery".to_string(),
+            }
+        }
+        src => format!("{src:?}"),
+    }
+}
+
 fn yesno(v: bool) -> &'static str {
     if v { "yes" } else { "no" }
 }
@@ -258,24 +461,28 @@ fn step_enabled(cfg: &Config, idx: usize) -> bool {
     if idx >= cfg.plan.len() {
         return false;
     }
-    let step = &cfg.plan[idx];
-    step.command.is_some() || (matches!(step.kind, PlanStepKind::LockScreen) && step.use_loginctl)
+    cfg.plan[idx].enabled()
 }
 
-fn next_step_line(cfg: &Config, state: &State, now_ms: u64) -> Option<String> {
-    let mut idx = state.step_index();
-    while idx < cfg.plan.len() && !step_enabled(cfg, idx) {
-        idx += 1;
-    }
-    if idx >= cfg.plan.len() {
-        return Some("Next: (end of plan)".to_string());
-    }
+/// Next enabled step at/after the current step index, if any.
+pub(super) fn next_enabled_step<'a>(cfg: &'a Config, state: &State) -> Option<&'a PlanStep> {
+    (state.step_index()..cfg.plan.len())
+        .find(|&idx| step_enabled(cfg, idx))
+        .map(|idx| &cfg.plan[idx])
+}
 
-    let step = &cfg.plan[idx];
-    let name = match &step.kind {
+pub(super) fn step_display_name(step: &PlanStep) -> String {
+    match &step.kind {
         PlanStepKind::Custom(s) => format!("Custom({s})"),
         other => format!("{other:?}"),
+    }
+}
+
+fn next_step_line(cfg: &Config, state: &State, now_ms: u64) -> Option<String> {
+    let Some(step) = next_enabled_step(cfg, state) else {
+        return Some("Next: (end of plan)".to_string());
     };
+    let name = step_display_name(step);
 
     let debounce_ms = cfg.debounce_seconds.saturating_mul(1000);
     let timeout_ms = step.timeout_seconds.saturating_mul(1000);
@@ -287,6 +494,10 @@ fn next_step_line(cfg: &Config, state: &State, now_ms: u64) -> Option<String> {
     let has_notification = cfg.notify_before_action && step.notification.is_some();
     let notify_wait_ms = step.notify_seconds_before.unwrap_or(0).saturating_mul(1000);
 
+    if Manager::compositor_timed(state, cfg) && !state.pre_action_notify_sent() {
+        return Some(format!("Next: {name} (on compositor idle timer)"));
+    }
+
     if has_notification {
         if !state.pre_action_notify_sent() {
             if now_ms >= base_due_ms {
@@ -312,6 +523,70 @@ fn next_step_line(cfg: &Config, state: &State, now_ms: u64) -> Option<String> {
     Some(format!("Next: {name} in {s}s"))
 }
 
+/// Remaining steps a scoped inhibitor is holding or skipping, e.g.
+/// "Suspend: held (media)". Empty while the whole plan is paused.
+fn held_back_steps(cfg: &Config, state: &State) -> Vec<String> {
+    if state.paused() {
+        return Vec::new();
+    }
+
+    (state.step_index()..cfg.plan.len())
+        .filter(|&idx| step_enabled(cfg, idx))
+        .filter_map(|idx| {
+            let (src, hold) = Manager::step_hold(state, cfg, idx)?;
+            let how = match hold {
+                StepHold::Hold => "held",
+                StepHold::Skip => "skipped",
+            };
+            Some(format!(
+                "{}: {} ({})",
+                step_display_name(&cfg.plan[idx]),
+                how,
+                src.as_str()
+            ))
+        })
+        .collect()
+}
+
+/// "Battery: 42%, discharging, 1h 05m left" plus the critical countdown, if any.
+fn battery_lines(cfg_opt: Option<&Config>, state: &State, now_ms: u64) -> Vec<String> {
+    let Some(battery) = state.battery() else {
+        return Vec::new();
+    };
+
+    let mut line = format!("Battery: {}%", battery.percent);
+    if battery.discharging {
+        line.push_str(", discharging");
+        if let Some(secs) = battery.time_to_empty_secs {
+            line.push_str(&format!(", {}h {:02}m left", secs / 3600, secs % 3600 / 60));
+        }
+    }
+    let mut out = vec![line];
+
+    let crit = cfg_opt.and_then(|cfg| cfg.critical_battery.as_ref());
+    if let (Some(crit), Some(warned_ms)) = (crit, state.critical_battery_warned_ms()) {
+        let action = crit.action_label();
+        if state.critical_battery_fired() {
+            out.push(format!("Critical Battery: {action} requested"));
+        } else {
+            let due_ms = warned_ms.saturating_add(crit.notify_seconds_before.saturating_mul(1000));
+            let s = due_ms.saturating_sub(now_ms) / 1000;
+            out.push(format!("Critical Battery: {action} in {s}s"));
+        }
+    }
+
+    out
+}
+
+fn expired_list(state: &State) -> String {
+    let names: Vec<&str> = state
+        .expired_inhibitors()
+        .iter()
+        .map(|src| src.as_str())
+        .collect();
+    names.join(", ")
+}
+
 fn join_patterns(v: &[Pattern]) -> String {
     v.iter().map(|p| p.render()).collect::<Vec<_>>().join(", ")
 }
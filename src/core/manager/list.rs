@@ -1,26 +1,15 @@
 // Author: Dustin Pilgrim
 // License: MIT
 
-use crate::core::{
-    config::PlanStepKind,
-    error::{ConfigError, Error},
-    state::State,
-};
+use crate::core::{config::PlanStepKind, state::State};
 
 use super::Manager;
 
 impl Manager {
     pub fn list_actions(&self, state: &State) -> String {
-        let cfg_opt = self
-            .cfg_file
-            .effective_for(state.active_profile(), state.plan_source());
-
-        let cfg = match cfg_opt {
-            Some(c) => c,
-            None => {
-                let e = Error::InvalidConfig(ConfigError::ProfileNotFound);
-                return format!("ERROR: {e}");
-            }
+        let cfg = match self.peek_effective(state) {
+            Ok(c) => c,
+            Err(e) => return format!("ERROR: {e}"),
         };
 
         let mut out = String::new();
@@ -37,6 +26,9 @@ impl Manager {
                 PlanStepKind::Brightness => "brightness".to_string(),
                 PlanStepKind::LockScreen => "lock-screen".to_string(),
                 PlanStepKind::Suspend => "suspend".to_string(),
+                PlanStepKind::Hibernate => "hibernate".to_string(),
+                PlanStepKind::HybridSleep => "hybrid-sleep".to_string(),
+                PlanStepKind::SuspendThenHibernate => "suspend-then-hibernate".to_string(),
                 PlanStepKind::Custom(k) => format!("custom:{}", Self::normalize_trigger_name(k)),
             };
 
@@ -47,6 +39,13 @@ impl Manager {
                 step.command.as_deref().unwrap()
             } else if matches!(step.kind, PlanStepKind::LockScreen) && step.use_loginctl {
                 "<loginctl lock-session>"
+            } else if step.is_native_sleep() {
+                match step.kind {
+                    PlanStepKind::Hibernate => "<login1 hibernate>",
+                    PlanStepKind::HybridSleep => "<login1 hybrid-sleep>",
+                    PlanStepKind::SuspendThenHibernate => "<login1 suspend-then-hibernate>",
+                    _ => "<login1 suspend>",
+                }
             } else {
                 "<none>"
             };
@@ -58,7 +57,7 @@ impl Manager {
             };
 
             out.push_str(&format!(
-                "  {:>2}. {:<18} timeout={}s{} cmd={}{}\n",
+                "  {:>2}. {:<22} timeout={}s{} cmd={}{}\n",
                 idx, name, timeout, instant, cmd, notify
             ));
         }
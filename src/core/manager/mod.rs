@@ -1,17 +1,23 @@
 // Author: Dustin Pilgrim
 // License: MIT
 
+mod effective;
 mod engine;
 mod info;
 mod list;
 mod snapshot;
 
+use std::sync::Arc;
+
 use crate::core::events::MediaState;
 use crate::core::config::ConfigFile;
 
+use effective::Effective;
+
 #[derive(Debug, Clone)]
 pub struct Manager {
     cfg_file: ConfigFile,
+    effective: Option<Arc<Effective>>,
     last_media: MediaState,
 }
 
@@ -19,12 +25,14 @@ impl Manager {
     pub fn new(cfg_file: ConfigFile) -> Self {
         Self {
             cfg_file,
+            effective: None,
             last_media: MediaState::Idle,
         }
     }
 
     pub fn set_config(&mut self, cfg_file: ConfigFile) {
         self.cfg_file = cfg_file;
+        self.effective = None;
     }
 
     pub fn cfg_file_ref(&self) -> &crate::core::config::ConfigFile {
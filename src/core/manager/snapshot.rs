@@ -2,7 +2,7 @@
 // License: MIT
 
 use crate::core::{
-    info::{InfoSnapshot, WaybarInfo},
+    info::{ControlStatus, InfoSnapshot, WaybarBattery, WaybarInfo},
     state::State,
 };
 
@@ -10,9 +10,7 @@ use super::Manager;
 
 impl Manager {
     pub fn snapshot(&self, state: &State, now_ms: u64) -> InfoSnapshot {
-        let cfg_opt = self
-            .cfg_file
-            .effective_for(state.active_profile(), state.plan_source());
+        let cfg_opt = self.peek_effective(state).ok();
 
         let alt = if state.manually_paused() {
             "manually_inhibited"
@@ -24,7 +22,11 @@ impl Manager {
 
         let profile = Some(state.active_profile().unwrap_or("default").to_string());
 
-        let rendered = crate::core::manager::info::render_info(cfg_opt.as_ref(), state, now_ms);
+        let rendered = crate::core::manager::info::render_info(
+            cfg_opt.as_deref().map(|eff| &**eff),
+            state,
+            now_ms,
+        );
 
         let waybar = WaybarInfo {
             text: "".to_string(),
@@ -32,8 +34,32 @@ impl Manager {
             class: alt.to_string(),
             tooltip: rendered.tooltip,
             profile,
+            battery: state.battery().map(|b| WaybarBattery {
+                percent: b.percent,
+                discharging: b.discharging,
+                time_to_empty: b.time_to_empty_secs,
+                critical: state.critical_battery_warned_ms().is_some(),
+            }),
         };
 
         InfoSnapshot::new(waybar, rendered.pretty, state.manually_paused())
     }
+
+    pub fn control_status(&self, state: &State) -> ControlStatus {
+        let cfg_opt = self.peek_effective(state).ok();
+
+        let next_step = cfg_opt
+            .as_deref()
+            .and_then(|cfg| crate::core::manager::info::next_enabled_step(cfg, state))
+            .map(crate::core::manager::info::step_display_name)
+            .unwrap_or_default();
+
+        ControlStatus {
+            paused: state.paused(),
+            locked: state.is_locked(),
+            active_profile: state.active_profile().unwrap_or("none").to_string(),
+            plan_source: format!("{:?}", state.plan_source()),
+            next_step,
+        }
+    }
 }
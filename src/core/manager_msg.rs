@@ -4,7 +4,7 @@
 use tokio::sync::oneshot;
 
 use crate::core::{
-    config::Pattern,
+    config::{InhibitRule, MediaBackend, Pattern},
     events::Event,
     info::InfoSnapshot,
 };
@@ -42,8 +42,14 @@ pub enum ManagerMsg {
     UpdateInhibitRules {
         epoch: u64,
         inhibit_apps: Vec<Pattern>,
+        inhibit_rules: Vec<InhibitRule>,
         monitor_media: bool,
         ignore_remote_media: bool,
+        media_backend: MediaBackend,
         media_blacklist: Vec<Pattern>,
+        monitor_capture: bool,
+        capture_blacklist: Vec<Pattern>,
+        screensaver_inhibit: bool,
+        logind_inhibit_delay: bool,
     },
 }
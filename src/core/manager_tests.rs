@@ -2,8 +2,11 @@
 // License: MIT
 
 use crate::core::action::Action;
-use crate::core::config::{Config, ConfigFile, PlanSource, PlanStep, PlanStepKind};
-use crate::core::events::{ActivityKind, Event};
+use crate::core::config::{
+    BatteryTier, Config, ConfigFile, CriticalBattery, InhibitScope, InhibitSource, LidPolicy, PartialConfig,
+    PlanSource, PlanStep, PlanStepKind, Profile, ProfileMode,
+};
+use crate::core::events::{ActivityKind, BatteryStatus, Event, PowerState};
 use crate::core::manager::Manager;
 use crate::core::state::State;
 
@@ -262,3 +265,829 @@ fn no_notification_text_ignores_notify_seconds_before() {
         }]
     );
 }
+
+#[test]
+fn suspend_without_command_uses_native_suspend() {
+    let mut s = step(PlanStepKind::Suspend, 5, "unused");
+    s.command = None;
+
+    let mut cfg = cfg_with_plan(vec![s]);
+    cfg.default.pre_suspend_command = Some("pre".to_string());
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 5000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![
+            Action::RunCommand {
+                command: "pre".to_string()
+            },
+            Action::Suspend,
+        ]
+    );
+}
+
+#[test]
+fn legacy_empty_suspend_slot_stays_disabled() {
+    let mut s = step(PlanStepKind::Suspend, 0, "unused");
+    s.command = None;
+
+    let mut mgr = Manager::new(cfg_with_plan(vec![s]));
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 5000 })
+        .unwrap();
+    assert!(actions.is_empty());
+}
+
+#[test]
+fn instant_hibernate_without_command_stays_enabled() {
+    let mut s = step(PlanStepKind::Hibernate, 0, "unused");
+    s.command = None;
+    assert!(s.is_native_sleep());
+
+    let mut mgr = Manager::new(cfg_with_plan(vec![s]));
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 5000 })
+        .unwrap();
+    assert_eq!(actions, vec![Action::Hibernate]);
+}
+
+#[test]
+fn triggered_hibernate_runs_pre_suspend_command_then_native_hibernate() {
+    let mut s = step(PlanStepKind::Hibernate, 600, "unused");
+    s.command = None;
+
+    let mut cfg = cfg_with_plan(vec![s]);
+    cfg.default.pre_suspend_command = Some("pre".to_string());
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(
+            &mut state,
+            Event::ManualTrigger {
+                name: "hibernate".to_string(),
+                now_ms: 1000,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![
+            Action::RunCommand {
+                command: "pre".to_string()
+            },
+            Action::Hibernate,
+        ]
+    );
+}
+
+#[test]
+fn lock_before_sleep_runs_lock_step_on_prepare_for_sleep() {
+    let mut lock = step(PlanStepKind::LockScreen, 600, "locker");
+    lock.use_loginctl = true;
+
+    let mut cfg = cfg_with_plan(vec![lock]);
+    cfg.default.lock_before_sleep = true;
+    cfg.default.pre_suspend_command = Some("pre".to_string());
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::PrepareForSleep { now_ms: 1000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunLockScreen {
+            command: "locker".to_string(),
+            use_loginctl: true,
+        }]
+    );
+}
+
+#[test]
+fn lock_before_sleep_without_lock_step_runs_pre_suspend_command() {
+    let mut cfg = cfg_with_plan(vec![step(PlanStepKind::Dpms, 600, "off")]);
+    cfg.default.lock_before_sleep = true;
+    cfg.default.pre_suspend_command = Some("pre".to_string());
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::PrepareForSleep { now_ms: 1000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "pre".to_string()
+        }]
+    );
+}
+
+#[test]
+fn scoped_media_inhibitor_holds_only_from_its_step() {
+    let mut cfg = cfg_with_plan(vec![
+        step(PlanStepKind::Brightness, 5, "dim"),
+        step(PlanStepKind::Suspend, 10, "sleep"),
+    ]);
+    cfg.default
+        .inhibit_scopes
+        .insert(InhibitSource::Media, InhibitScope::UpTo("suspend".to_string()));
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    mgr.handle_event(&mut state, Event::MediaInhibitorCount { count: 1, now_ms: 0 })
+        .unwrap();
+    assert!(!state.paused());
+
+    // Dimming is not covered by the scope.
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 5000 })
+        .unwrap();
+    assert_eq!(actions.len(), 1);
+
+    // Suspend waits with its countdown frozen (5s of 10s elapsed at 10000).
+    for now_ms in [10_000, 20_000] {
+        let actions = mgr.handle_event(&mut state, Event::Tick { now_ms }).unwrap();
+        assert!(actions.is_empty());
+    }
+
+    mgr.handle_event(&mut state, Event::MediaInhibitorCount { count: 0, now_ms: 21_000 })
+        .unwrap();
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 25_999 })
+        .unwrap();
+    assert!(actions.is_empty());
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 26_000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "sleep".to_string()
+        }]
+    );
+}
+
+#[test]
+fn stale_inhibitor_expires_until_it_changes() {
+    let mut cfg = cfg_with_plan(vec![step(PlanStepKind::Suspend, 10, "sleep")]);
+    cfg.default.max_inhibit_seconds = 60;
+    cfg.default.notify_on_inhibit_expiry = true;
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    mgr.handle_event(&mut state, Event::MediaInhibitorCount { count: 1, now_ms: 0 })
+        .unwrap();
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 59_999 })
+        .unwrap();
+    assert!(actions.is_empty());
+    assert!(state.paused());
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 60_000 })
+        .unwrap();
+    assert!(matches!(actions.as_slice(), [Action::Notify { .. }]));
+    assert!(!state.paused());
+
+    // The countdown resumes where the pause froze it.
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 70_000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "sleep".to_string()
+        }]
+    );
+
+    // A second player is a change: the inhibitor counts again.
+    mgr.handle_event(&mut state, Event::MediaInhibitorCount { count: 2, now_ms: 71_000 })
+        .unwrap();
+    assert!(state.paused());
+}
+
+#[test]
+fn scoped_app_inhibitor_skips_listed_steps() {
+    let mut cfg = cfg_with_plan(vec![
+        step(PlanStepKind::LockScreen, 5, "locker"),
+        step(PlanStepKind::Dpms, 5, "off"),
+    ]);
+    cfg.default.inhibit_scopes.insert(
+        InhibitSource::Apps,
+        InhibitScope::Steps(vec!["lock_screen".to_string()]),
+    );
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    mgr.handle_event(&mut state, Event::AppInhibitorCount { count: 1, now_ms: 0 })
+        .unwrap();
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 5000 })
+        .unwrap();
+    assert!(actions.is_empty());
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 10_000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "off".to_string()
+        }]
+    );
+}
+
+#[test]
+fn critical_battery_overrides_inhibitors() {
+    let mut cfg = cfg_with_plan(vec![step(PlanStepKind::Suspend, 600, "sleep")]);
+    cfg.default.critical_battery = Some(CriticalBattery {
+        threshold_percent: 5,
+        command: None,
+        notification: None,
+        notify_seconds_before: 30,
+    });
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Battery);
+
+    mgr.handle_event(&mut state, Event::ManualPause { now_ms: 0 })
+        .unwrap();
+    mgr.handle_event(&mut state, Event::AppInhibitorCount { count: 1, now_ms: 0 })
+        .unwrap();
+
+    let low = BatteryStatus {
+        percent: 4,
+        discharging: true,
+        time_to_empty_secs: Some(300),
+    };
+    mgr.handle_event(&mut state, Event::BatteryChanged { battery: low, now_ms: 1000 })
+        .unwrap();
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 1200 })
+        .unwrap();
+    assert!(matches!(actions.as_slice(), [Action::Notify { .. }]));
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 31_199 })
+        .unwrap();
+    assert!(actions.is_empty());
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 31_200 })
+        .unwrap();
+    assert_eq!(actions, vec![Action::Hibernate]);
+
+    // Once per discharge.
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 60_000 })
+        .unwrap();
+    assert!(actions.is_empty());
+
+    // Plugging in re-arms it.
+    let charging = BatteryStatus {
+        discharging: false,
+        ..low
+    };
+    mgr.handle_event(&mut state, Event::BatteryChanged { battery: charging, now_ms: 61_000 })
+        .unwrap();
+    mgr.handle_event(&mut state, Event::Tick { now_ms: 61_200 })
+        .unwrap();
+    assert!(!state.critical_battery_fired());
+    assert_eq!(state.critical_battery_warned_ms(), None);
+}
+
+#[test]
+fn critical_battery_schedules_a_tick_while_paused() {
+    let mut cfg = cfg_with_plan(vec![step(PlanStepKind::Suspend, 600, "sleep")]);
+    cfg.default.critical_battery = Some(CriticalBattery {
+        threshold_percent: 5,
+        command: None,
+        notification: None,
+        notify_seconds_before: 30,
+    });
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Battery);
+
+    let handled = mgr
+        .handle(&mut state, Event::ManualPause { now_ms: 0 })
+        .unwrap();
+    assert_eq!(handled.next_deadline_ms, None);
+
+    let low = BatteryStatus {
+        percent: 4,
+        discharging: true,
+        time_to_empty_secs: Some(300),
+    };
+    let handled = mgr
+        .handle(
+            &mut state,
+            Event::BatteryChanged {
+                battery: low,
+                now_ms: 1000,
+            },
+        )
+        .unwrap();
+    assert_eq!(handled.next_deadline_ms, Some(1000));
+
+    let handled = mgr
+        .handle(&mut state, Event::Tick { now_ms: 1000 })
+        .unwrap();
+    assert!(matches!(
+        handled.actions.as_slice(),
+        [Action::Notify { .. }]
+    ));
+    assert_eq!(handled.next_deadline_ms, Some(31_000));
+
+    let handled = mgr
+        .handle(&mut state, Event::Tick { now_ms: 31_000 })
+        .unwrap();
+    assert_eq!(handled.actions, vec![Action::Hibernate]);
+    assert_eq!(handled.next_deadline_ms, None);
+}
+
+#[test]
+fn battery_tier_switches_plan_and_restarts_cycle() {
+    let mut cfg = cfg_with_plan(vec![step(PlanStepKind::Dpms, 600, "desktop-off")]);
+    cfg.default.plan_battery = vec![
+        step(PlanStepKind::Startup, 0, "powersave"),
+        step(PlanStepKind::Dpms, 300, "off"),
+        step(PlanStepKind::Suspend, 900, "sleep"),
+    ];
+    // Only retimes dpms; the other battery steps carry over.
+    cfg.default.battery_tiers = vec![BatteryTier {
+        name: "battery_low".to_string(),
+        below_percent: 20,
+        plan: vec![step(PlanStepKind::Dpms, 60, "off-soon")],
+    }];
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Battery);
+
+    let battery = |percent| BatteryStatus {
+        percent,
+        discharging: true,
+        time_to_empty_secs: None,
+    };
+
+    mgr.handle_event(&mut state, Event::BatteryChanged { battery: battery(50), now_ms: 0 })
+        .unwrap();
+    assert_eq!(state.plan_source(), PlanSource::Battery);
+
+    // Crossing the threshold restarts the cycle and re-fires instant steps.
+    let actions = mgr
+        .handle_event(&mut state, Event::BatteryChanged { battery: battery(19), now_ms: 100_000 })
+        .unwrap();
+    assert_eq!(state.plan_source(), PlanSource::BatteryBelow(20));
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "powersave".to_string()
+        }]
+    );
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 159_999 })
+        .unwrap();
+    assert!(actions.is_empty());
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 160_000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "off-soon".to_string()
+        }]
+    );
+
+    // A reading back above the threshold doesn't loosen the plan mid-discharge.
+    mgr.handle_event(&mut state, Event::BatteryChanged { battery: battery(21), now_ms: 161_000 })
+        .unwrap();
+    assert_eq!(state.plan_source(), PlanSource::BatteryBelow(20));
+}
+
+#[test]
+fn docked_lid_runs_docked_plan_instead_of_pausing() {
+    let mut cfg = cfg_with_plan(vec![step(PlanStepKind::Dpms, 600, "desktop-off")]);
+    cfg.default.plan_ac = vec![step(PlanStepKind::Dpms, 300, "panel-off")];
+    cfg.default.plan_docked = vec![step(PlanStepKind::Dpms, 900, "monitors-off")];
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Ac);
+    state.set_power_state(PowerState::OnAC);
+
+    mgr.handle_event(&mut state, Event::ExternalOutputs { count: 1, now_ms: 0 })
+        .unwrap();
+    assert_eq!(state.plan_source(), PlanSource::Ac);
+
+    mgr.handle_event(&mut state, Event::LidClosed { now_ms: 1000 })
+        .unwrap();
+    assert_eq!(state.plan_source(), PlanSource::Docked);
+    assert!(!state.paused());
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 901_000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "monitors-off".to_string()
+        }]
+    );
+
+    // Unplugging the monitor with the lid still shut: back to AC, paused.
+    mgr.handle_event(&mut state, Event::ExternalOutputs { count: 0, now_ms: 902_000 })
+        .unwrap();
+    assert_eq!(state.plan_source(), PlanSource::Ac);
+    assert!(state.paused());
+
+    mgr.handle_event(&mut state, Event::LidOpened { now_ms: 903_000 })
+        .unwrap();
+    assert!(!state.paused());
+}
+
+#[test]
+fn lid_policy_lock_locks_without_pausing() {
+    let mut cfg = cfg_with_plan(vec![
+        step(PlanStepKind::LockScreen, 300, "swaylock"),
+        step(PlanStepKind::Suspend, 600, "sleep"),
+    ]);
+    cfg.default.lid_policy = LidPolicy::Lock;
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::LidClosed { now_ms: 1000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunLockScreen {
+            command: "swaylock".to_string(),
+            use_loginctl: false
+        }]
+    );
+    assert!(!state.paused());
+}
+
+#[test]
+fn opening_the_lid_keeps_a_sleep_pause() {
+    let mut cfg = cfg_with_plan(vec![step(PlanStepKind::Dpms, 300, "off")]);
+    cfg.default.lid_policy = LidPolicy::Lock;
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    mgr.handle_event(&mut state, Event::PrepareForSleep { now_ms: 1000 })
+        .unwrap();
+    mgr.handle_event(&mut state, Event::LidClosed { now_ms: 1500 })
+        .unwrap();
+    mgr.handle_event(&mut state, Event::LidOpened { now_ms: 2000 })
+        .unwrap();
+    assert!(state.system_paused());
+    assert!(state.paused());
+
+    mgr.handle_event(&mut state, Event::ResumedFromSleep { now_ms: 3000 })
+        .unwrap();
+    assert!(!state.paused());
+}
+
+#[test]
+fn lid_policy_suspend_runs_the_plans_sleep_step() {
+    let mut cfg = cfg_with_plan(vec![
+        step(PlanStepKind::Dpms, 300, "off"),
+        step(PlanStepKind::Suspend, 600, "sleep"),
+    ]);
+    cfg.default.lid_policy = LidPolicy::Suspend;
+    cfg.default.pre_suspend_command = Some("pre".to_string());
+
+    let mut mgr = Manager::new(cfg.clone());
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::LidClosed { now_ms: 1000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![
+            Action::RunCommand {
+                command: "pre".to_string()
+            },
+            Action::RunCommand {
+                command: "sleep".to_string()
+            },
+        ]
+    );
+    assert!(!state.paused());
+
+    // No sleep step: plain login1 suspend.
+    cfg.default.plan_desktop.pop();
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::LidClosed { now_ms: 1000 })
+        .unwrap();
+    assert_eq!(actions, vec![Action::Suspend]);
+}
+
+#[test]
+fn next_deadline_tracks_debounce_notify_and_pause() {
+    let mut dpms = step(PlanStepKind::Dpms, 10, "off");
+    dpms.notification = Some("screen off soon".to_string());
+    dpms.notify_seconds_before = Some(5);
+    let cfg = cfg_with_plan_and_notify(
+        vec![dpms, step(PlanStepKind::Suspend, 20, "sleep")],
+        3,
+        true,
+    );
+
+    let mut mgr = Manager::new(cfg);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    // Debounce + timeout from the activity.
+    let handled = mgr
+        .handle(
+            &mut state,
+            Event::UserActivity {
+                kind: ActivityKind::Any,
+                now_ms: 1000,
+            },
+        )
+        .unwrap();
+    assert_eq!(handled.next_deadline_ms, Some(14_000));
+
+    // Notification goes out at the deadline; the step waits out notify_seconds_before.
+    let handled = mgr
+        .handle(&mut state, Event::Tick { now_ms: 14_000 })
+        .unwrap();
+    assert!(matches!(
+        handled.actions.as_slice(),
+        [Action::Notify { .. }]
+    ));
+    assert_eq!(handled.next_deadline_ms, Some(19_000));
+
+    let handled = mgr
+        .handle(&mut state, Event::Tick { now_ms: 19_000 })
+        .unwrap();
+    assert_eq!(
+        handled.actions,
+        vec![Action::RunCommand {
+            command: "off".to_string()
+        }]
+    );
+    assert_eq!(handled.next_deadline_ms, Some(39_000));
+
+    // Nothing is due while paused.
+    let handled = mgr
+        .handle(&mut state, Event::ManualPause { now_ms: 20_000 })
+        .unwrap();
+    assert_eq!(handled.next_deadline_ms, None);
+
+    // Resuming starts a fresh cycle.
+    let handled = mgr
+        .handle(&mut state, Event::ManualResume { now_ms: 25_000 })
+        .unwrap();
+    assert_eq!(handled.next_deadline_ms, Some(25_000 + 3000 + 10_000));
+}
+
+#[test]
+fn set_config_drops_cached_effective_config() {
+    let mut mgr = Manager::new(cfg_with_plan(vec![step(PlanStepKind::Dpms, 10, "old")]));
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 1000 })
+        .unwrap();
+    assert!(actions.is_empty());
+
+    mgr.set_config(cfg_with_plan(vec![step(PlanStepKind::Dpms, 5, "new")]));
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 5000 })
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "new".to_string()
+        }]
+    );
+}
+
+#[test]
+fn compositor_idle_timers_fire_steps_on_idle_reached() {
+    let mut file = cfg_with_plan(vec![
+        step(PlanStepKind::Dpms, 10, "dim"),
+        step(PlanStepKind::LockScreen, 20, "lock"),
+    ]);
+    file.default.compositor_idle_timers = true;
+
+    let mut mgr = Manager::new(file);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    // Without an idle notifier, our own clock still runs the plan.
+    assert_eq!(mgr.idle_timers(&state).len(), 2);
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 10_000 })
+        .unwrap();
+    assert_eq!(actions.len(), 1);
+
+    mgr.handle_event(
+        &mut state,
+        Event::IdleNotifierChanged {
+            available: true,
+            now_ms: 11_000,
+        },
+    )
+    .unwrap();
+    mgr.handle_event(
+        &mut state,
+        Event::UserActivity {
+            kind: ActivityKind::Any,
+            now_ms: 12_000,
+        },
+    )
+    .unwrap();
+    assert_eq!(mgr.idle_timers(&state), vec![(0, 10_000), (1, 30_000)]);
+
+    // Only the compositor says when a step is due.
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 60_000 })
+        .unwrap();
+    assert!(actions.is_empty());
+
+    let epoch = state.cycle_epoch();
+    let stale = epoch.wrapping_sub(1);
+    let actions = mgr
+        .handle_event(
+            &mut state,
+            Event::IdleReached {
+                epoch: stale,
+                step: 0,
+                now_ms: 61_000,
+            },
+        )
+        .unwrap();
+    assert!(actions.is_empty());
+
+    let actions = mgr
+        .handle_event(
+            &mut state,
+            Event::IdleReached {
+                epoch,
+                step: 0,
+                now_ms: 62_000,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        actions,
+        vec![Action::RunCommand {
+            command: "dim".to_string()
+        }]
+    );
+}
+
+#[test]
+fn compositor_idle_timers_start_over_after_a_pause() {
+    let mut file = cfg_with_plan(vec![
+        step(PlanStepKind::Dpms, 10, "dim"),
+        step(PlanStepKind::LockScreen, 20, "lock"),
+    ]);
+    file.default.compositor_idle_timers = true;
+
+    let mut mgr = Manager::new(file);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+    state.set_idle_notifier(true);
+
+    mgr.handle_event(
+        &mut state,
+        Event::MediaInhibitorCount {
+            count: 1,
+            now_ms: 1_000,
+        },
+    )
+    .unwrap();
+    assert!(state.paused());
+
+    // The compositor times out every step during a long video.
+    let epoch = state.cycle_epoch();
+    for (step, now_ms) in [(0, 11_000), (1, 31_000)] {
+        let actions = mgr
+            .handle_event(
+                &mut state,
+                Event::IdleReached {
+                    epoch,
+                    step,
+                    now_ms,
+                },
+            )
+            .unwrap();
+        assert!(actions.is_empty());
+    }
+
+    let handled = mgr
+        .handle(
+            &mut state,
+            Event::MediaInhibitorCount {
+                count: 0,
+                now_ms: 7_200_000,
+            },
+        )
+        .unwrap();
+    assert!(handled.actions.is_empty());
+    assert_eq!(handled.next_deadline_ms, None);
+    assert_ne!(state.cycle_epoch(), epoch);
+    assert_eq!(mgr.idle_timers(&state), vec![(0, 10_000), (1, 30_000)]);
+
+    let actions = mgr
+        .handle_event(&mut state, Event::Tick { now_ms: 7_200_001 })
+        .unwrap();
+    assert!(actions.is_empty());
+}
+
+#[test]
+fn compositor_inhibitor_pauses_only_when_honored() {
+    let mut file = cfg_with_plan(vec![step(PlanStepKind::Dpms, 10, "off")]);
+    file.default.compositor_inhibit = true;
+    file.profiles = vec![Profile {
+        name: "ignore".to_string(),
+        mode: ProfileMode::Overlay,
+        config: PartialConfig {
+            compositor_inhibit: Some(false),
+            ..Default::default()
+        },
+    }];
+
+    let mut mgr = Manager::new(file);
+    let mut state = State::new(0);
+    state.set_plan_source(PlanSource::Desktop);
+
+    mgr.handle_event(
+        &mut state,
+        Event::CompositorInhibitor {
+            active: true,
+            now_ms: 1000,
+        },
+    )
+    .unwrap();
+    assert!(state.paused());
+    assert_eq!(
+        state.active_inhibit_sources(),
+        vec![InhibitSource::Compositor]
+    );
+
+    // Still seen, no longer honored.
+    mgr.handle_event(
+        &mut state,
+        Event::ProfileChanged {
+            name: "ignore".to_string(),
+            now_ms: 2000,
+        },
+    )
+    .unwrap();
+    assert!(state.compositor_inhibitor());
+    assert!(!state.paused());
+}
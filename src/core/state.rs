@@ -1,10 +1,10 @@
 // Author: Dustin Pilgrim
 // License: MIT
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
-use crate::core::config::{PlanSource, PlanStep, PlanStepKind};
-use crate::core::events::PowerState;
+use crate::core::config::{InhibitSource, PlanSource, PlanStep, PlanStepKind};
+use crate::core::events::{BatteryStatus, InhibitHolder, PowerState};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct OneShotKey {
@@ -30,17 +30,33 @@ pub struct State {
     // Inhibitors (counts provided by services)
     app_inhibitor_count: u64,
     media_inhibitor_count: u64,
+    logind_inhibitor_count: u64,
+    capture_inhibitor_count: u64,
+    screensaver_inhibitors: Vec<InhibitHolder>,
+    compositor_inhibitor: bool,
+    // Copied from the effective config; an ignored compositor inhibitor is
+    // still tracked for `info`.
+    honor_compositor_inhibit: bool,
+
+    // max_inhibit_seconds: since when each source has been active unchanged and
+    // without user activity, and which ones have outstayed that limit.
+    inhibit_since_ms: BTreeMap<InhibitSource, u64>,
+    expired_inhibitors: BTreeSet<InhibitSource>,
 
     // Pause policy
     manually_paused: bool,
 
-    // System pause (lid closed / preparing for sleep, etc.)
+    // System pause (preparing for sleep, etc.) and the lid policy's own pause
     system_paused: bool,
+    lid_paused: bool,
 
     // Derived pause (manual OR inhibitors OR system)
     paused: bool,
     pause_started_ms: Option<u64>,
 
+    // Scoped inhibitor holding the current step: last time its timer was frozen.
+    step_held_ms: Option<u64>,
+
     // Mirrors config; manager copies from effective config each event.
     debounce_seconds: u64,
 
@@ -56,6 +72,18 @@ pub struct State {
     power_state: Option<PowerState>,
     plan_source: PlanSource,
 
+    // Lid and what is still plugged in while it's shut (docked mode).
+    lid_closed: bool,
+    logind_docked: bool,
+    external_outputs: usize,
+
+    // Battery charge (and when it was read) and the critical-battery
+    // countdown (warned at, then fired).
+    battery: Option<BatteryStatus>,
+    battery_ms: u64,
+    critical_battery_warned_ms: Option<u64>,
+    critical_battery_fired: bool,
+
     // Timing (ms since epoch, supplied by Tick/UserActivity/etc.)
     last_activity_ms: u64,
     last_action_ms: u64,
@@ -64,6 +92,14 @@ pub struct State {
     step_index: usize,
     step_base_ms: u64,
 
+    // Start of the current cycle (0, or the post-lock step); the epoch bumps
+    // on every restart so stale compositor idle timers can be told apart.
+    cycle_start_idx: usize,
+    cycle_epoch: u64,
+    // Furthest step whose compositor idle timer has run out this cycle.
+    idle_reached: Option<usize>,
+    idle_notifier: bool,
+
     // One-shot notification (per step / per idle cycle)
     sent_pre_action_notify: bool,
     pre_action_notify_ms: u64,
@@ -95,10 +131,19 @@ impl State {
         Self {
             app_inhibitor_count: 0,
             media_inhibitor_count: 0,
+            logind_inhibitor_count: 0,
+            capture_inhibitor_count: 0,
+            screensaver_inhibitors: Vec::new(),
+            compositor_inhibitor: false,
+            honor_compositor_inhibit: false,
+            inhibit_since_ms: BTreeMap::new(),
+            expired_inhibitors: BTreeSet::new(),
             manually_paused: false,
             system_paused: false,
+            lid_paused: false,
             paused: false,
             pause_started_ms: None,
+            step_held_ms: None,
 
             debounce_seconds: 0,
             debounce_pending: true, // boot behaves like "fresh idle cycle"
@@ -109,12 +154,26 @@ impl State {
             power_state: None,
             plan_source: PlanSource::Desktop,
 
+            lid_closed: false,
+            logind_docked: false,
+            external_outputs: 0,
+
+            battery: None,
+            battery_ms: 0,
+            critical_battery_warned_ms: None,
+            critical_battery_fired: false,
+
             last_activity_ms: now_ms,
             last_action_ms: now_ms,
 
             step_index: 0,
             step_base_ms: now_ms,
 
+            cycle_start_idx: 0,
+            cycle_epoch: 0,
+            idle_reached: None,
+            idle_notifier: false,
+
             sent_pre_action_notify: false,
             pre_action_notify_ms: 0,
 
@@ -282,6 +341,11 @@ impl State {
         self.pause_started_ms.take()
     }
 
+    /// Swap the hold timestamp, returning the previous one.
+    pub fn replace_step_held_ms(&mut self, v: Option<u64>) -> Option<u64> {
+        std::mem::replace(&mut self.step_held_ms, v)
+    }
+
     // ---------------- getters ----------------
 
     pub fn app_inhibitor_count(&self) -> u64 {
@@ -292,12 +356,38 @@ impl State {
         self.media_inhibitor_count
     }
 
+    pub fn logind_inhibitor_count(&self) -> u64 {
+        self.logind_inhibitor_count
+    }
+
+    pub fn capture_inhibitor_count(&self) -> u64 {
+        self.capture_inhibitor_count
+    }
+
+    pub fn screensaver_inhibitor_count(&self) -> u64 {
+        self.screensaver_inhibitors.len() as u64
+    }
+
+    pub fn screensaver_inhibitors(&self) -> &[InhibitHolder] {
+        &self.screensaver_inhibitors
+    }
+
+    /// Whether a surface inhibitor holds the compositor awake, honored or not.
+    pub fn compositor_inhibitor(&self) -> bool {
+        self.compositor_inhibitor
+    }
+
+    pub fn honors_compositor_inhibit(&self) -> bool {
+        self.honor_compositor_inhibit
+    }
+
     pub fn manually_paused(&self) -> bool {
         self.manually_paused
     }
 
+    /// Paused by the system: sleeping, or the lid policy.
     pub fn system_paused(&self) -> bool {
-        self.system_paused
+        self.system_paused || self.lid_paused
     }
 
     pub fn paused(&self) -> bool {
@@ -316,8 +406,82 @@ impl State {
         self.plan_source
     }
 
+    pub fn power_state(&self) -> Option<PowerState> {
+        self.power_state
+    }
+
+    pub fn lid_closed(&self) -> bool {
+        self.lid_closed
+    }
+
+    /// Lid shut while an external display (or dock) keeps the laptop in use.
+    pub fn docked(&self) -> bool {
+        self.lid_closed && (self.logind_docked || self.external_outputs > 0)
+    }
+
+    pub fn battery(&self) -> Option<BatteryStatus> {
+        self.battery
+    }
+
+    pub fn battery_ms(&self) -> u64 {
+        self.battery_ms
+    }
+
+    pub fn critical_battery_warned_ms(&self) -> Option<u64> {
+        self.critical_battery_warned_ms
+    }
+
+    pub fn critical_battery_fired(&self) -> bool {
+        self.critical_battery_fired
+    }
+
     pub fn inhibitors_active(&self) -> bool {
-        self.app_inhibitor_count > 0 || self.media_inhibitor_count > 0
+        self.active_inhibit_sources()
+            .into_iter()
+            .any(|src| src != InhibitSource::Manual)
+    }
+
+    /// Inhibit sources that are currently active, manual pause included.
+    /// Expired ones (see `expire_inhibitor`) no longer count.
+    pub fn active_inhibit_sources(&self) -> Vec<InhibitSource> {
+        let mut out = self.raw_inhibit_sources();
+        out.retain(|src| !self.expired_inhibitors.contains(src));
+        out
+    }
+
+    fn raw_inhibit_sources(&self) -> Vec<InhibitSource> {
+        let mut out = Vec::new();
+        if self.manually_paused {
+            out.push(InhibitSource::Manual);
+        }
+        if self.app_inhibitor_count > 0 {
+            out.push(InhibitSource::Apps);
+        }
+        if self.media_inhibitor_count > 0 {
+            out.push(InhibitSource::Media);
+        }
+        if self.capture_inhibitor_count > 0 {
+            out.push(InhibitSource::Capture);
+        }
+        if self.logind_inhibitor_count > 0 {
+            out.push(InhibitSource::Logind);
+        }
+        if !self.screensaver_inhibitors.is_empty() {
+            out.push(InhibitSource::ScreenSaver);
+        }
+        if self.compositor_inhibitor && self.honor_compositor_inhibit {
+            out.push(InhibitSource::Compositor);
+        }
+        out
+    }
+
+    /// When `src` started inhibiting unchanged, if it is active.
+    pub fn inhibit_since_ms(&self, src: InhibitSource) -> Option<u64> {
+        self.inhibit_since_ms.get(&src).copied()
+    }
+
+    pub fn expired_inhibitors(&self) -> &BTreeSet<InhibitSource> {
+        &self.expired_inhibitors
     }
 
     pub fn step_index(&self) -> usize {
@@ -336,6 +500,22 @@ impl State {
         self.pre_action_notify_ms
     }
 
+    pub fn cycle_start_idx(&self) -> usize {
+        self.cycle_start_idx
+    }
+
+    pub fn cycle_epoch(&self) -> u64 {
+        self.cycle_epoch
+    }
+
+    pub fn idle_reached(&self) -> Option<usize> {
+        self.idle_reached
+    }
+
+    pub fn idle_notifier(&self) -> bool {
+        self.idle_notifier
+    }
+
     pub fn debounce_pending(&self) -> bool {
         self.debounce_pending
     }
@@ -343,13 +523,86 @@ impl State {
     // ---------------- setters ----------------
 
     pub fn set_app_inhibitor_count(&mut self, count: u64) {
+        if self.app_inhibitor_count != count {
+            self.inhibitor_changed(InhibitSource::Apps);
+        }
         self.app_inhibitor_count = count;
     }
 
     pub fn set_media_inhibitor_count(&mut self, count: u64) {
+        if self.media_inhibitor_count != count {
+            self.inhibitor_changed(InhibitSource::Media);
+        }
         self.media_inhibitor_count = count;
     }
 
+    pub fn set_logind_inhibitor_count(&mut self, count: u64) {
+        if self.logind_inhibitor_count != count {
+            self.inhibitor_changed(InhibitSource::Logind);
+        }
+        self.logind_inhibitor_count = count;
+    }
+
+    pub fn set_capture_inhibitor_count(&mut self, count: u64) {
+        if self.capture_inhibitor_count != count {
+            self.inhibitor_changed(InhibitSource::Capture);
+        }
+        self.capture_inhibitor_count = count;
+    }
+
+    pub fn set_screensaver_inhibitors(&mut self, holders: Vec<InhibitHolder>) {
+        if self.screensaver_inhibitors != holders {
+            self.inhibitor_changed(InhibitSource::ScreenSaver);
+        }
+        self.screensaver_inhibitors = holders;
+    }
+
+    pub fn set_compositor_inhibitor(&mut self, active: bool) {
+        if self.compositor_inhibitor != active {
+            self.inhibitor_changed(InhibitSource::Compositor);
+        }
+        self.compositor_inhibitor = active;
+    }
+
+    pub fn set_honor_compositor_inhibit(&mut self, v: bool) {
+        self.honor_compositor_inhibit = v;
+    }
+
+    /// A changed inhibitor starts a fresh max_inhibit_seconds window.
+    fn inhibitor_changed(&mut self, src: InhibitSource) {
+        self.inhibit_since_ms.remove(&src);
+        self.expired_inhibitors.remove(&src);
+    }
+
+    /// Start the clock for newly active sources and forget inactive ones.
+    /// Manual pauses have their own duration and are never tracked.
+    pub fn stamp_inhibitors(&mut self, now_ms: u64) {
+        let active: Vec<InhibitSource> = self
+            .raw_inhibit_sources()
+            .into_iter()
+            .filter(|src| *src != InhibitSource::Manual)
+            .collect();
+
+        self.inhibit_since_ms.retain(|src, _| active.contains(src));
+        self.expired_inhibitors.retain(|src| active.contains(src));
+        for src in active {
+            self.inhibit_since_ms.entry(src).or_insert(now_ms);
+        }
+    }
+
+    /// User activity: every active inhibitor counts again, from now.
+    pub fn restart_inhibitor_clocks(&mut self, now_ms: u64) {
+        self.expired_inhibitors.clear();
+        for since in self.inhibit_since_ms.values_mut() {
+            *since = now_ms;
+        }
+    }
+
+    /// Stop honoring `src` until it changes or the user is active again.
+    pub fn expire_inhibitor(&mut self, src: InhibitSource) {
+        self.expired_inhibitors.insert(src);
+    }
+
     pub fn set_manually_paused(&mut self, v: bool) {
         self.manually_paused = v;
     }
@@ -358,6 +611,10 @@ impl State {
         self.system_paused = v;
     }
 
+    pub fn set_lid_paused(&mut self, v: bool) {
+        self.lid_paused = v;
+    }
+
     pub fn set_paused(&mut self, v: bool) {
         self.paused = v;
         if !v {
@@ -382,6 +639,37 @@ impl State {
         self.plan_source = src;
     }
 
+    pub fn set_lid_closed(&mut self, v: bool) {
+        self.lid_closed = v;
+    }
+
+    pub fn set_logind_docked(&mut self, v: bool) {
+        self.logind_docked = v;
+    }
+
+    pub fn set_external_outputs(&mut self, count: usize) {
+        self.external_outputs = count;
+    }
+
+    pub fn set_battery(&mut self, battery: BatteryStatus, now_ms: u64) {
+        self.battery = Some(battery);
+        self.battery_ms = now_ms;
+    }
+
+    pub fn set_critical_battery_warned_ms(&mut self, t: u64) {
+        self.critical_battery_warned_ms = Some(t);
+    }
+
+    pub fn mark_critical_battery_fired(&mut self) {
+        self.critical_battery_fired = true;
+    }
+
+    /// Re-arm the critical-battery action (charging again, resumed from sleep, ...).
+    pub fn reset_critical_battery(&mut self) {
+        self.critical_battery_warned_ms = None;
+        self.critical_battery_fired = false;
+    }
+
     pub fn set_debounce_seconds(&mut self, secs: u64) {
         self.debounce_seconds = secs;
     }
@@ -413,6 +701,26 @@ impl State {
         self.debounce_pending = v;
     }
 
+    pub fn set_idle_notifier(&mut self, v: bool) {
+        self.idle_notifier = v;
+    }
+
+    pub fn mark_idle_reached(&mut self, step: usize) {
+        self.idle_reached = self.idle_reached.max(Some(step));
+    }
+
+    /// New compositor idle timers from the current step, forgetting any
+    /// that already ran out.
+    pub fn restart_idle_timers(&mut self) {
+        self.restart_cycle(self.step_index);
+    }
+
+    fn restart_cycle(&mut self, start_idx: usize) {
+        self.cycle_start_idx = start_idx;
+        self.cycle_epoch = self.cycle_epoch.wrapping_add(1);
+        self.idle_reached = None;
+    }
+
     // ---------------- cycle control ----------------
 
     /// Full idle cycle reset (unlocked activity, profile/power transitions, etc.)
@@ -421,6 +729,7 @@ impl State {
 
         self.step_index = 0;
         self.step_base_ms = now_ms;
+        self.restart_cycle(0);
 
         self.sent_pre_action_notify = false;
         self.pre_action_notify_ms = 0;
@@ -431,6 +740,7 @@ impl State {
 
         // Reset pause timestamp for a fresh cycle.
         self.pause_started_ms = None;
+        self.step_held_ms = None;
     }
 
     /// Restart timers AND rewind to the post-lock start step so the post-lock
@@ -440,6 +750,7 @@ impl State {
 
         self.step_index = post_lock_start_idx;
         self.step_base_ms = now_ms;
+        self.restart_cycle(post_lock_start_idx);
 
         self.sent_pre_action_notify = false;
         self.pre_action_notify_ms = 0;
@@ -450,6 +761,7 @@ impl State {
 
         // Reset pause timestamp for a restarted segment.
         self.pause_started_ms = None;
+        self.step_held_ms = None;
     }
 }
 
@@ -2,9 +2,10 @@
 // License: MIT
 
 use crate::core::{action::Action, events::Event, manager_msg::ManagerMsg};
+use crate::services::login1::{self, SleepKind};
 
 use tokio::process::Command;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
 
 use std::process::Stdio;
 
@@ -18,7 +19,7 @@ impl Daemon {
     ) -> Result<(), AnyError> {
         match action {
             Action::RunLockScreen { command, use_loginctl } => {
-                Self::spawn_lock_screen(tx, command, use_loginctl);
+                Self::spawn_lock_screen(tx, command, use_loginctl, None);
             }
 
             Action::RunCommand { command } => {
@@ -45,13 +46,20 @@ impl Daemon {
             }
 
             Action::LockSession => {
-                eventline::info!("lock-session: loginctl lock-session");
-                crate::core::utils::run_shell_command_silent("loginctl lock-session")
-                    .map_err(into_any_error)?;
+                eventline::info!("lock-session: login1 Session.Lock");
+                login1::Session::current().await?.lock().await?;
             }
 
             Action::Suspend => {
-                eventline::info!("suspend requested");
+                eventline::info!("suspend: login1 Suspend");
+                let conn = login1::system_connection().await?;
+                login1::suspend(&conn).await?;
+            }
+
+            Action::Hibernate => self.native_sleep(SleepKind::Hibernate).await?,
+            Action::HybridSleep => self.native_sleep(SleepKind::HybridSleep).await?,
+            Action::SuspendThenHibernate => {
+                self.native_sleep(SleepKind::SuspendThenHibernate).await?
             }
 
             #[cfg(test)]
@@ -61,7 +69,28 @@ impl Daemon {
         Ok(())
     }
 
-    fn spawn_lock_screen(tx: mpsc::Sender<ManagerMsg>, command: String, use_loginctl: bool) {
+    async fn native_sleep(&self, kind: SleepKind) -> Result<(), AnyError> {
+        eventline::info!("{kind}: login1 request");
+        let conn = login1::system_connection().await?;
+        let used = login1::sleep(&conn, kind).await?;
+        eventline::info!("{kind}: login1 {used} requested");
+        Ok(())
+    }
+
+    async fn set_locked_hint(session: Option<&login1::Session>, locked: bool) {
+        let Some(session) = session else { return };
+        if let Err(e) = session.set_locked_hint(locked).await {
+            eventline::warn!("lock: {e}");
+        }
+    }
+
+    /// `running` fires once the locker process has been spawned.
+    pub(super) fn spawn_lock_screen(
+        tx: mpsc::Sender<ManagerMsg>,
+        command: String,
+        use_loginctl: bool,
+        running: Option<oneshot::Sender<()>>,
+    ) {
         tokio::spawn(async move {
             let _ = tx
                 .send(ManagerMsg::Event(Event::SessionLocked {
@@ -69,9 +98,19 @@ impl Daemon {
                 }))
                 .await;
 
-            if use_loginctl {
-                eventline::info!("lock-session: loginctl lock-session");
-                let _ = crate::core::utils::run_shell_command_silent("loginctl lock-session");
+            let session = match login1::Session::current().await {
+                Ok(s) => Some(s),
+                Err(e) => {
+                    eventline::warn!("lock: {e}; skipping Session.Lock/LockedHint");
+                    None
+                }
+            };
+
+            if let Some(session) = session.as_ref().filter(|_| use_loginctl) {
+                eventline::info!("lock-session: login1 Session.Lock");
+                if let Err(e) = session.lock().await {
+                    eventline::warn!("lock-session: {e}");
+                }
             }
 
             eventline::info!("lock: {} (await exit)", command);
@@ -97,8 +136,16 @@ impl Daemon {
                 }
             };
 
+            Self::set_locked_hint(session.as_ref(), true).await;
+
+            if let Some(running) = running {
+                let _ = running.send(());
+            }
+
             let _ = child.wait().await;
 
+            Self::set_locked_hint(session.as_ref(), false).await;
+
             let _ = tx
                 .send(ManagerMsg::Event(Event::SessionUnlocked {
                     now_ms: crate::core::utils::now_ms(),
@@ -3,10 +3,11 @@
 
 mod actions;
 mod run;
+mod sleep;
 
 use crate::core::{
     action::Action,
-    config::{ConfigFile, PlanSource, Pattern},
+    config::{ConfigFile, InhibitRule, LidPolicy, MediaBackend, PlanSource, Pattern},
     events::{Event, PowerState},
     manager::Manager,
     manager_msg::ManagerMsg,
@@ -42,16 +43,32 @@ pub struct Daemon {
     config_path: PathBuf,
 
     inhibit_apps: Vec<Pattern>,
+    inhibit_rules: Vec<InhibitRule>,
 
     monitor_media: bool,
     ignore_remote_media: bool,
+    media_backend: MediaBackend,
     media_blacklist: Vec<Pattern>,
 
+    monitor_capture: bool,
+    capture_blacklist: Vec<Pattern>,
+
+    screensaver_inhibit: bool,
+    logind_inhibit_delay: bool,
+
     inhibit_epoch: u64,
     enable_loginctl: bool,
 
+    lock_before_sleep: bool,
+    sleep_delay: crate::services::login1::InhibitLock,
+    suspend_on_lid: bool,
+    lid_switch: crate::services::login1::InhibitLock,
+
     chassis: crate::core::utils::ChassisKind,
     bad_profile_logged: bool,
+
+    /// Next `Tick` the manager asked for; published to the ticker.
+    next_deadline_ms: Option<u64>,
 }
 
 impl Daemon {
@@ -95,21 +112,31 @@ impl Daemon {
             });
 
         let inhibit_apps = effective.inhibit_apps.clone();
+        let inhibit_rules = effective.inhibit_rules.clone();
         let monitor_media = effective.monitor_media;
         let ignore_remote_media = effective.ignore_remote_media;
+        let media_backend = effective.media_backend;
         let media_blacklist = effective.media_blacklist.clone();
+        let monitor_capture = effective.monitor_capture;
+        let capture_blacklist = effective.capture_blacklist.clone();
+        let screensaver_inhibit = effective.screensaver_inhibit;
+        let logind_inhibit_delay = effective.logind_inhibit_delay;
 
-        let enable_loginctl = effective.plan.iter().any(|s| s.is_lock() && s.use_loginctl);
+        let lock_before_sleep = effective.lock_before_sleep;
+        let enable_loginctl = lock_before_sleep
+            || effective.plan.iter().any(|s| s.is_lock() && s.use_loginctl);
 
         eventline::debug!(
-            "daemon: chassis={:?}, plan_src={:?}, active_profile={:?}, monitor_media={}, ignore_remote_media={}, media_blacklist_len={}, inhibit_apps_len={}, enable_loginctl={}, config_path={}",
+            "daemon: chassis={:?}, plan_src={:?}, active_profile={:?}, monitor_media={}, ignore_remote_media={}, media_backend={}, media_blacklist_len={}, inhibit_apps_len={}, inhibit_rules_len={}, enable_loginctl={}, config_path={}",
             chassis,
             plan_src,
             cfg_file.active_profile,
             monitor_media,
             ignore_remote_media,
+            media_backend.as_str(),
             media_blacklist.len(),
             inhibit_apps.len(),
+            inhibit_rules.len(),
             enable_loginctl,
             config_path.display(),
         );
@@ -119,8 +146,10 @@ impl Daemon {
 
         match plan_src {
             PlanSource::Ac => state.set_power_state(PowerState::OnAC),
-            PlanSource::Battery => state.set_power_state(PowerState::OnBattery),
-            PlanSource::Desktop => {}
+            PlanSource::Battery | PlanSource::BatteryBelow(_) => {
+                state.set_power_state(PowerState::OnBattery)
+            }
+            PlanSource::Desktop | PlanSource::Docked => {}
         }
 
         state.set_active_profile(cfg_file.active_profile.clone());
@@ -130,13 +159,24 @@ impl Daemon {
             state,
             config_path,
             inhibit_apps,
+            inhibit_rules,
             monitor_media,
             ignore_remote_media,
+            media_backend,
             media_blacklist,
+            monitor_capture,
+            capture_blacklist,
+            screensaver_inhibit,
+            logind_inhibit_delay,
             inhibit_epoch: 0,
             enable_loginctl,
+            lock_before_sleep,
+            sleep_delay: crate::services::login1::InhibitLock::sleep_delay(),
+            suspend_on_lid: false,
+            lid_switch: crate::services::login1::InhibitLock::lid_switch(),
             chassis,
             bad_profile_logged: false,
+            next_deadline_ms: None,
         }
     }
 
@@ -152,14 +192,25 @@ impl Daemon {
             c
         });
 
+        self.lock_before_sleep = effective.lock_before_sleep;
+        self.sync_sleep_delay();
+        self.suspend_on_lid = effective.lid_policy == LidPolicy::Suspend;
+        self.sync_lid_switch_block();
+
         self.inhibit_epoch = self.inhibit_epoch.wrapping_add(1);
 
         let msg = ManagerMsg::UpdateInhibitRules {
             epoch: self.inhibit_epoch,
             inhibit_apps: effective.inhibit_apps.clone(),
+            inhibit_rules: effective.inhibit_rules.clone(),
             monitor_media: effective.monitor_media,
             ignore_remote_media: effective.ignore_remote_media,
+            media_backend: effective.media_backend,
             media_blacklist: effective.media_blacklist.clone(),
+            monitor_capture: effective.monitor_capture,
+            capture_blacklist: effective.capture_blacklist.clone(),
+            screensaver_inhibit: effective.screensaver_inhibit,
+            logind_inhibit_delay: effective.logind_inhibit_delay,
         };
 
         let _ = tx.try_send(msg);
@@ -167,33 +218,50 @@ impl Daemon {
 
     fn handle_one_event_scoped(&mut self, event: Event) -> Vec<Action> {
         if matches!(event, Event::Tick { .. }) {
-            return self
-                .manager
-                .handle_event(&mut self.state, event)
-                .unwrap_or_else(|e| {
-                    self.log_handle_event_error_once(&e);
-                    Vec::new()
-                });
+            return self.handle_event_tracking_deadline(event);
         }
 
         eventline::scope!("event", {
             eventline::debug!("incoming: {:?}", event);
 
-            match self.manager.handle_event(&mut self.state, event.clone()) {
-                Ok(actions) => {
-                    if !actions.is_empty() {
-                        eventline::debug!("actions: {:?}", actions);
-                    }
-                    actions
-                }
-                Err(e) => {
-                    self.log_handle_event_error_once(&e);
-                    Vec::new()
-                }
+            let actions = self.handle_event_tracking_deadline(event.clone());
+            if !actions.is_empty() {
+                eventline::debug!("actions: {:?}", actions);
             }
+            actions
         })
     }
 
+    /// Run one event through the manager and remember its next deadline.
+    fn handle_event_tracking_deadline(&mut self, event: Event) -> Vec<Action> {
+        match self.manager.handle(&mut self.state, event) {
+            Ok(handled) => {
+                self.next_deadline_ms = handled.next_deadline_ms;
+                handled.actions
+            }
+            Err(e) => {
+                self.log_handle_event_error_once(&e);
+                // State may have moved before the error.
+                self.next_deadline_ms = self.manager.next_deadline(&self.state);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Per-step compositor timers for the Wayland service; none unless
+    /// `compositor_idle_timers` is on.
+    fn idle_timers(&self) -> crate::services::wayland::IdleTimers {
+        let steps = self.manager.idle_timers(&self.state);
+        if steps.is_empty() {
+            return Default::default();
+        }
+        crate::services::wayland::IdleTimers {
+            epoch: self.state.cycle_epoch(),
+            steps,
+            honor_inhibitors: self.state.honors_compositor_inhibit(),
+        }
+    }
+
     fn log_handle_event_error_once(&mut self, e: &crate::core::error::Error) {
         let s = format!("{e:?}");
         if s.contains("ProfileNotFound") {
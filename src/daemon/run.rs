@@ -28,6 +28,17 @@ impl Daemon {
             eventline::warn!("ipc: failed to start: {}", e);
         }
 
+        let (status_tx, status_rx) = watch::channel(self.manager.control_status(&self.state));
+        if let Err(e) = crate::ipc::dbus::spawn_dbus_control(tx.clone(), status_rx).await {
+            eventline::warn!("ipc: {}", e);
+        }
+
+        // Open the shared system bus on this runtime, which outlives the
+        // listener thread's own.
+        if let Err(e) = crate::services::dbus::system_bus().await {
+            eventline::warn!("dbus: no system bus: {e}");
+        }
+
         {
             let sink: Arc<dyn EventSink> = Arc::new(MpscEventSink { tx: tx.clone() });
 
@@ -41,23 +52,61 @@ impl Daemon {
             }
         }
 
-        tokio::spawn(crate::services::ticker::run_ticker(tx.clone()));
+        self.next_deadline_ms = self.manager.next_deadline(&self.state);
+        let (deadline_tx, deadline_rx) = watch::channel(self.next_deadline_ms);
+        tokio::spawn(crate::services::ticker::run_ticker(tx.clone(), deadline_rx));
 
         let (app_rules_tx, app_rules_rx) = watch::channel(crate::services::app_inhibit::AppRules {
             epoch: self.inhibit_epoch,
             apps: self.inhibit_apps.clone(),
+            rules: self.inhibit_rules.clone(),
         });
-        tokio::spawn(crate::services::app_inhibit::run_app_inhibit(tx.clone(), app_rules_rx));
+        let (toplevels_tx, toplevels_rx) = watch::channel(crate::services::wayland::Toplevels::default());
+        let (idle_timers_tx, idle_timers_rx) = watch::channel(self.idle_timers());
+        tokio::spawn(crate::services::app_inhibit::run_app_inhibit(
+            tx.clone(),
+            app_rules_rx,
+            toplevels_rx,
+        ));
 
         let (media_rules_tx, media_rules_rx) =
             watch::channel(crate::services::media::MediaRules {
                 epoch: self.inhibit_epoch,
                 monitor_media: self.monitor_media,
                 ignore_remote_media: self.ignore_remote_media,
+                media_backend: self.media_backend,
                 media_blacklist: self.media_blacklist.clone(),
             });
         tokio::spawn(crate::services::media::run_media(tx.clone(), media_rules_rx));
 
+        let (capture_rules_tx, capture_rules_rx) =
+            watch::channel(crate::services::capture::CaptureRules {
+                epoch: self.inhibit_epoch,
+                enabled: self.monitor_capture,
+                blacklist: self.capture_blacklist.clone(),
+            });
+        tokio::spawn(crate::services::capture::run_capture(tx.clone(), capture_rules_rx));
+
+        let (screensaver_rules_tx, screensaver_rules_rx) =
+            watch::channel(crate::services::screensaver::ScreenSaverRules {
+                epoch: self.inhibit_epoch,
+                enabled: self.screensaver_inhibit,
+            });
+        tokio::spawn(crate::services::screensaver::run_screensaver(
+            tx.clone(),
+            screensaver_rules_rx,
+        ));
+
+        let (logind_rules_tx, logind_rules_rx) =
+            watch::channel(crate::services::logind_inhibit::LogindInhibitRules {
+                epoch: self.inhibit_epoch,
+                honor_delay: self.logind_inhibit_delay,
+            });
+        tokio::spawn(crate::services::logind_inhibit::run_logind_inhibit(
+            tx.clone(),
+            logind_rules_rx,
+        ));
+
         if matches!(self.chassis, crate::core::utils::ChassisKind::Laptop) {
             tokio::spawn(crate::services::power::run_power(tx.clone()));
         } else {
@@ -68,7 +117,8 @@ impl Daemon {
             let tx = tx.clone();
             let shutdown = shutdown.clone();
             async move {
-                let _ = crate::services::wayland::run_wayland(tx, shutdown).await;
+                crate::services::wayland::run_wayland(tx, shutdown, toplevels_tx, idle_timers_rx)
+                    .await;
             }
         });
 
@@ -91,39 +141,76 @@ impl Daemon {
 
                     match msg {
                         ManagerMsg::Event(event) => {
-                            let refresh_after = matches!(event, Event::ProfileChanged{..} | Event::PowerChanged{..});
+                            let src_before = self.state.plan_source();
+                            let mut refresh_after = matches!(event, Event::ProfileChanged{..} | Event::PowerChanged{..});
+                            let before_sleep = matches!(event, Event::PrepareForSleep{..});
+                            let resumed = matches!(event, Event::ResumedFromSleep{..});
 
                             let actions = self.handle_one_event_scoped(event);
+                            // Battery tiers switch plans without a PowerChanged.
+                            refresh_after |= self.state.plan_source() != src_before;
 
                             if refresh_after {
                                 self.push_inhibit_rules_from_effective(&tx);
                             }
 
-                            for action in actions {
-                                if let Err(e) = self.exec_action_with_tx(action, tx.clone()).await {
-                                    eventline::error!("action failed: {}", e);
+                            if resumed {
+                                self.sync_sleep_delay();
+                            }
+
+                            if before_sleep {
+                                self.exec_before_sleep(actions, tx.clone()).await;
+                            } else {
+                                for action in actions {
+                                    if let Err(e) = self.exec_action_with_tx(action, tx.clone()).await {
+                                        eventline::error!("action failed: {}", e);
+                                    }
                                 }
                             }
                         }
 
-                        ManagerMsg::UpdateInhibitRules { epoch, inhibit_apps, monitor_media, ignore_remote_media, media_blacklist } => {
+                        ManagerMsg::UpdateInhibitRules { epoch, inhibit_apps, inhibit_rules, monitor_media, ignore_remote_media, media_backend, media_blacklist, monitor_capture, capture_blacklist, screensaver_inhibit, logind_inhibit_delay } => {
                             self.inhibit_epoch = epoch;
                             self.inhibit_apps = inhibit_apps.clone();
+                            self.inhibit_rules = inhibit_rules.clone();
                             self.monitor_media = monitor_media;
                             self.ignore_remote_media = ignore_remote_media;
+                            self.media_backend = media_backend;
                             self.media_blacklist = media_blacklist.clone();
+                            self.monitor_capture = monitor_capture;
+                            self.capture_blacklist = capture_blacklist.clone();
+                            self.screensaver_inhibit = screensaver_inhibit;
+                            self.logind_inhibit_delay = logind_inhibit_delay;
 
                             let _ = app_rules_tx.send(crate::services::app_inhibit::AppRules {
                                 epoch,
                                 apps: inhibit_apps,
+                                rules: inhibit_rules,
                             });
 
                             let _ = media_rules_tx.send(crate::services::media::MediaRules {
                                 epoch,
                                 monitor_media,
                                 ignore_remote_media,
+                                media_backend,
                                 media_blacklist,
                             });
+
+                            let _ = capture_rules_tx.send(crate::services::capture::CaptureRules {
+                                epoch,
+                                enabled: monitor_capture,
+                                blacklist: capture_blacklist,
+                            });
+
+                            let _ = screensaver_rules_tx.send(crate::services::screensaver::ScreenSaverRules {
+                                epoch,
+                                enabled: screensaver_inhibit,
+                            });
+
+                            let _ = logind_rules_tx.send(crate::services::logind_inhibit::LogindInhibitRules {
+                                epoch,
+                                honor_delay: logind_inhibit_delay,
+                            });
                         }
 
                         ManagerMsg::List { kind, reply } => {
@@ -145,10 +232,11 @@ impl Daemon {
                             let raw = name.clone().unwrap_or_else(|| "none".to_string());
 
                             let ev = Event::ProfileChanged { name: raw, now_ms };
-                            let res = self.manager.handle_event(&mut self.state, ev);
+                            let res = self.manager.handle(&mut self.state, ev);
 
                             let out = match res {
-                                Ok(_actions) => {
+                                Ok(handled) => {
+                                    self.next_deadline_ms = handled.next_deadline_ms;
                                     self.bad_profile_logged = false;
                                     let shown = name.unwrap_or_else(|| "none".to_string());
 
@@ -192,11 +280,12 @@ impl Daemon {
 
                                     let ev = Event::ProfileChanged { name: desired.clone(), now_ms };
 
-                                    match self.manager.handle_event(&mut self.state, ev) {
-                                        Ok(actions) => {
+                                    match self.manager.handle(&mut self.state, ev) {
+                                        Ok(handled) => {
                                             self.bad_profile_logged = false;
+                                            self.next_deadline_ms = handled.next_deadline_ms;
 
-                                            for action in actions {
+                                            for action in handled.actions {
                                                 if let Err(e) = self.exec_action_with_tx(action, tx.clone()).await {
                                                     eventline::error!("action failed: {}", e);
                                                 }
@@ -226,6 +315,27 @@ impl Daemon {
                             break;
                         }
                     }
+
+                    let status = self.manager.control_status(&self.state);
+                    status_tx.send_if_modified(|cur| {
+                        if *cur == status {
+                            return false;
+                        }
+                        *cur = status;
+                        true
+                    });
+
+                    let next = self.next_deadline_ms;
+                    deadline_tx.send_if_modified(|cur| std::mem::replace(cur, next) != next);
+
+                    let timers = self.idle_timers();
+                    idle_timers_tx.send_if_modified(|cur| {
+                        if *cur == timers {
+                            return false;
+                        }
+                        *cur = timers;
+                        true
+                    });
                 }
             }
         }
@@ -0,0 +1,132 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use crate::core::{action::Action, manager_msg::ManagerMsg};
+
+use tokio::process::Command;
+use tokio::sync::{mpsc, oneshot};
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use super::Daemon;
+
+/// How long we hold up sleep waiting for the locker / pre-suspend command.
+/// Kept under logind's default InhibitDelayMaxSec (5s).
+const SLEEP_DELAY_TIMEOUT: Duration = Duration::from_secs(4);
+
+impl Daemon {
+    /// Hold the login1 sleep delay lock iff `lock_before_sleep` is enabled.
+    pub(super) fn sync_sleep_delay(&self) {
+        if !self.lock_before_sleep {
+            if self.sleep_delay.release() {
+                eventline::info!("sleep-delay: released (lock_before_sleep disabled)");
+            }
+            return;
+        }
+
+        if self.sleep_delay.is_held() {
+            return;
+        }
+
+        let delay = self.sleep_delay.clone();
+        tokio::spawn(async move {
+            let res = match crate::services::login1::system_connection().await {
+                Ok(conn) => delay.acquire(&conn).await,
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Ok(()) => eventline::info!("sleep-delay: acquired"),
+                Err(e) => eventline::warn!("sleep-delay: {e}"),
+            }
+        });
+    }
+
+    /// Hold logind's handle-lid-switch block lock iff `lid_policy` is
+    /// "suspend", so a lid close doesn't suspend through both of us.
+    pub(super) fn sync_lid_switch_block(&self) {
+        if !self.suspend_on_lid {
+            if self.lid_switch.release() {
+                eventline::info!("lid-switch: released (lid_policy is not suspend)");
+            }
+            return;
+        }
+
+        if self.lid_switch.is_held() {
+            return;
+        }
+
+        let lid_switch = self.lid_switch.clone();
+        tokio::spawn(async move {
+            let res = match crate::services::login1::system_connection().await {
+                Ok(conn) => lid_switch.acquire(&conn).await,
+                Err(e) => Err(e),
+            };
+
+            match res {
+                Ok(()) => eventline::info!("lid-switch: blocked logind's lid handling"),
+                Err(e) => eventline::warn!(
+                    "lid-switch: {e}; set HandleLidSwitch=ignore or logind suspends too"
+                ),
+            }
+        });
+    }
+
+    /// Run the PrepareForSleep actions, then release the delay lock once the
+    /// locker is running and pre-suspend commands have exited (or on timeout).
+    pub(super) async fn exec_before_sleep(
+        &mut self,
+        actions: Vec<Action>,
+        tx: mpsc::Sender<ManagerMsg>,
+    ) {
+        let mut pending = Vec::new();
+
+        for action in actions {
+            match action {
+                Action::RunLockScreen { command, use_loginctl } => {
+                    let (running_tx, running_rx) = oneshot::channel();
+                    Self::spawn_lock_screen(tx.clone(), command, use_loginctl, Some(running_tx));
+                    pending.push(running_rx);
+                }
+
+                Action::RunCommand { command } => {
+                    let (done_tx, done_rx) = oneshot::channel();
+                    eventline::info!("pre-sleep: {} (await exit)", command);
+                    tokio::spawn(async move {
+                        let status = Command::new("sh")
+                            .arg("-lc")
+                            .arg(&command)
+                            .stdout(Stdio::null())
+                            .stderr(Stdio::null())
+                            .status()
+                            .await;
+                        if let Err(e) = status {
+                            eventline::error!("pre-sleep: {command} failed: {e}");
+                        }
+                        let _ = done_tx.send(());
+                    });
+                    pending.push(done_rx);
+                }
+
+                other => {
+                    if let Err(e) = self.exec_action_with_tx(other, tx.clone()).await {
+                        eventline::error!("action failed: {}", e);
+                    }
+                }
+            }
+        }
+
+        let delay = self.sleep_delay.clone();
+        tokio::spawn(async move {
+            let ready = futures::future::join_all(pending);
+            if tokio::time::timeout(SLEEP_DELAY_TIMEOUT, ready).await.is_err() {
+                eventline::warn!("sleep-delay: timed out waiting for pre-sleep actions");
+            }
+
+            if delay.release() {
+                eventline::info!("sleep-delay: released");
+            }
+        });
+    }
+}
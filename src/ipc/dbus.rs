@@ -0,0 +1,156 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use tokio::sync::{mpsc, watch};
+use zbus::{connection, fdo, object_server::InterfaceRef, Connection};
+
+use crate::core::{info::ControlStatus, manager_msg::ManagerMsg};
+
+pub const DBUS_NAME: &str = "org.stasis.Daemon1";
+pub const DBUS_PATH: &str = "/org/stasis/Daemon1";
+
+/// `org.stasis.Daemon1`: the text IPC commands as D-Bus methods, plus live state
+/// as properties. Methods go through the same `ipc::handlers` as the socket.
+pub struct Control {
+    tx: mpsc::Sender<ManagerMsg>,
+    status: watch::Receiver<ControlStatus>,
+}
+
+impl Control {
+    pub fn new(tx: mpsc::Sender<ManagerMsg>, status: watch::Receiver<ControlStatus>) -> Self {
+        Self { tx, status }
+    }
+}
+
+/// Handlers report failures as "ERROR: ..." text; surface those as D-Bus errors.
+fn into_reply(out: String) -> fdo::Result<String> {
+    match out.strip_prefix("ERROR:") {
+        Some(e) => Err(fdo::Error::Failed(e.trim().to_string())),
+        None => Ok(out),
+    }
+}
+
+#[zbus::interface(name = "org.stasis.Daemon1")]
+impl Control {
+    /// Pause idle timers. Empty `duration` pauses until Resume; otherwise takes the
+    /// same forms as the CLI ("5m", "1h30m", "until 13:30").
+    async fn pause(&self, duration: String) -> fdo::Result<String> {
+        let duration = duration.trim();
+        let args = if duration.is_empty()
+            || duration.starts_with("for ")
+            || duration.starts_with("until ")
+        {
+            duration.to_string()
+        } else {
+            format!("for {duration}")
+        };
+
+        into_reply(crate::ipc::handlers::pause::handle_pause(&args, &self.tx).await)
+    }
+
+    async fn resume(&self) -> fdo::Result<String> {
+        into_reply(crate::ipc::handlers::pause::handle_resume(&self.tx).await)
+    }
+
+    async fn trigger(&self, step: String) -> fdo::Result<String> {
+        into_reply(crate::ipc::handlers::trigger::handle_trigger(&step, &self.tx).await)
+    }
+
+    /// "none" clears the active profile.
+    async fn set_profile(&self, name: String) -> fdo::Result<String> {
+        into_reply(crate::ipc::handlers::profile::handle_profile(&name, &self.tx).await)
+    }
+
+    async fn reload(&self) -> fdo::Result<String> {
+        into_reply(crate::ipc::handlers::reload::handle_reload(&self.tx).await)
+    }
+
+    /// Same JSON as `stasis info --json`.
+    async fn get_info(&self) -> fdo::Result<String> {
+        into_reply(crate::ipc::router::route_command("info --json", &self.tx).await)
+    }
+
+    #[zbus(property)]
+    fn paused(&self) -> bool {
+        self.status.borrow().paused
+    }
+
+    #[zbus(property)]
+    fn locked(&self) -> bool {
+        self.status.borrow().locked
+    }
+
+    #[zbus(property)]
+    fn active_profile(&self) -> String {
+        self.status.borrow().active_profile.clone()
+    }
+
+    #[zbus(property)]
+    fn plan_source(&self) -> String {
+        self.status.borrow().plan_source.clone()
+    }
+
+    #[zbus(property)]
+    fn next_step(&self) -> String {
+        self.status.borrow().next_step.clone()
+    }
+}
+
+/// Own `org.stasis.Daemon1` on the session bus and keep its properties in sync
+/// with the daemon's status channel.
+pub async fn spawn_dbus_control(
+    tx: mpsc::Sender<ManagerMsg>,
+    status: watch::Receiver<ControlStatus>,
+) -> Result<(), String> {
+    let conn = connection::Builder::session()
+        .and_then(|b| b.name(DBUS_NAME))
+        .and_then(|b| b.serve_at(DBUS_PATH, Control::new(tx, status.clone())))
+        .map_err(|e| format!("dbus control: {e}"))?
+        .build()
+        .await
+        .map_err(|e| format!("dbus control: {e}"))?;
+
+    eventline::info!("ipc: serving {} on the session bus", DBUS_NAME);
+
+    tokio::spawn(emit_property_changes(conn, status));
+    Ok(())
+}
+
+pub(crate) async fn emit_property_changes(
+    conn: Connection,
+    mut status: watch::Receiver<ControlStatus>,
+) {
+    let iface: InterfaceRef<Control> = match conn.object_server().interface(DBUS_PATH).await {
+        Ok(i) => i,
+        Err(e) => {
+            eventline::warn!("dbus control: {e}");
+            return;
+        }
+    };
+
+    let mut last = status.borrow().clone();
+
+    while status.changed().await.is_ok() {
+        let cur = status.borrow_and_update().clone();
+        let emitter = iface.signal_emitter();
+        let ctl = iface.get().await;
+
+        if cur.paused != last.paused {
+            let _ = ctl.paused_changed(emitter).await;
+        }
+        if cur.locked != last.locked {
+            let _ = ctl.locked_changed(emitter).await;
+        }
+        if cur.active_profile != last.active_profile {
+            let _ = ctl.active_profile_changed(emitter).await;
+        }
+        if cur.plan_source != last.plan_source {
+            let _ = ctl.plan_source_changed(emitter).await;
+        }
+        if cur.next_step != last.next_step {
+            let _ = ctl.next_step_changed(emitter).await;
+        }
+
+        last = cur;
+    }
+}
@@ -0,0 +1,90 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use futures::StreamExt;
+use tokio::sync::{mpsc, watch};
+use zbus::{connection, fdo::PropertiesProxy, Connection, Guid, Proxy};
+
+use crate::core::{events::Event, info::ControlStatus, manager_msg::ManagerMsg};
+use crate::ipc::dbus::{emit_property_changes, Control, DBUS_NAME, DBUS_PATH};
+
+async fn private_control(
+    status: watch::Receiver<ControlStatus>,
+) -> (Connection, Connection, mpsc::Receiver<ManagerMsg>) {
+    let (tx, rx) = mpsc::channel(8);
+    let (a, b) = tokio::net::UnixStream::pair().unwrap();
+
+    let server = connection::Builder::unix_stream(a)
+        .server(Guid::generate())
+        .unwrap()
+        .p2p()
+        .serve_at(DBUS_PATH, Control::new(tx, status))
+        .unwrap()
+        .build();
+    let client = connection::Builder::unix_stream(b).p2p().build();
+
+    let (server, client) = futures::try_join!(server, client).unwrap();
+    (server, client, rx)
+}
+
+async fn proxy(client: &Connection) -> Proxy<'static> {
+    Proxy::new(client, DBUS_NAME, DBUS_PATH, "org.stasis.Daemon1")
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn trigger_goes_through_the_ipc_handler() {
+    let (_status_tx, status_rx) = watch::channel(ControlStatus::default());
+    let (_server, client, mut rx) = private_control(status_rx).await;
+
+    let reply: String = proxy(&client).await.call("Trigger", &("dpms",)).await.unwrap();
+
+    assert_eq!(reply, "Triggered 'dpms'");
+    match rx.recv().await {
+        Some(ManagerMsg::Event(Event::ManualTrigger { name, .. })) => assert_eq!(name, "dpms"),
+        other => panic!("unexpected message: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn handler_errors_become_dbus_errors() {
+    let (_status_tx, status_rx) = watch::channel(ControlStatus::default());
+    let (_server, client, _rx) = private_control(status_rx).await;
+
+    let err = proxy(&client)
+        .await
+        .call::<_, _, String>("Pause", &("5 parsecs",))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("Duration format"));
+}
+
+#[tokio::test]
+async fn status_changes_emit_properties_changed() {
+    let (status_tx, status_rx) = watch::channel(ControlStatus::default());
+    let (server, client, _rx) = private_control(status_rx.clone()).await;
+    tokio::spawn(emit_property_changes(server, status_rx));
+
+    let props = PropertiesProxy::builder(&client)
+        .destination(DBUS_NAME)
+        .unwrap()
+        .path(DBUS_PATH)
+        .unwrap()
+        .build()
+        .await
+        .unwrap();
+    let mut changes = props.receive_properties_changed().await.unwrap();
+
+    status_tx.send_modify(|s| s.paused = true);
+
+    let sig = changes.next().await.unwrap();
+    let args = sig.args().unwrap();
+    assert_eq!(args.interface_name().as_str(), "org.stasis.Daemon1");
+    assert!(args.changed_properties().contains_key("Paused"));
+    assert!(!args.changed_properties().contains_key("Locked"));
+
+    let paused: bool = proxy(&client).await.get_property("Paused").await.unwrap();
+    assert!(paused);
+}
@@ -17,6 +17,9 @@ Examples:
   stasis trigger brightness
   stasis trigger lock_screen
   stasis trigger suspend
+  stasis trigger hibernate
+  stasis trigger hybrid-sleep
+  stasis trigger suspend-then-hibernate
   stasis trigger custom:my-step
 
 Notes:
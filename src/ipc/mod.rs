@@ -2,10 +2,14 @@
 // License: MIT
 
 pub mod client;
+pub mod dbus;
 pub mod handlers;
 pub mod router;
 pub mod server;
 
+#[cfg(test)]
+mod dbus_tests;
+
 use std::path::PathBuf;
 
 pub fn runtime_dir() -> Result<PathBuf, String> {
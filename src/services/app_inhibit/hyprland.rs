@@ -0,0 +1,204 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+
+use super::{Window, count_matching_windows, json_str, needs_visibility};
+use crate::core::config::InhibitRule;
+
+/// socket2 events that can change which clients exist (or how they show up).
+const WINDOW_EVENTS: &[&str] = &["openwindow", "closewindow", "fullscreen"];
+
+/// Extra events that matter once rules look at titles, focus or workspaces.
+const PROPERTY_EVENTS: &[&str] = &[
+    "activewindow",
+    "windowtitle",
+    "workspace",
+    "focusedmon",
+    "movewindow",
+    "activespecial",
+];
+
+/// Talks to Hyprland over `.socket.sock` (requests) and `.socket2.sock` (events).
+#[derive(Debug)]
+pub(super) struct HyprlandBackend {
+    socket_dir: PathBuf,
+}
+
+impl HyprlandBackend {
+    pub(super) fn new(socket_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            socket_dir: socket_dir.into(),
+        }
+    }
+
+    /// `$XDG_RUNTIME_DIR/hypr/<sig>` (Hyprland >= 0.40) or `/tmp/hypr/<sig>`.
+    /// Without a signature, a lone running instance is used.
+    pub(super) fn from_env() -> Option<Self> {
+        let mut roots = Vec::new();
+        if let Ok(rt) = env::var("XDG_RUNTIME_DIR") {
+            roots.push(Path::new(&rt).join("hypr"));
+        }
+        roots.push(PathBuf::from("/tmp/hypr"));
+
+        if let Ok(sig) = env::var("HYPRLAND_INSTANCE_SIGNATURE") {
+            return roots
+                .iter()
+                .map(|r| r.join(&sig))
+                .find(|d| d.join(".socket.sock").exists())
+                .map(Self::new);
+        }
+
+        roots.iter().find_map(|r| {
+            let mut dirs = std::fs::read_dir(r)
+                .ok()?
+                .flatten()
+                .map(|e| e.path())
+                .filter(|d| d.join(".socket.sock").exists());
+            let only = dirs.next()?;
+            dirs.next().is_none().then(|| Self::new(only))
+        })
+    }
+
+    async fn request(&self, cmd: &str) -> Result<Vec<u8>, String> {
+        let path = self.socket_dir.join(".socket.sock");
+        let mut stream = UnixStream::connect(&path)
+            .await
+            .map_err(|e| format!("connect {}: {e}", path.display()))?;
+
+        stream
+            .write_all(cmd.as_bytes())
+            .await
+            .map_err(|e| format!("hyprland write failed: {e}"))?;
+
+        // Hyprland answers once and closes the connection.
+        let mut out = Vec::new();
+        stream
+            .read_to_end(&mut out)
+            .await
+            .map_err(|e| format!("hyprland read failed: {e}"))?;
+        Ok(out)
+    }
+
+    async fn request_json(&self, cmd: &str) -> Result<serde_json::Value, String> {
+        let out = self.request(cmd).await?;
+        serde_json::from_slice(&out).map_err(|e| format!("hyprland {cmd} json parse failed: {e}"))
+    }
+
+    /// Workspace ids currently shown on some monitor (special workspaces included).
+    async fn visible_workspaces(&self) -> Result<HashSet<i64>, String> {
+        let v = self.request_json("j/monitors").await?;
+        let arr = v
+            .as_array()
+            .ok_or_else(|| "hyprland monitors json: expected array".to_string())?;
+
+        let mut ids = HashSet::new();
+        for mon in arr {
+            for key in ["activeWorkspace", "specialWorkspace"] {
+                if let Some(id) = mon
+                    .get(key)
+                    .and_then(|w| w.get("id"))
+                    .and_then(|x| x.as_i64())
+                    && id != 0
+                {
+                    ids.insert(id);
+                }
+            }
+        }
+        Ok(ids)
+    }
+
+    /// `j/clients`, plus `j/monitors` when `visibility` is needed.
+    pub(super) async fn windows(&self, visibility: bool) -> Result<Vec<Window>, String> {
+        let v = self.request_json("j/clients").await?;
+        let arr = v
+            .as_array()
+            .ok_or_else(|| "hyprland clients json: expected array".to_string())?;
+
+        let visible = if visibility {
+            Some(self.visible_workspaces().await?)
+        } else {
+            None
+        };
+
+        Ok(arr
+            .iter()
+            .map(|item| {
+                let workspace = item
+                    .get("workspace")
+                    .and_then(|w| w.get("id"))
+                    .and_then(|x| x.as_i64());
+                let hidden = item
+                    .get("hidden")
+                    .and_then(|x| x.as_bool())
+                    .unwrap_or(false);
+
+                Window {
+                    // Old behavior: app_id == class.
+                    app_id: json_str(item, "class"),
+                    title: json_str(item, "title"),
+                    // bool before 0.42; a mode bitmask (2 = fullscreen) since.
+                    fullscreen: item
+                        .get("fullscreen")
+                        .and_then(|f| f.as_bool().or_else(|| f.as_u64().map(|m| m & 2 != 0))),
+                    focused: item
+                        .get("focusHistoryID")
+                        .and_then(|x| x.as_i64())
+                        .map(|id| id == 0),
+                    workspace_visible: visible
+                        .as_ref()
+                        .zip(workspace)
+                        .map(|(ids, ws)| !hidden && ids.contains(&ws)),
+                }
+            })
+            .collect())
+    }
+
+    pub(super) async fn count_matches_dedup(&self, rules: &[InhibitRule]) -> Result<u64, String> {
+        let windows = self.windows(needs_visibility(rules)).await?;
+        Ok(count_matching_windows(&windows, rules))
+    }
+
+    /// Follow socket2 and ping on window open/close/fullscreen (and, with
+    /// `props`, focus/title/workspace changes). Pings coalesce; the channel
+    /// closes if the event socket goes away.
+    pub(super) fn subscribe(&self, props: bool) -> mpsc::Receiver<()> {
+        let (tx, rx) = mpsc::channel(1);
+        let path = self.socket_dir.join(".socket2.sock");
+
+        tokio::spawn(async move {
+            let stream = match UnixStream::connect(&path).await {
+                Ok(s) => s,
+                Err(e) => {
+                    eventline::warn!("app_inhibit: connect {}: {e}", path.display());
+                    return;
+                }
+            };
+
+            let mut lines = BufReader::new(stream).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Some((event, _data)) = line.split_once(">>") else {
+                    continue;
+                };
+                let relevant = WINDOW_EVENTS.contains(&event)
+                    || (props && PROPERTY_EVENTS.contains(&event.trim_end_matches("v2")));
+                if !relevant {
+                    continue;
+                }
+
+                if tx.is_closed() {
+                    return;
+                }
+                let _ = tx.try_send(());
+            }
+        });
+
+        rx
+    }
+}
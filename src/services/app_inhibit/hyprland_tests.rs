@@ -0,0 +1,101 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+
+use super::hyprland::HyprlandBackend;
+use crate::core::config::{InhibitRule, Pattern};
+
+const CLIENTS: &str = r#"[
+  {"address": "0x1", "class": "mpv", "title": "movie.mkv"},
+  {"address": "0x2", "class": "mpv", "title": "other.mkv"},
+  {"address": "0x3", "class": "org.mozilla.firefox", "title": "Mozilla Firefox"},
+  {"address": "0x4", "class": "", "title": "splash"}
+]"#;
+
+fn socket_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("stasis-hypr-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Fake `.socket.sock`: answers `j/clients` with CLIENTS, then hangs up.
+fn serve_requests(dir: &Path) {
+    let listener = UnixListener::bind(dir.join(".socket.sock")).unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut conn, _)) = listener.accept().await {
+            let mut buf = [0u8; 64];
+            let n = conn.read(&mut buf).await.unwrap();
+            let reply = if &buf[..n] == b"j/clients" {
+                CLIENTS
+            } else {
+                "unknown request"
+            };
+            conn.write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+}
+
+#[tokio::test]
+async fn clients_are_counted_by_class_over_the_request_socket() {
+    let dir = socket_dir("query");
+    serve_requests(&dir);
+
+    let backend = HyprlandBackend::new(&dir);
+    let apps = InhibitRule::combined(
+        &[
+            Pattern::Literal("mpv".into()),
+            Pattern::Literal("firefox".into()),
+        ],
+        &[],
+    );
+
+    // Two mpv windows dedup to one; a bare "firefox" does not match the reverse-DNS class.
+    assert_eq!(backend.count_matches_dedup(&apps).await, Ok(1));
+
+    let apps = InhibitRule::combined(
+        &[
+            Pattern::Literal("org.mozilla.firefox".into()),
+            Pattern::Literal("mpv".into()),
+        ],
+        &[],
+    );
+    assert_eq!(backend.count_matches_dedup(&apps).await, Ok(2));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn window_events_ping_and_others_do_not() {
+    let dir = socket_dir("events");
+    let listener = UnixListener::bind(dir.join(".socket2.sock")).unwrap();
+
+    let backend = HyprlandBackend::new(&dir);
+    let mut pings = backend.subscribe(false);
+
+    let (mut conn, _) = listener.accept().await.unwrap();
+
+    conn.write_all(b"workspace>>2\nactivewindow>>kitty,~\n")
+        .await
+        .unwrap();
+    let quiet = tokio::time::timeout(Duration::from_millis(100), pings.recv()).await;
+    assert!(quiet.is_err(), "non-window events must not ping");
+
+    conn.write_all(b"openwindow>>80a1,1,mpv,movie.mkv\n")
+        .await
+        .unwrap();
+    let ping = tokio::time::timeout(Duration::from_secs(2), pings.recv()).await;
+    assert_eq!(ping.unwrap(), Some(()));
+
+    // Socket gone => channel closes so the service can fall back to polling.
+    drop(conn);
+    let closed = tokio::time::timeout(Duration::from_secs(2), pings.recv()).await;
+    assert_eq!(closed.unwrap(), None);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
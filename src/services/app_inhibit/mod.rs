@@ -0,0 +1,619 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashSet;
+use std::env;
+use std::path::Path;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+
+use crate::core::config::{InhibitRule, Pattern};
+use crate::core::events::Event;
+use crate::core::manager_msg::ManagerMsg;
+use crate::services::wayland::Toplevels;
+
+mod hyprland;
+mod niri;
+mod sway;
+mod toplevel;
+
+use hyprland::HyprlandBackend;
+use niri::NiriBackend;
+use sway::SwayBackend;
+use toplevel::ToplevelBackend;
+
+#[cfg(test)]
+mod hyprland_tests;
+#[cfg(test)]
+mod niri_tests;
+#[cfg(test)]
+mod sway_tests;
+#[cfg(test)]
+mod toplevel_tests;
+
+#[derive(Debug, Clone)]
+pub struct AppRules {
+    pub epoch: u64,
+    pub apps: Vec<Pattern>,
+    pub rules: Vec<InhibitRule>,
+}
+
+/// Spawnable task: tracks app inhibitors and emits events on change. Backends
+/// with a window event stream are re-queried when it fires; the rest are polled.
+///
+/// Logging policy (INFO):
+/// - If count changes: log "X -> Y"
+/// - If we are forcing a refresh (profile/reload/rules changed) OR first poll:
+///     log "0 -> N" ONLY when N != 0
+pub async fn run_app_inhibit(
+    tx: mpsc::Sender<ManagerMsg>,
+    mut rules_rx: watch::Receiver<AppRules>,
+    mut toplevels: watch::Receiver<Toplevels>,
+) {
+    let initial = rules_rx.borrow().clone();
+    let mut last_epoch = initial.epoch;
+
+    let mut svc = AppInhibitService::new(&initial.apps, &initial.rules).with_poll_interval_ms(1000);
+
+    eventline::info!("app_inhibit: started (backend={})", svc.backend_name());
+
+    let mut changes = svc.subscribe();
+    if changes.is_some() {
+        eventline::info!("app_inhibit: following {} window events", svc.backend_name());
+    }
+
+    // Ensure we emit once immediately (and log 0->N if N!=0).
+    svc.force_emit_next();
+    let now_ms = crate::core::utils::now_ms();
+    if let Some(ev) = svc.poll(now_ms).await
+        && tx.send(ManagerMsg::Event(ev)).await.is_err()
+    {
+        return;
+    }
+
+    let sleep_ms = 250u64;
+
+    // Without a compositor IPC backend, switch to the Wayland toplevel list as
+    // soon as services::wayland has bound one.
+    let mut await_toplevels = svc.backend_name() == "proc";
+
+    loop {
+        tokio::select! {
+            changed = rules_rx.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+
+                let rules = rules_rx.borrow().clone();
+
+                let epoch_bumped = rules.epoch != last_epoch;
+                if epoch_bumped {
+                    last_epoch = rules.epoch;
+                    svc.force_emit_next();
+                }
+
+                // Title/focus/workspace rules need a wider event subscription.
+                let had_props = svc.needs_window_props();
+                svc.reconfigure(&rules.apps, &rules.rules);
+                if changes.is_some() && svc.needs_window_props() != had_props {
+                    changes = svc.subscribe();
+                }
+
+                let now_ms = crate::core::utils::now_ms();
+                if let Some(ev) = svc.poll(now_ms).await
+                    && tx.send(ManagerMsg::Event(ev)).await.is_err()
+                {
+                    return;
+                }
+            }
+
+            ping = next_change(&mut changes) => {
+                if ping.is_none() {
+                    eventline::warn!(
+                        "app_inhibit: {} event stream ended; falling back to polling",
+                        svc.backend_name()
+                    );
+                    changes = None;
+                    continue;
+                }
+
+                svc.poll_now();
+                let now_ms = crate::core::utils::now_ms();
+                if let Some(ev) = svc.poll(now_ms).await
+                    && tx.send(ManagerMsg::Event(ev)).await.is_err()
+                {
+                    return;
+                }
+            }
+
+            bound = toplevels.changed(), if await_toplevels => {
+                if bound.is_err() {
+                    await_toplevels = false; // Wayland service gone
+                    continue;
+                }
+                if !svc.adopt_toplevels(&toplevels) {
+                    continue;
+                }
+                await_toplevels = false;
+
+                changes = svc.subscribe();
+                let now_ms = crate::core::utils::now_ms();
+                if let Some(ev) = svc.poll(now_ms).await
+                    && tx.send(ManagerMsg::Event(ev)).await.is_err()
+                {
+                    return;
+                }
+            }
+
+            _ = tokio::time::sleep(Duration::from_millis(sleep_ms)), if changes.is_none() => {
+                let now_ms = crate::core::utils::now_ms();
+                if let Some(ev) = svc.poll(now_ms).await
+                    && tx.send(ManagerMsg::Event(ev)).await.is_err()
+                {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn next_change(changes: &mut Option<mpsc::Receiver<()>>) -> Option<()> {
+    match changes.as_mut() {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// One window as a compositor backend reports it. Properties a backend can't
+/// see stay `None` and never satisfy a rule that asks for them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Window {
+    app_id: String,
+    title: String,
+    fullscreen: Option<bool>,
+    focused: Option<bool>,
+    workspace_visible: Option<bool>,
+}
+
+#[derive(Debug)]
+pub struct AppInhibitService {
+    rules: Vec<InhibitRule>,
+    backend: Backend,
+
+    poll_interval_ms: u64,
+    last_poll_ms: u64,
+
+    last_count: Option<u64>, // None => never polled yet
+    force_emit: bool,        // next poll must emit and do baseline logging
+}
+
+#[derive(Debug)]
+enum Backend {
+    Hyprland(HyprlandBackend),
+    Niri(NiriBackend),
+    Sway(SwayBackend),
+    Toplevel(ToplevelBackend),
+    Proc(ProcBackend),
+}
+
+#[derive(Debug, Default)]
+struct ProcBackend {}
+
+impl AppInhibitService {
+    pub fn new(inhibit_apps: &[Pattern], inhibit_rules: &[InhibitRule]) -> Self {
+        let rules = InhibitRule::combined(inhibit_apps, inhibit_rules);
+        let backend = detect_backend().unwrap_or_else(|| Backend::Proc(ProcBackend::default()));
+
+        Self {
+            rules,
+            backend,
+            poll_interval_ms: 1000,
+            last_poll_ms: 0,
+            last_count: None,
+            force_emit: false,
+        }
+    }
+
+    pub fn with_poll_interval_ms(mut self, ms: u64) -> Self {
+        self.poll_interval_ms = ms.max(100);
+        self
+    }
+
+    pub fn reconfigure(&mut self, inhibit_apps: &[Pattern], inhibit_rules: &[InhibitRule]) {
+        let new_rules = InhibitRule::combined(inhibit_apps, inhibit_rules);
+
+        // Avoid requiring Pattern:PartialEq by comparing formatted strings.
+        if rules_same(&self.rules, &new_rules) {
+            return;
+        }
+
+        self.rules = new_rules;
+        self.force_emit_next();
+
+        eventline::info!(
+            "app_inhibit: reconfigured (rules_len={}, backend={})",
+            self.rules.len(),
+            self.backend_name(),
+        );
+
+        if matches!(self.backend, Backend::Proc(_))
+            && self.rules.iter().any(|r| r.app_only().is_none())
+        {
+            eventline::warn!(
+                "app_inhibit: proc backend can only match app ids; rules on title/fullscreen/focus/workspace are skipped"
+            );
+        }
+    }
+
+    /// Whether any rule looks past the app_id.
+    pub fn needs_window_props(&self) -> bool {
+        self.rules.iter().any(|r| r.app_only().is_none())
+    }
+
+    pub fn force_emit_next(&mut self) {
+        self.force_emit = true;
+        self.last_poll_ms = 0;
+    }
+
+    /// Allow the next poll() to run immediately (without forcing an emission).
+    pub fn poll_now(&mut self) {
+        self.last_poll_ms = 0;
+    }
+
+    /// Replace the /proc fallback with the Wayland toplevel list once a
+    /// foreign-toplevel protocol is bound. Returns whether the backend changed.
+    pub fn adopt_toplevels(&mut self, toplevels: &watch::Receiver<Toplevels>) -> bool {
+        if !matches!(self.backend, Backend::Proc(_)) {
+            return false;
+        }
+
+        let backend = ToplevelBackend::new(toplevels.clone());
+        let Some(protocol) = backend.protocol() else {
+            return false;
+        };
+
+        self.backend = Backend::Toplevel(backend);
+        self.force_emit_next();
+        eventline::info!("app_inhibit: switched to {} (via {})", self.backend_name(), protocol);
+        true
+    }
+
+    /// Window change pings, for backends that can push them.
+    pub fn subscribe(&self) -> Option<mpsc::Receiver<()>> {
+        let props = self.needs_window_props();
+        match &self.backend {
+            Backend::Hyprland(h) => Some(h.subscribe(props)),
+            Backend::Niri(n) => Some(n.subscribe(props)),
+            Backend::Sway(s) => Some(s.subscribe(props)),
+            Backend::Toplevel(t) => Some(t.subscribe()),
+            Backend::Proc(_) => None,
+        }
+    }
+
+    pub async fn poll(&mut self, now_ms: u64) -> Option<Event> {
+        if now_ms < self.last_poll_ms.saturating_add(self.poll_interval_ms) {
+            return None;
+        }
+        self.last_poll_ms = now_ms;
+
+        let prev_count = self.last_count.unwrap_or(0);
+
+        let count = if self.rules.is_empty() {
+            0
+        } else {
+            match &self.backend {
+                // IMPORTANT: if Hyprland is selected, DO NOT fall back to /proc.
+                // If IPC fails, keep the previous count so we don't spike/flap.
+                Backend::Hyprland(h) => match h.count_matches_dedup(&self.rules).await {
+                    Ok(n) => n,
+                    Err(e) => {
+                        eventline::warn!(
+                            "app_inhibit: hyprland query failed (keeping previous count={}): {}",
+                            prev_count,
+                            e
+                        );
+                        prev_count
+                    }
+                },
+
+                // Same for niri: no /proc fallback if niri backend is selected.
+                Backend::Niri(n) => match n.count_matches_dedup(&self.rules).await {
+                    Ok(n) => n,
+                    Err(e) => {
+                        eventline::warn!(
+                            "app_inhibit: niri query failed (keeping previous count={}): {}",
+                            prev_count,
+                            e
+                        );
+                        prev_count
+                    }
+                },
+
+                Backend::Sway(s) => match s.count_matches_dedup(&self.rules).await {
+                    Ok(n) => n,
+                    Err(e) => {
+                        eventline::warn!(
+                            "app_inhibit: sway query failed (keeping previous count={}): {}",
+                            prev_count,
+                            e
+                        );
+                        prev_count
+                    }
+                },
+
+                Backend::Toplevel(t) => t.count_matches_dedup(&self.rules),
+
+                // Only used when we couldn't detect a compositor backend.
+                Backend::Proc(p) => p.count_matches_dedup(&self.rules),
+            }
+        };
+
+        let first_poll = self.last_count.is_none();
+        let prev = self.last_count.unwrap_or(0);
+        let changed = !first_poll && prev != count;
+
+        if changed {
+            eventline::info!(
+                "app_inhibit: count {} -> {} (backend={}, rules_len={})",
+                prev,
+                count,
+                self.backend_name(),
+                self.rules.len()
+            );
+        } else if (first_poll || self.force_emit) && count != 0 {
+            eventline::info!(
+                "app_inhibit: count {} -> {} (backend={}, rules_len={})",
+                0u64,
+                count,
+                self.backend_name(),
+                self.rules.len()
+            );
+        }
+
+        if first_poll || changed || self.force_emit {
+            self.last_count = Some(count);
+            self.force_emit = false;
+            return Some(Event::AppInhibitorCount { count, now_ms });
+        }
+
+        None
+    }
+
+    pub fn backend_name(&self) -> &'static str {
+        match self.backend {
+            Backend::Hyprland(_) => "hyprland",
+            Backend::Niri(_) => "niri",
+            Backend::Sway(_) => "sway",
+            Backend::Toplevel(_) => "foreign-toplevel",
+            Backend::Proc(_) => "proc",
+        }
+    }
+}
+
+// ----------------------------- backend detection -----------------------------
+
+fn detect_backend() -> Option<Backend> {
+    detect_hyprland_backend()
+        .or_else(detect_niri_backend)
+        .or_else(detect_sway_backend)
+}
+
+fn detect_hyprland_backend() -> Option<Backend> {
+    // Most reliable in-session signal:
+    let in_hyprland = env::var("HYPRLAND_INSTANCE_SIGNATURE").is_ok()
+        // Fallback: desktop hint
+        || env::var("XDG_CURRENT_DESKTOP").is_ok_and(|d| d.to_lowercase().contains("hyprland"));
+
+    if !in_hyprland {
+        return None;
+    }
+
+    match HyprlandBackend::from_env() {
+        Some(h) => Some(Backend::Hyprland(h)),
+        None => {
+            eventline::warn!("app_inhibit: running under Hyprland but its IPC socket was not found");
+            None
+        }
+    }
+}
+
+fn detect_niri_backend() -> Option<Backend> {
+    // niri exports NIRI_SOCKET into the session; that's all the IPC needs.
+    if let Some(n) = NiriBackend::from_env() {
+        return Some(Backend::Niri(n));
+    }
+
+    if env::var("XDG_CURRENT_DESKTOP").is_ok_and(|d| d.to_lowercase().contains("niri")) {
+        eventline::warn!("app_inhibit: running under niri but NIRI_SOCKET is not set");
+    }
+
+    None
+}
+
+fn detect_sway_backend() -> Option<Backend> {
+    SwayBackend::from_env().map(Backend::Sway)
+}
+
+// ----------------------------- matching helpers -----------------------------
+
+/// Windows matching any rule, deduped by app_id (title when there is none) so
+/// several windows of one app count once.
+fn count_matching_windows(windows: &[Window], rules: &[InhibitRule]) -> u64 {
+    let seen: HashSet<&str> = windows
+        .iter()
+        .filter(|w| rules.iter().any(|r| rule_matches(r, w)))
+        .map(|w| if w.app_id.is_empty() { &w.title } else { &w.app_id })
+        .map(String::as_str)
+        .collect();
+
+    seen.len() as u64
+}
+
+/// AND over every field the rule sets.
+fn rule_matches(rule: &InhibitRule, w: &Window) -> bool {
+    let prop = |want: Option<bool>, have: Option<bool>| want.is_none_or(|v| have == Some(v));
+
+    rule.app_id
+        .as_ref()
+        .is_none_or(|p| should_inhibit_app_id(&w.app_id, std::slice::from_ref(p)))
+        && rule.title.as_ref().is_none_or(|p| title_matches(p, &w.title))
+        && prop(rule.fullscreen, w.fullscreen)
+        && prop(rule.focused, w.focused)
+        && prop(rule.workspace_visible, w.workspace_visible)
+}
+
+/// Literal: case-insensitive substring. Regex: as written, against the raw title.
+fn title_matches(pattern: &Pattern, title: &str) -> bool {
+    match pattern {
+        Pattern::Literal(_) => pattern.matches_lc(&title.to_lowercase()),
+        Pattern::Regex(r) => r.is_match(title),
+    }
+}
+
+fn needs_visibility(rules: &[InhibitRule]) -> bool {
+    rules.iter().any(|r| r.workspace_visible.is_some())
+}
+
+fn json_str(v: &serde_json::Value, key: &str) -> String {
+    v.get(key)
+        .and_then(|x| x.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+// Old app_id semantics, still used for `app_id` fields.
+
+fn should_inhibit_app_id(app_id: &str, patterns: &[Pattern]) -> bool {
+    if app_id.is_empty() {
+        return false;
+    }
+
+    for pat in patterns {
+        let matched = match pat {
+            Pattern::Literal(s) => app_id_matches_static(s, app_id),
+            Pattern::Regex(r) => r.is_match(app_id),
+        };
+
+        if matched {
+            return true;
+        }
+    }
+
+    false
+}
+
+// EXACTLY the old helper behavior.
+fn app_id_matches_static(pattern: &str, app_id: &str) -> bool {
+    if pattern.eq_ignore_ascii_case(app_id) {
+        return true;
+    }
+    if app_id.ends_with(".exe") {
+        let name = app_id.strip_suffix(".exe").unwrap_or(app_id);
+        if pattern.eq_ignore_ascii_case(name) {
+            return true;
+        }
+    }
+    if let Some(last) = pattern.split('.').last() {
+        if last.eq_ignore_ascii_case(app_id) {
+            return true;
+        }
+    }
+    false
+}
+
+// ----------------------------- /proc fallback -----------------------------
+
+impl ProcBackend {
+    /// No window properties here: only app_id-only rules can match.
+    fn count_matches_dedup(&self, rules: &[InhibitRule]) -> u64 {
+        let apps: Vec<Pattern> = rules.iter().filter_map(InhibitRule::app_only).cloned().collect();
+        if apps.is_empty() {
+            return 0;
+        }
+
+        let Ok(rd) = std::fs::read_dir("/proc") else {
+            return 0;
+        };
+
+        // Dedupe by “matched key” so fallback doesn’t explode counts.
+        let mut seen: HashSet<String> = HashSet::new();
+
+        for ent in rd.flatten() {
+            let pid_str = ent.file_name().to_string_lossy().to_string();
+            if !pid_str.chars().all(|c| c.is_ascii_digit()) {
+                continue;
+            }
+
+            let pid_path = ent.path();
+
+            if let Some(comm) = read_proc_comm(&pid_path) {
+                if let Some(key) = proc_match_key(&comm, &apps) {
+                    seen.insert(key);
+                    continue;
+                }
+            }
+
+            if let Some(cmd) = read_proc_cmdline(&pid_path) {
+                let argv0 = cmd.split_whitespace().next().unwrap_or("").to_string();
+                if !argv0.is_empty() {
+                    if let Some(key) = proc_match_key(&argv0, &apps) {
+                        seen.insert(key);
+                        continue;
+                    }
+                }
+            }
+        }
+
+        seen.len() as u64
+    }
+}
+
+fn proc_match_key(hay: &str, apps: &[Pattern]) -> Option<String> {
+    for p in apps {
+        let matched = match p {
+            // Old proc behavior was “exact” for literals
+            Pattern::Literal(s) => hay.eq_ignore_ascii_case(s),
+            Pattern::Regex(r) => r.is_match(hay),
+        };
+        if matched {
+            return Some(hay.to_lowercase());
+        }
+    }
+    None
+}
+
+fn read_proc_comm(pid_path: &Path) -> Option<String> {
+    let p = pid_path.join("comm");
+    let s = std::fs::read_to_string(p).ok()?;
+    Some(s.trim().to_string())
+}
+
+fn read_proc_cmdline(pid_path: &Path) -> Option<String> {
+    let p = pid_path.join("cmdline");
+    let bytes = std::fs::read(p).ok()?;
+    if bytes.is_empty() {
+        return None;
+    }
+
+    let s = bytes
+        .split(|b| *b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(s)
+}
+
+// ----------------------------- utils -----------------------------
+
+fn rules_same(a: &[InhibitRule], b: &[InhibitRule]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter()
+        .map(|r| r.to_string())
+        .zip(b.iter().map(|r| r.to_string()))
+        .all(|(x, y)| x == y)
+}
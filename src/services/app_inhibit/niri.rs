@@ -0,0 +1,180 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashSet;
+use std::env;
+use std::path::PathBuf;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+
+use super::{Window, count_matching_windows, json_str, needs_visibility};
+use crate::core::config::InhibitRule;
+
+/// EventStream events that can change the window set (or an app_id/title).
+const WINDOW_EVENTS: &[&str] = &["WindowsChanged", "WindowOpenedOrChanged", "WindowClosed"];
+
+/// Extra events that matter once rules look at focus or workspaces.
+const PROPERTY_EVENTS: &[&str] = &[
+    "WindowFocusChanged",
+    "WorkspaceActivated",
+    "WorkspacesChanged",
+];
+
+/// Talks to niri's JSON IPC on `$NIRI_SOCKET`: one request per connection,
+/// one JSON reply line back.
+#[derive(Debug)]
+pub(super) struct NiriBackend {
+    socket: PathBuf,
+}
+
+impl NiriBackend {
+    pub(super) fn new(socket: impl Into<PathBuf>) -> Self {
+        Self {
+            socket: socket.into(),
+        }
+    }
+
+    pub(super) fn from_env() -> Option<Self> {
+        env::var_os("NIRI_SOCKET").map(Self::new)
+    }
+
+    /// Send `request` and return the reply line's `Ok` payload.
+    async fn request(
+        &self,
+        request: &str,
+    ) -> Result<(serde_json::Value, BufReader<UnixStream>), String> {
+        let mut stream = UnixStream::connect(&self.socket)
+            .await
+            .map_err(|e| format!("connect {}: {e}", self.socket.display()))?;
+
+        stream
+            .write_all(format!("\"{request}\"\n").as_bytes())
+            .await
+            .map_err(|e| format!("niri write failed: {e}"))?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .await
+            .map_err(|e| format!("niri read failed: {e}"))?;
+
+        let mut reply: serde_json::Value = serde_json::from_str(&line)
+            .map_err(|e| format!("niri {request} reply parse failed: {e}"))?;
+
+        if let Some(err) = reply.get("Err") {
+            return Err(format!("niri {request} failed: {err}"));
+        }
+        let ok = reply
+            .get_mut("Ok")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| format!("niri {request} reply: missing Ok"))?;
+
+        Ok((ok, reader))
+    }
+
+    /// Ids of workspaces active on their output.
+    async fn visible_workspaces(&self) -> Result<HashSet<u64>, String> {
+        let (ok, _) = self.request("Workspaces").await?;
+
+        let arr = ok
+            .get("Workspaces")
+            .and_then(|w| w.as_array())
+            .ok_or_else(|| "niri Workspaces reply: expected array".to_string())?;
+
+        Ok(arr
+            .iter()
+            .filter(|ws| ws.get("is_active").and_then(|x| x.as_bool()) == Some(true))
+            .filter_map(|ws| ws.get("id").and_then(|x| x.as_u64()))
+            .collect())
+    }
+
+    /// `Windows`, plus `Workspaces` when `visibility` is needed. niri's IPC
+    /// doesn't report fullscreen, so that stays unknown.
+    pub(super) async fn windows(&self, visibility: bool) -> Result<Vec<Window>, String> {
+        let (ok, _) = self.request("Windows").await?;
+
+        let arr = ok
+            .get("Windows")
+            .and_then(|w| w.as_array())
+            .ok_or_else(|| "niri Windows reply: expected array".to_string())?;
+
+        let visible = if visibility {
+            Some(self.visible_workspaces().await?)
+        } else {
+            None
+        };
+
+        Ok(arr
+            .iter()
+            .map(|w| {
+                let workspace = w.get("workspace_id").and_then(|x| x.as_u64());
+                Window {
+                    app_id: json_str(w, "app_id"),
+                    title: json_str(w, "title"),
+                    fullscreen: None,
+                    focused: w.get("is_focused").and_then(|x| x.as_bool()),
+                    workspace_visible: visible
+                        .as_ref()
+                        .zip(workspace)
+                        .map(|(ids, ws)| ids.contains(&ws)),
+                }
+            })
+            .collect())
+    }
+
+    pub(super) async fn count_matches_dedup(&self, rules: &[InhibitRule]) -> Result<u64, String> {
+        let windows = self.windows(needs_visibility(rules)).await?;
+        Ok(count_matching_windows(&windows, rules))
+    }
+
+    /// Open an EventStream and ping on window open/change/close (and, with
+    /// `props`, focus/workspace changes). Pings coalesce; the channel closes if
+    /// niri drops the stream.
+    pub(super) fn subscribe(&self, props: bool) -> mpsc::Receiver<()> {
+        let (tx, rx) = mpsc::channel(1);
+        let backend = Self::new(self.socket.clone());
+
+        tokio::spawn(async move {
+            let mut reader = match backend.request("EventStream").await {
+                Ok((_, reader)) => reader,
+                Err(e) => {
+                    eventline::warn!("app_inhibit: {e}");
+                    return;
+                }
+            };
+
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match reader.read_line(&mut line).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(_) => {}
+                }
+
+                // Each event is a single-key object: {"WindowClosed":{"id":7}}
+                let Ok(ev) = serde_json::from_str::<serde_json::Value>(&line) else {
+                    continue;
+                };
+                let relevant = ev.as_object().is_some_and(|o| {
+                    o.keys().any(|k| {
+                        WINDOW_EVENTS.contains(&k.as_str())
+                            || (props && PROPERTY_EVENTS.contains(&k.as_str()))
+                    })
+                });
+                if !relevant {
+                    continue;
+                }
+
+                if tx.is_closed() {
+                    return;
+                }
+                let _ = tx.try_send(());
+            }
+        });
+
+        rx
+    }
+}
@@ -0,0 +1,100 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixListener;
+
+use super::niri::NiriBackend;
+use crate::core::config::{InhibitRule, Pattern};
+
+const WINDOWS_REPLY: &str = r#"{"Ok":{"Windows":[{"id":1,"title":"movie.mkv","app_id":"mpv","pid":10,"workspace_id":1,"is_focused":true,"is_floating":false,"is_urgent":false},{"id":2,"title":"x","app_id":"mpv","pid":11,"workspace_id":1,"is_focused":false,"is_floating":false,"is_urgent":false},{"id":3,"title":"Slides","app_id":"org.libreoffice.Impress","pid":12,"workspace_id":2,"is_focused":false,"is_floating":false,"is_urgent":false},{"id":4,"title":"?","app_id":null,"pid":null,"workspace_id":2,"is_focused":false,"is_floating":false,"is_urgent":false}]}}"#;
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("stasis-niri-{}-{name}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn windows_request_counts_app_ids() {
+    let path = socket_path("windows");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((conn, _)) = listener.accept().await {
+            let mut conn = BufReader::new(conn);
+            let mut req = String::new();
+            conn.read_line(&mut req).await.unwrap();
+            assert_eq!(req, "\"Windows\"\n");
+            conn.get_mut()
+                .write_all(format!("{WINDOWS_REPLY}\n").as_bytes())
+                .await
+                .unwrap();
+        }
+    });
+
+    let backend = NiriBackend::new(&path);
+    let apps = InhibitRule::combined(
+        &[
+            Pattern::Literal("mpv".into()),
+            Pattern::Literal("org.libreoffice.impress".into()),
+        ],
+        &[],
+    );
+    assert_eq!(backend.count_matches_dedup(&apps).await, Ok(2));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn error_replies_surface_as_errors() {
+    let path = socket_path("err");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio::spawn(async move {
+        let (mut conn, _) = listener.accept().await.unwrap();
+        conn.write_all(b"{\"Err\":\"unknown request\"}\n")
+            .await
+            .unwrap();
+    });
+
+    let backend = NiriBackend::new(&path);
+    let err = backend.count_matches_dedup(&[]).await.unwrap_err();
+    assert!(err.contains("unknown request"), "{err}");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn event_stream_pings_on_window_events_only() {
+    let path = socket_path("events");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let backend = NiriBackend::new(&path);
+    let mut pings = backend.subscribe(false);
+
+    let (conn, _) = listener.accept().await.unwrap();
+    let mut conn = BufReader::new(conn);
+    let mut req = String::new();
+    conn.read_line(&mut req).await.unwrap();
+    assert_eq!(req, "\"EventStream\"\n");
+
+    let conn = conn.get_mut();
+    conn.write_all(b"{\"Ok\":\"Handled\"}\n").await.unwrap();
+    conn.write_all(b"{\"WorkspaceActivated\":{\"id\":2,\"focused\":true}}\n")
+        .await
+        .unwrap();
+    let quiet = tokio::time::timeout(Duration::from_millis(100), pings.recv()).await;
+    assert!(quiet.is_err(), "non-window events must not ping");
+
+    conn.write_all(b"{\"WindowClosed\":{\"id\":3}}\n")
+        .await
+        .unwrap();
+    let ping = tokio::time::timeout(Duration::from_secs(2), pings.recv()).await;
+    assert_eq!(ping.unwrap(), Some(()));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,215 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::env;
+use std::path::PathBuf;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+
+use super::{Window, count_matching_windows, json_str};
+use crate::core::config::InhibitRule;
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+
+const MSG_SUBSCRIBE: u32 = 2;
+const MSG_GET_TREE: u32 = 4;
+
+/// Event replies carry the high bit; `workspace` is event 0, `window` event 3.
+const EVENT_WORKSPACE: u32 = 0x8000_0000;
+const EVENT_WINDOW: u32 = 0x8000_0003;
+
+/// `window` event changes that can alter the set of app_ids on screen.
+const WINDOW_CHANGES: &[&str] = &["new", "close", "fullscreen_mode"];
+
+/// Further `window` changes that matter once rules look at titles or focus.
+const PROPERTY_CHANGES: &[&str] = &["title", "focus", "move", "floating"];
+
+/// Speaks the i3-ipc binary protocol on `$SWAYSOCK`.
+#[derive(Debug)]
+pub(super) struct SwayBackend {
+    socket: PathBuf,
+}
+
+pub(super) async fn write_message(
+    stream: &mut UnixStream,
+    kind: u32,
+    payload: &[u8],
+) -> std::io::Result<()> {
+    let mut msg = Vec::with_capacity(14 + payload.len());
+    msg.extend_from_slice(MAGIC);
+    msg.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    msg.extend_from_slice(&kind.to_ne_bytes());
+    msg.extend_from_slice(payload);
+    stream.write_all(&msg).await
+}
+
+pub(super) async fn read_message(stream: &mut UnixStream) -> std::io::Result<(u32, Vec<u8>)> {
+    let mut header = [0u8; 14];
+    stream.read_exact(&mut header).await?;
+
+    if &header[..6] != MAGIC {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "bad i3-ipc magic",
+        ));
+    }
+
+    let len = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+    let kind = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+    Ok((kind, payload))
+}
+
+/// Views in the tree: app_id for Wayland clients, `window_properties.class`
+/// for Xwayland ones. Only views carry `app_id` (possibly null) or `pid`.
+fn collect_windows(node: &serde_json::Value, out: &mut Vec<Window>) {
+    if node.get("app_id").is_some() || node.get("pid").is_some() {
+        let app_id = node
+            .get("app_id")
+            .and_then(|x| x.as_str())
+            .filter(|s| !s.is_empty())
+            .or_else(|| {
+                node.get("window_properties")
+                    .and_then(|p| p.get("class"))
+                    .and_then(|x| x.as_str())
+            })
+            .unwrap_or("");
+
+        out.push(Window {
+            app_id: app_id.to_string(),
+            title: json_str(node, "name"),
+            fullscreen: node
+                .get("fullscreen_mode")
+                .and_then(|x| x.as_u64())
+                .map(|m| m != 0),
+            focused: node.get("focused").and_then(|x| x.as_bool()),
+            // sway's `visible`: on a shown workspace and not hidden in a tab/stack.
+            workspace_visible: node.get("visible").and_then(|x| x.as_bool()),
+        });
+    }
+
+    for key in ["nodes", "floating_nodes"] {
+        if let Some(children) = node.get(key).and_then(|c| c.as_array()) {
+            for child in children {
+                collect_windows(child, out);
+            }
+        }
+    }
+}
+
+impl SwayBackend {
+    pub(super) fn new(socket: impl Into<PathBuf>) -> Self {
+        Self {
+            socket: socket.into(),
+        }
+    }
+
+    pub(super) fn from_env() -> Option<Self> {
+        env::var_os("SWAYSOCK").map(Self::new)
+    }
+
+    async fn connect(&self) -> Result<UnixStream, String> {
+        UnixStream::connect(&self.socket)
+            .await
+            .map_err(|e| format!("connect {}: {e}", self.socket.display()))
+    }
+
+    pub(super) async fn windows(&self) -> Result<Vec<Window>, String> {
+        let mut stream = self.connect().await?;
+
+        write_message(&mut stream, MSG_GET_TREE, b"")
+            .await
+            .map_err(|e| format!("sway write failed: {e}"))?;
+        let (_, payload) = read_message(&mut stream)
+            .await
+            .map_err(|e| format!("sway read failed: {e}"))?;
+
+        let tree: serde_json::Value = serde_json::from_slice(&payload)
+            .map_err(|e| format!("sway get_tree json parse failed: {e}"))?;
+
+        let mut windows = Vec::new();
+        collect_windows(&tree, &mut windows);
+        Ok(windows)
+    }
+
+    pub(super) async fn count_matches_dedup(&self, rules: &[InhibitRule]) -> Result<u64, String> {
+        let windows = self.windows().await?;
+        Ok(count_matching_windows(&windows, rules))
+    }
+
+    /// Subscribe to `window` events and ping on new/close/fullscreen. With
+    /// `props`, also title/focus changes and any `workspace` event. Pings
+    /// coalesce; the channel closes if sway drops the connection.
+    pub(super) fn subscribe(&self, props: bool) -> mpsc::Receiver<()> {
+        let (tx, rx) = mpsc::channel(1);
+        let socket = self.socket.clone();
+
+        tokio::spawn(async move {
+            let backend = SwayBackend::new(socket);
+            let mut stream = match backend.connect().await {
+                Ok(s) => s,
+                Err(e) => {
+                    eventline::warn!("app_inhibit: {e}");
+                    return;
+                }
+            };
+
+            let events: &[u8] = if props {
+                br#"["window","workspace"]"#
+            } else {
+                br#"["window"]"#
+            };
+            if let Err(e) = write_message(&mut stream, MSG_SUBSCRIBE, events).await {
+                eventline::warn!("app_inhibit: sway subscribe failed: {e}");
+                return;
+            }
+
+            while let Ok((kind, payload)) = read_message(&mut stream).await {
+                if kind == MSG_SUBSCRIBE {
+                    let ok = serde_json::from_slice::<serde_json::Value>(&payload)
+                        .ok()
+                        .and_then(|v| v.get("success").and_then(|s| s.as_bool()))
+                        .unwrap_or(false);
+                    if !ok {
+                        eventline::warn!("app_inhibit: sway refused the window subscription");
+                        return;
+                    }
+                    continue;
+                }
+
+                if kind == EVENT_WORKSPACE && props {
+                    if tx.is_closed() {
+                        return;
+                    }
+                    let _ = tx.try_send(());
+                    continue;
+                }
+
+                if kind != EVENT_WINDOW {
+                    continue;
+                }
+
+                let change = serde_json::from_slice::<serde_json::Value>(&payload)
+                    .ok()
+                    .and_then(|v| v.get("change").and_then(|c| c.as_str()).map(str::to_string))
+                    .unwrap_or_default();
+                let relevant = WINDOW_CHANGES.contains(&change.as_str())
+                    || (props && PROPERTY_CHANGES.contains(&change.as_str()));
+                if !relevant {
+                    continue;
+                }
+
+                if tx.is_closed() {
+                    return;
+                }
+                let _ = tx.try_send(());
+            }
+        });
+
+        rx
+    }
+}
@@ -0,0 +1,149 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tokio::net::UnixListener;
+
+use super::sway::{SwayBackend, read_message, write_message};
+use crate::core::config::{InhibitRule, Pattern};
+
+const TREE: &str = r#"{
+  "id": 1, "type": "root", "nodes": [
+    {"id": 2, "type": "output", "nodes": [
+      {"id": 3, "type": "workspace", "nodes": [
+        {"id": 10, "type": "con", "app_id": "mpv", "name": "movie.mkv",
+         "fullscreen_mode": 1, "focused": true, "visible": true, "nodes": []},
+        {"id": 11, "type": "con", "app_id": null,
+         "window_properties": {"class": "steam_app_1086940"}, "nodes": []}
+      ], "floating_nodes": [
+        {"id": 12, "type": "floating_con", "app_id": "org.mozilla.firefox",
+         "name": "Lofi beats - YouTube — Mozilla Firefox", "fullscreen_mode": 0,
+         "focused": false, "visible": false, "nodes": []},
+        {"id": 13, "type": "floating_con", "app_id": "mpv", "nodes": []}
+      ]}
+    ]}
+  ]
+}"#;
+
+fn socket_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("stasis-sway-{}-{name}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[tokio::test]
+async fn get_tree_finds_app_ids_and_xwayland_classes() {
+    let path = socket_path("tree");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut conn, _)) = listener.accept().await {
+            let (kind, _) = read_message(&mut conn).await.unwrap();
+            assert_eq!(kind, 4);
+            write_message(&mut conn, 4, TREE.as_bytes()).await.unwrap();
+        }
+    });
+
+    let backend = SwayBackend::new(&path);
+
+    let apps = InhibitRule::combined(
+        &[
+            Pattern::Literal("mpv".into()),
+            Pattern::Regex(regex::Regex::new("^steam_app_").unwrap()),
+        ],
+        &[],
+    );
+    assert_eq!(backend.count_matches_dedup(&apps).await, Ok(2));
+
+    let apps = InhibitRule::combined(&[Pattern::Literal("org.mozilla.firefox".into())], &[]);
+    assert_eq!(backend.count_matches_dedup(&apps).await, Ok(1));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn structured_rules_and_their_fields() {
+    let path = socket_path("rules");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut conn, _)) = listener.accept().await {
+            read_message(&mut conn).await.unwrap();
+            write_message(&mut conn, 4, TREE.as_bytes()).await.unwrap();
+        }
+    });
+
+    let backend = SwayBackend::new(&path);
+
+    let youtube = InhibitRule {
+        app_id: Some(Pattern::Literal("org.mozilla.firefox".into())),
+        title: Some(Pattern::Regex(regex::Regex::new("YouTube").unwrap())),
+        ..InhibitRule::default()
+    };
+    assert_eq!(
+        backend
+            .count_matches_dedup(std::slice::from_ref(&youtube))
+            .await,
+        Ok(1)
+    );
+
+    // AND: the YouTube tab is on a hidden workspace.
+    let visible_youtube = InhibitRule {
+        workspace_visible: Some(true),
+        ..youtube
+    };
+    assert_eq!(backend.count_matches_dedup(&[visible_youtube]).await, Ok(0));
+
+    let any_fullscreen = InhibitRule {
+        fullscreen: Some(true),
+        focused: Some(true),
+        ..InhibitRule::default()
+    };
+    assert_eq!(backend.count_matches_dedup(&[any_fullscreen]).await, Ok(1));
+
+    // Literal titles are case-insensitive substrings.
+    let title_only = InhibitRule {
+        title: Some(Pattern::Literal("lofi".into())),
+        ..InhibitRule::default()
+    };
+    assert_eq!(backend.count_matches_dedup(&[title_only]).await, Ok(1));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn window_subscription_pings_on_new_and_close() {
+    let path = socket_path("events");
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let backend = SwayBackend::new(&path);
+    let mut pings = backend.subscribe(false);
+
+    let (mut conn, _) = listener.accept().await.unwrap();
+    let (kind, payload) = read_message(&mut conn).await.unwrap();
+    assert_eq!(kind, 2);
+    assert_eq!(payload, br#"["window"]"#);
+    write_message(&mut conn, 2, br#"{"success": true}"#)
+        .await
+        .unwrap();
+
+    write_message(&mut conn, 0x8000_0003, br#"{"change": "focus"}"#)
+        .await
+        .unwrap();
+    let quiet = tokio::time::timeout(Duration::from_millis(100), pings.recv()).await;
+    assert!(quiet.is_err(), "focus changes must not ping");
+
+    write_message(&mut conn, 0x8000_0003, br#"{"change": "new"}"#)
+        .await
+        .unwrap();
+    let ping = tokio::time::timeout(Duration::from_secs(2), pings.recv()).await;
+    assert_eq!(ping.unwrap(), Some(()));
+
+    drop(conn);
+    let closed = tokio::time::timeout(Duration::from_secs(2), pings.recv()).await;
+    assert_eq!(closed.unwrap(), None);
+
+    std::fs::remove_file(&path).unwrap();
+}
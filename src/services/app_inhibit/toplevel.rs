@@ -0,0 +1,62 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use tokio::sync::{mpsc, watch};
+
+use super::{Window, count_matching_windows};
+use crate::core::config::InhibitRule;
+use crate::services::wayland::Toplevels;
+
+/// Reads the toplevel list `services::wayland` keeps from the foreign-toplevel
+/// protocols; works on any compositor that advertises one of them.
+#[derive(Debug)]
+pub(super) struct ToplevelBackend {
+    rx: watch::Receiver<Toplevels>,
+}
+
+impl ToplevelBackend {
+    pub(super) fn new(rx: watch::Receiver<Toplevels>) -> Self {
+        Self { rx }
+    }
+
+    pub(super) fn protocol(&self) -> Option<&'static str> {
+        self.rx.borrow().protocol
+    }
+
+    /// Neither protocol says which workspace a toplevel is on.
+    pub(super) fn windows(&self) -> Vec<Window> {
+        self.rx
+            .borrow()
+            .windows
+            .iter()
+            .map(|t| Window {
+                app_id: t.app_id.clone(),
+                title: t.title.clone(),
+                fullscreen: t.fullscreen,
+                focused: t.focused,
+                workspace_visible: None,
+            })
+            .collect()
+    }
+
+    pub(super) fn count_matches_dedup(&self, rules: &[InhibitRule]) -> u64 {
+        count_matching_windows(&self.windows(), rules)
+    }
+
+    /// Ping whenever the published list changes; closes with the Wayland service.
+    pub(super) fn subscribe(&self) -> mpsc::Receiver<()> {
+        let (tx, rx) = mpsc::channel(1);
+        let mut toplevels = self.rx.clone();
+
+        tokio::spawn(async move {
+            while toplevels.changed().await.is_ok() {
+                if tx.is_closed() {
+                    return;
+                }
+                let _ = tx.try_send(());
+            }
+        });
+
+        rx
+    }
+}
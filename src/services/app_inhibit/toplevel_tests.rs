@@ -0,0 +1,45 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use super::toplevel::ToplevelBackend;
+use crate::core::config::{InhibitRule, Pattern};
+use crate::services::wayland::{Toplevels, toplevel::Toplevel};
+
+fn toplevels(app_ids: &[&str]) -> Toplevels {
+    Toplevels {
+        protocol: Some("ext_foreign_toplevel_list_v1"),
+        windows: app_ids
+            .iter()
+            .map(|a| Toplevel {
+                app_id: a.to_string(),
+                ..Toplevel::default()
+            })
+            .collect(),
+    }
+}
+
+#[tokio::test]
+async fn counts_follow_the_published_list() {
+    let (tx, rx) = watch::channel(toplevels(&["mpv", "mpv", "foot"]));
+    let backend = ToplevelBackend::new(rx);
+    let apps = InhibitRule::combined(&[Pattern::Literal("mpv".into())], &[]);
+
+    assert_eq!(backend.protocol(), Some("ext_foreign_toplevel_list_v1"));
+    assert_eq!(backend.count_matches_dedup(&apps), 1);
+
+    let mut pings = backend.subscribe();
+    tx.send_replace(toplevels(&["foot"]));
+
+    let ping = tokio::time::timeout(Duration::from_secs(2), pings.recv()).await;
+    assert_eq!(ping.unwrap(), Some(()));
+    assert_eq!(backend.count_matches_dedup(&apps), 0);
+
+    // Wayland service gone => stream ends.
+    drop(tx);
+    let closed = tokio::time::timeout(Duration::from_secs(2), pings.recv()).await;
+    assert_eq!(closed.unwrap(), None);
+}
@@ -0,0 +1,141 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use zbus::{Connection, Proxy, zvariant::OwnedValue};
+
+use crate::core::events::BatteryStatus;
+
+pub const UPOWER_DEST: &str = "org.freedesktop.UPower";
+pub const DISPLAY_DEVICE_PATH: &str = "/org/freedesktop/UPower/devices/DisplayDevice";
+pub const UPOWER_DEVICE_IFACE: &str = "org.freedesktop.UPower.Device";
+
+pub const SYSFS_POWER_SUPPLY: &str = "/sys/class/power_supply";
+
+/// `org.freedesktop.UPower.Device` State values that mean the battery drains.
+const UPOWER_DISCHARGING: u32 = 2;
+const UPOWER_EMPTY: u32 = 3;
+const UPOWER_PENDING_DISCHARGE: u32 = 6;
+
+/// Current battery charge: UPower's DisplayDevice (all batteries combined),
+/// else sysfs. `None` when neither reports a battery.
+pub async fn read(conn: Option<&Connection>) -> Option<BatteryStatus> {
+    if let Some(conn) = conn {
+        match display_device(conn).await {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) => {}
+            Err(e) => eventline::debug!("battery: UPower DisplayDevice unavailable: {e}"),
+        }
+    }
+
+    read_sysfs(Path::new(SYSFS_POWER_SUPPLY))
+}
+
+/// `Ok(None)` when UPower has no battery behind the DisplayDevice.
+pub async fn display_device(conn: &Connection) -> zbus::Result<Option<BatteryStatus>> {
+    let proxy = Proxy::new(
+        conn,
+        UPOWER_DEST,
+        DISPLAY_DEVICE_PATH,
+        "org.freedesktop.DBus.Properties",
+    )
+    .await?;
+
+    let props: HashMap<String, OwnedValue> = proxy.call("GetAll", &(UPOWER_DEVICE_IFACE,)).await?;
+
+    let prop = |name: &str| props.get(name);
+    if !prop("IsPresent")
+        .and_then(|v| v.downcast_ref::<bool>().ok())
+        .unwrap_or(false)
+    {
+        return Ok(None);
+    }
+    let Some(percent) = prop("Percentage").and_then(|v| v.downcast_ref::<f64>().ok()) else {
+        return Ok(None);
+    };
+    let state = prop("State")
+        .and_then(|v| v.downcast_ref::<u32>().ok())
+        .unwrap_or(0);
+    let time_to_empty = prop("TimeToEmpty")
+        .and_then(|v| v.downcast_ref::<i64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(|secs| secs as u64);
+
+    Ok(Some(BatteryStatus {
+        percent: percent.round().clamp(0.0, 100.0) as u8,
+        discharging: matches!(
+            state,
+            UPOWER_DISCHARGING | UPOWER_EMPTY | UPOWER_PENDING_DISCHARGE
+        ),
+        time_to_empty_secs: time_to_empty,
+    }))
+}
+
+/// Combine every system battery under `root` (peripherals report `scope` Device).
+pub fn read_sysfs(root: &Path) -> Option<BatteryStatus> {
+    let read = |dir: &Path, name: &str| {
+        std::fs::read_to_string(dir.join(name))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+    let read_u64 = |dir: &Path, name: &str| read(dir, name).and_then(|s| s.parse::<u64>().ok());
+
+    let mut now = 0u64;
+    let mut full = 0u64;
+    let mut drain = 0u64;
+    let mut capacities = Vec::new();
+    let mut discharging = false;
+    let mut found = false;
+
+    for entry in std::fs::read_dir(root).ok()?.flatten() {
+        let dir = entry.path();
+        if read(&dir, "type").as_deref() != Some("Battery")
+            || read(&dir, "scope").as_deref() == Some("Device")
+            || read(&dir, "present").as_deref() == Some("0")
+        {
+            continue;
+        }
+        found = true;
+
+        discharging |= read(&dir, "status").as_deref() == Some("Discharging");
+
+        // energy_* (µWh, power_now µW) or charge_* (µAh, current_now µA).
+        let counters = read_u64(&dir, "energy_now")
+            .zip(read_u64(&dir, "energy_full"))
+            .map(|c| (c, read_u64(&dir, "power_now")))
+            .or_else(|| {
+                read_u64(&dir, "charge_now")
+                    .zip(read_u64(&dir, "charge_full"))
+                    .map(|c| (c, read_u64(&dir, "current_now")))
+            });
+
+        match counters {
+            Some(((n, f), rate)) => {
+                now += n;
+                full += f;
+                drain += rate.unwrap_or(0);
+            }
+            None => capacities.extend(read_u64(&dir, "capacity")),
+        }
+    }
+
+    if !found {
+        return None;
+    }
+
+    let percent = match (now.saturating_mul(100) + full / 2).checked_div(full) {
+        Some(p) => p,
+        None => capacities
+            .iter()
+            .sum::<u64>()
+            .checked_div(capacities.len() as u64)?,
+    };
+
+    Some(BatteryStatus {
+        percent: percent.min(100) as u8,
+        discharging,
+        time_to_empty_secs: (discharging && drain > 0).then(|| now.saturating_mul(3600) / drain),
+    })
+}
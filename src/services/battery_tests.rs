@@ -0,0 +1,172 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::path::{Path, PathBuf};
+
+use zbus::{Connection, Guid, connection};
+
+use crate::core::events::BatteryStatus;
+use crate::services::battery::{self, DISPLAY_DEVICE_PATH};
+
+/// Minimal stand-in for UPower's DisplayDevice.
+struct MockDevice {
+    present: bool,
+    percentage: f64,
+    state: u32,
+    time_to_empty: i64,
+}
+
+#[zbus::interface(name = "org.freedesktop.UPower.Device")]
+impl MockDevice {
+    #[zbus(property)]
+    fn is_present(&self) -> bool {
+        self.present
+    }
+
+    #[zbus(property)]
+    fn percentage(&self) -> f64 {
+        self.percentage
+    }
+
+    #[zbus(property)]
+    fn state(&self) -> u32 {
+        self.state
+    }
+
+    #[zbus(property)]
+    fn time_to_empty(&self) -> i64 {
+        self.time_to_empty
+    }
+}
+
+async fn private_upower(device: MockDevice) -> (Connection, Connection) {
+    let (a, b) = tokio::net::UnixStream::pair().unwrap();
+
+    let server = connection::Builder::unix_stream(a)
+        .server(Guid::generate())
+        .unwrap()
+        .p2p()
+        .serve_at(DISPLAY_DEVICE_PATH, device)
+        .unwrap()
+        .build();
+    let client = connection::Builder::unix_stream(b).p2p().build();
+
+    futures::try_join!(server, client).unwrap()
+}
+
+#[tokio::test]
+async fn display_device_reports_charge_and_time_to_empty() {
+    let (_server, client) = private_upower(MockDevice {
+        present: true,
+        percentage: 4.6,
+        state: 2,
+        time_to_empty: 540,
+    })
+    .await;
+
+    assert_eq!(
+        battery::display_device(&client).await.unwrap(),
+        Some(BatteryStatus {
+            percent: 5,
+            discharging: true,
+            time_to_empty_secs: Some(540),
+        })
+    );
+}
+
+#[tokio::test]
+async fn display_device_without_battery_is_none() {
+    let (_server, client) = private_upower(MockDevice {
+        present: false,
+        percentage: 0.0,
+        state: 0,
+        time_to_empty: 0,
+    })
+    .await;
+
+    assert_eq!(battery::display_device(&client).await.unwrap(), None);
+}
+
+fn supply(root: &Path, name: &str, files: &[(&str, &str)]) {
+    let dir = root.join(name);
+    std::fs::create_dir_all(&dir).unwrap();
+    for (file, contents) in files {
+        std::fs::write(dir.join(file), format!("{contents}\n")).unwrap();
+    }
+}
+
+fn sysfs_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("stasis-battery-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&root).unwrap();
+    root
+}
+
+#[test]
+fn sysfs_combines_batteries_and_ignores_peripherals() {
+    let root = sysfs_root("combined");
+    supply(&root, "AC", &[("type", "Mains"), ("online", "0")]);
+    supply(
+        &root,
+        "BAT0",
+        &[
+            ("type", "Battery"),
+            ("status", "Discharging"),
+            ("energy_now", "6000000"),
+            ("energy_full", "40000000"),
+            ("power_now", "8000000"),
+        ],
+    );
+    supply(
+        &root,
+        "BAT1",
+        &[
+            ("type", "Battery"),
+            ("status", "Unknown"),
+            ("energy_now", "2000000"),
+            ("energy_full", "20000000"),
+        ],
+    );
+    supply(
+        &root,
+        "hidpp_battery_0",
+        &[("type", "Battery"), ("scope", "Device"), ("capacity", "90")],
+    );
+
+    assert_eq!(
+        battery::read_sysfs(&root),
+        Some(BatteryStatus {
+            percent: 13,
+            discharging: true,
+            time_to_empty_secs: Some(3600),
+        })
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn sysfs_falls_back_to_capacity() {
+    let root = sysfs_root("capacity");
+    supply(
+        &root,
+        "BAT0",
+        &[
+            ("type", "Battery"),
+            ("status", "Charging"),
+            ("capacity", "57"),
+        ],
+    );
+
+    assert_eq!(
+        battery::read_sysfs(&root),
+        Some(BatteryStatus {
+            percent: 57,
+            discharging: false,
+            time_to_empty_secs: None,
+        })
+    );
+
+    std::fs::remove_dir_all(&root).unwrap();
+    assert_eq!(battery::read_sysfs(&root), None);
+}
@@ -0,0 +1,289 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use tokio::sync::{mpsc, watch};
+
+use crate::core::config::Pattern;
+use crate::core::events::Event;
+use crate::core::manager_msg::ManagerMsg;
+
+#[derive(Debug, Clone)]
+pub struct CaptureRules {
+    pub epoch: u64, // forces watch::changed() on profile/reload even if values are identical
+    pub enabled: bool,
+    pub blacklist: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureKind {
+    Microphone,
+    Camera,
+}
+
+/// Something currently recording: an uncorked pactl source-output or a process
+/// holding a `/dev/video*` node open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capturer {
+    pub kind: CaptureKind,
+    pub pid: Option<u32>,
+    pub app: String,
+    pub binary: String,
+}
+
+impl Capturer {
+    /// A call app usually shows up twice (mic + camera); count it once.
+    fn key(&self) -> String {
+        match self.pid {
+            Some(pid) => format!("pid:{pid}"),
+            None => format!("app:{}", self.app.to_lowercase()),
+        }
+    }
+}
+
+/// Distinct capturing processes left after `blacklist`.
+pub fn count_capturers(capturers: &[Capturer], blacklist: &[Pattern]) -> u64 {
+    let mut keys: HashSet<String> = HashSet::new();
+
+    for c in capturers {
+        let hay_lc = format!("{} {}", c.app, c.binary).to_lowercase();
+        if blacklist.iter().any(|p| p.matches_lc(&hay_lc)) {
+            continue;
+        }
+        keys.insert(c.key());
+    }
+
+    keys.len() as u64
+}
+
+/// Spawnable task: polls for active audio capture and open cameras while
+/// `monitor_capture` is enabled.
+pub async fn run_capture(
+    tx: mpsc::Sender<ManagerMsg>,
+    mut rules_rx: watch::Receiver<CaptureRules>,
+) {
+    let initial = rules_rx.borrow().clone();
+    let mut enabled = initial.enabled;
+    let mut blacklist = initial.blacklist;
+    let mut last: Option<u64> = None;
+
+    eventline::info!(
+        "capture: started (enabled={}, blacklist_len={})",
+        enabled,
+        blacklist.len()
+    );
+
+    loop {
+        let count = if enabled {
+            let capturers = tokio::task::spawn_blocking(read_capturers)
+                .await
+                .unwrap_or_default();
+            let count = count_capturers(&capturers, &blacklist);
+
+            if last.is_some_and(|l| l != count) || (last.is_none() && count != 0) {
+                eventline::info!("capture: count {} -> {}", last.unwrap_or(0), count);
+                for c in &capturers {
+                    eventline::debug!(
+                        "capture: {:?} {} ({}, pid={:?})",
+                        c.kind,
+                        c.app,
+                        c.binary,
+                        c.pid
+                    );
+                }
+            }
+            count
+        } else {
+            0
+        };
+
+        if last != Some(count) {
+            last = Some(count);
+            let ev = Event::CaptureInhibitorCount {
+                count,
+                now_ms: crate::core::utils::now_ms(),
+            };
+            if tx.send(ManagerMsg::Event(ev)).await.is_err() {
+                return;
+            }
+        }
+
+        tokio::select! {
+            changed = rules_rx.changed() => {
+                if changed.is_err() {
+                    return; // sender dropped => shutting down
+                }
+
+                let rules = rules_rx.borrow().clone();
+                eventline::debug!(
+                    "capture: rules epoch={} enabled={} blacklist_len={}",
+                    rules.epoch,
+                    rules.enabled,
+                    rules.blacklist.len()
+                );
+                enabled = rules.enabled;
+                blacklist = rules.blacklist;
+
+                // The manager zeroes the count on profile switch; re-seed it.
+                last = None;
+            }
+
+            // Nothing to poll while disabled.
+            _ = tokio::time::sleep(Duration::from_secs(2)), if enabled => {}
+        }
+    }
+}
+
+fn read_capturers() -> Vec<Capturer> {
+    let mut out = read_pactl_source_outputs().unwrap_or_else(|e| {
+        eventline::debug!("capture: {e}");
+        Vec::new()
+    });
+    out.extend(scan_video_handles(Path::new("/proc")));
+    out
+}
+
+fn read_pactl_source_outputs() -> Result<Vec<Capturer>, String> {
+    let out = Command::new("sh")
+        .arg("-lc")
+        .arg("pactl list source-outputs")
+        .output()
+        .map_err(|e| format!("failed to run pactl: {e}"))?;
+
+    if !out.status.success() {
+        return Err(format!(
+            "pactl exited non-zero: {}",
+            out.status.code().unwrap_or(-1)
+        ));
+    }
+
+    Ok(parse_pactl_source_outputs(&String::from_utf8_lossy(
+        &out.stdout,
+    )))
+}
+
+/// Uncorked `pactl list source-outputs` entries, minus level meters.
+pub fn parse_pactl_source_outputs(text: &str) -> Vec<Capturer> {
+    #[derive(Default)]
+    struct Block {
+        corked: bool,
+        app: String,
+        binary: String,
+        pid: Option<u32>,
+        media_name: String,
+    }
+
+    fn flush(block: Option<Block>, out: &mut Vec<Capturer>) {
+        let Some(b) = block else { return };
+        if b.corked {
+            return;
+        }
+        // pavucontrol & friends open "Peak detect" streams just to draw VU meters.
+        if b.media_name.to_lowercase().contains("peak detect") {
+            return;
+        }
+        if b.app.is_empty() && b.binary.is_empty() && b.pid.is_none() {
+            return;
+        }
+        out.push(Capturer {
+            kind: CaptureKind::Microphone,
+            pid: b.pid,
+            app: if b.app.is_empty() {
+                b.binary.clone()
+            } else {
+                b.app
+            },
+            binary: b.binary,
+        });
+    }
+
+    let mut out = Vec::new();
+    let mut block: Option<Block> = None;
+
+    for line in text.lines() {
+        let l = line.trim();
+
+        if l.starts_with("Source Output #") {
+            flush(block.take(), &mut out);
+            block = Some(Block::default());
+            continue;
+        }
+
+        let Some(b) = block.as_mut() else { continue };
+
+        if let Some(rest) = l.strip_prefix("Corked:") {
+            b.corked = rest.trim().eq_ignore_ascii_case("yes");
+            continue;
+        }
+
+        let Some((k, v)) = l.split_once('=') else {
+            continue;
+        };
+        let v = v.trim().trim_matches('"').to_string();
+        match k.trim() {
+            "application.name" => b.app = v,
+            "application.process.binary" => b.binary = v,
+            "application.process.id" => b.pid = v.parse().ok(),
+            "media.name" => b.media_name = v,
+            _ => {}
+        }
+    }
+    flush(block, &mut out);
+
+    out
+}
+
+/// Processes under `proc_root` with a `/dev/video*` fd open. Unreadable
+/// processes (other users, races with exit) are skipped.
+pub fn scan_video_handles(proc_root: &Path) -> Vec<Capturer> {
+    let me = std::process::id();
+    let mut out = Vec::new();
+
+    let Ok(entries) = fs::read_dir(proc_root) else {
+        return out;
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid == me {
+            continue;
+        }
+
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+
+        let has_video = fds.flatten().any(|fd| {
+            fs::read_link(fd.path())
+                .map(|t| t.to_string_lossy().starts_with("/dev/video"))
+                .unwrap_or(false)
+        });
+        if !has_video {
+            continue;
+        }
+
+        let comm = fs::read_to_string(entry.path().join("comm"))
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+
+        out.push(Capturer {
+            kind: CaptureKind::Camera,
+            pid: Some(pid),
+            app: comm.clone(),
+            binary: comm,
+        });
+    }
+
+    out
+}
@@ -0,0 +1,95 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::fs;
+use std::os::unix::fs::symlink;
+
+use crate::core::config::Pattern;
+use crate::services::capture::{
+    count_capturers, parse_pactl_source_outputs, scan_video_handles, CaptureKind,
+};
+
+const SOURCE_OUTPUTS: &str = r#"Source Output #41
+	Driver: PipeWire
+	Owner Module: n/a
+	Client: 77
+	Source: 52
+	Corked: no
+	Properties:
+		media.name = "Peak detect"
+		application.name = "pavucontrol"
+		application.process.id = "900"
+
+Source Output #42
+	Driver: PipeWire
+	Client: 80
+	Source: 52
+	Corked: no
+	Properties:
+		media.name = "audio-capture"
+		application.name = "Chromium input"
+		application.process.binary = "chromium"
+		application.process.id = "1234"
+
+Source Output #43
+	Driver: PipeWire
+	Client: 81
+	Source: 52
+	Corked: yes
+	Properties:
+		application.name = "obs"
+		application.process.id = "555"
+"#;
+
+#[test]
+fn source_outputs_skip_corked_and_peak_meters() {
+    let caps = parse_pactl_source_outputs(SOURCE_OUTPUTS);
+
+    assert_eq!(caps.len(), 1);
+    assert_eq!(caps[0].kind, CaptureKind::Microphone);
+    assert_eq!(caps[0].app, "Chromium input");
+    assert_eq!(caps[0].binary, "chromium");
+    assert_eq!(caps[0].pid, Some(1234));
+}
+
+#[test]
+fn video_handles_are_found_by_fd_target() {
+    let root = std::env::temp_dir().join(format!("stasis-capture-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+
+    for (pid, comm, target) in [
+        ("1234", "chromium", "/dev/video0"),
+        ("2000", "foot", "/dev/pts/1"),
+    ] {
+        let fd = root.join(pid).join("fd");
+        fs::create_dir_all(&fd).unwrap();
+        fs::write(root.join(pid).join("comm"), format!("{comm}\n")).unwrap();
+        symlink(target, fd.join("3")).unwrap();
+    }
+    fs::create_dir_all(root.join("self")).unwrap();
+
+    let caps = scan_video_handles(&root);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(caps.len(), 1);
+    assert_eq!(caps[0].kind, CaptureKind::Camera);
+    assert_eq!(caps[0].pid, Some(1234));
+    assert_eq!(caps[0].app, "chromium");
+}
+
+#[test]
+fn mic_and_camera_from_one_process_count_once() {
+    let mut caps = parse_pactl_source_outputs(SOURCE_OUTPUTS);
+    caps.push(crate::services::capture::Capturer {
+        kind: CaptureKind::Camera,
+        pid: Some(1234),
+        app: "chromium".into(),
+        binary: "chromium".into(),
+    });
+
+    assert_eq!(count_capturers(&caps, &[]), 1);
+    assert_eq!(
+        count_capturers(&caps, &[Pattern::Literal("chromium".into())]),
+        0
+    );
+}
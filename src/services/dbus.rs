@@ -9,10 +9,11 @@ use std::{
 
 use futures::StreamExt;
 use tokio::runtime::Runtime;
-use tokio::sync::watch;
+use tokio::sync::{OnceCell, watch};
 use zbus::{Connection, MatchRule, Proxy};
 
 use crate::core::events::Event;
+use crate::services::login1::{LOGIN1_DEST, LOGIN1_MANAGER_IFACE, LOGIN1_PATH};
 
 /// Sink for pushing events into the (sync) manager loop.
 /// Implement this for whatever channel/queue you’re using.
@@ -20,13 +21,21 @@ pub trait EventSink: Send + Sync + 'static {
     fn push(&self, ev: Event);
 }
 
+static SYSTEM_BUS: OnceCell<Connection> = OnceCell::const_new();
+
+/// System bus connection shared by the D-Bus listeners (opened on first use).
+pub async fn system_bus() -> zbus::Result<Connection> {
+    SYSTEM_BUS.get_or_try_init(Connection::system).await.cloned()
+}
+
 /// Spawn D-Bus listeners.
 ///
 /// `enable_loginctl` gates all login1-related monitoring:
 /// - PrepareForSleep (org.freedesktop.login1.Manager)
 /// - Lock/Unlock (org.freedesktop.login1.Session)
 ///
-/// Lid events via UPower are always monitored.
+/// Lid events via UPower are always monitored; each one re-reads logind's
+/// `Docked` so the manager can tell a docked laptop from a closed one.
 ///
 /// IMPORTANT: This runs in a dedicated OS thread. That thread MUST terminate on shutdown,
 /// otherwise the stasis process will never exit (even if the main async loop stops).
@@ -57,7 +66,7 @@ async fn run_dbus(
     enable_loginctl: bool,
     mut shutdown: watch::Receiver<bool>,
 ) -> zbus::Result<()> {
-    let sys = match Connection::system().await {
+    let sys = match system_bus().await {
         Ok(c) => c,
         Err(e) => {
             eventline::warn!("D-Bus: could not connect to system bus: {e:?}");
@@ -153,6 +162,15 @@ async fn run_dbus(
 
     // 3) Lid events via UPower PropertiesChanged
     {
+        // Lid already shut at startup (e.g. booted docked).
+        if let Some((lid_closed, docked)) = logind_lid_and_dock(&sys).await {
+            let t = now_ms();
+            sink.push(Event::DockChanged { docked, now_ms: t });
+            if lid_closed {
+                sink.push(Event::LidClosed { now_ms: t });
+            }
+        }
+
         let rule = MatchRule::builder()
             .msg_type(zbus::message::Type::Signal)
             .interface("org.freedesktop.DBus.Properties")?
@@ -162,6 +180,7 @@ async fn run_dbus(
 
         let mut stream = zbus::MessageStream::for_match_rule(rule, &sys, None).await?;
         let sink = sink.clone();
+        let sys = sys.clone();
 
         tokio::spawn(async move {
             use zbus::zvariant::Value;
@@ -184,6 +203,9 @@ async fn run_dbus(
                 if let Some(v) = changed.get("LidIsClosed") {
                     if let Ok(closed) = v.clone().downcast::<bool>() {
                         let t = now_ms();
+                        if let Some((_, docked)) = logind_lid_and_dock(&sys).await {
+                            sink.push(Event::DockChanged { docked, now_ms: t });
+                        }
                         sink.push(if closed {
                             Event::LidClosed { now_ms: t }
                         } else {
@@ -212,9 +234,35 @@ async fn run_dbus(
     Ok(())
 }
 
+/// logind's `LidClosed` and `Docked`. Read through Properties.Get: logind
+/// doesn't signal changes to either, so a caching proxy would go stale.
+async fn logind_lid_and_dock(sys: &Connection) -> Option<(bool, bool)> {
+    let proxy = Proxy::new(sys, LOGIN1_DEST, LOGIN1_PATH, "org.freedesktop.DBus.Properties")
+        .await
+        .ok()?;
+
+    Some((
+        logind_bool(&proxy, "LidClosed").await?,
+        logind_bool(&proxy, "Docked").await?,
+    ))
+}
+
+async fn logind_bool(proxy: &Proxy<'_>, name: &str) -> Option<bool> {
+    match proxy
+        .call::<_, _, zbus::zvariant::OwnedValue>("Get", &(LOGIN1_MANAGER_IFACE, name))
+        .await
+    {
+        Ok(value) => value.downcast_ref::<bool>().ok(),
+        Err(e) => {
+            eventline::debug!("D-Bus: login1 {name} unavailable: {e}");
+            None
+        }
+    }
+}
+
 // ---- Session path resolution (ported from old stasis) ----
 
-async fn get_current_session_path(
+pub(crate) async fn get_current_session_path(
     connection: &Connection,
 ) -> zbus::Result<zbus::zvariant::OwnedObjectPath> {
     let proxy = Proxy::new(
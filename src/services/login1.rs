@@ -0,0 +1,312 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::sync::{Arc, Mutex};
+
+use zbus::{
+    zvariant::{OwnedFd, OwnedObjectPath},
+    Connection, Proxy,
+};
+
+pub const LOGIN1_DEST: &str = "org.freedesktop.login1";
+pub const LOGIN1_PATH: &str = "/org/freedesktop/login1";
+pub const LOGIN1_MANAGER_IFACE: &str = "org.freedesktop.login1.Manager";
+pub const LOGIN1_SESSION_IFACE: &str = "org.freedesktop.login1.Session";
+
+#[derive(Debug)]
+pub enum Login1Error {
+    /// Could not reach the system bus / login1.
+    Connect(String),
+
+    /// Could not resolve our login1 session object.
+    Session(String),
+
+    /// A `Can*` query answered something other than "yes" or "challenge".
+    /// ("no" usually means polkit denied it, "na" means the system can't do it at all.)
+    Unavailable {
+        query: &'static str,
+        answer: String,
+    },
+
+    /// The actual method call failed (polkit denial, interactive auth required, ...).
+    Call {
+        method: &'static str,
+        detail: String,
+    },
+}
+
+impl std::fmt::Display for Login1Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Login1Error::Connect(s) => write!(f, "login1 unavailable: {s}"),
+            Login1Error::Session(s) => write!(f, "login1 session not found: {s}"),
+            Login1Error::Unavailable { query, answer } => {
+                write!(f, "login1 {query} returned \"{answer}\"")
+            }
+            Login1Error::Call { method, detail } => {
+                write!(f, "login1 {method} failed: {detail}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Login1Error {}
+
+pub async fn system_connection() -> Result<Connection, Login1Error> {
+    Connection::system()
+        .await
+        .map_err(|e| Login1Error::Connect(e.to_string()))
+}
+
+async fn manager_proxy(conn: &Connection) -> Result<Proxy<'static>, Login1Error> {
+    Proxy::new(conn, LOGIN1_DEST, LOGIN1_PATH, LOGIN1_MANAGER_IFACE)
+        .await
+        .map_err(|e| Login1Error::Connect(e.to_string()))
+}
+
+/// Ask login1 whether `query` (e.g. "CanSuspend") is allowed.
+///
+/// Returns `Ok(interactive)` when the action may proceed: `false` for "yes",
+/// `true` for "challenge" (polkit wants to ask the user).
+async fn check_can(proxy: &Proxy<'_>, query: &'static str) -> Result<bool, Login1Error> {
+    let answer: String = proxy
+        .call(query, &())
+        .await
+        .map_err(|e| Login1Error::Call {
+            method: query,
+            detail: e.to_string(),
+        })?;
+
+    match answer.as_str() {
+        "yes" => Ok(false),
+        "challenge" => Ok(true),
+        _ => Err(Login1Error::Unavailable { query, answer }),
+    }
+}
+
+/// The login1 sleep operations Stasis can request natively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SleepKind {
+    Suspend,
+    Hibernate,
+    HybridSleep,
+    SuspendThenHibernate,
+}
+
+impl SleepKind {
+    /// `Can*` query and method name on `org.freedesktop.login1.Manager`.
+    fn calls(self) -> (&'static str, &'static str) {
+        match self {
+            SleepKind::Suspend => ("CanSuspend", "Suspend"),
+            SleepKind::Hibernate => ("CanHibernate", "Hibernate"),
+            SleepKind::HybridSleep => ("CanHybridSleep", "HybridSleep"),
+            SleepKind::SuspendThenHibernate => ("CanSuspendThenHibernate", "SuspendThenHibernate"),
+        }
+    }
+}
+
+impl std::fmt::Display for SleepKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SleepKind::Suspend => "suspend",
+            SleepKind::Hibernate => "hibernate",
+            SleepKind::HybridSleep => "hybrid-sleep",
+            SleepKind::SuspendThenHibernate => "suspend-then-hibernate",
+        })
+    }
+}
+
+async fn request_sleep(proxy: &Proxy<'_>, kind: SleepKind) -> Result<(), Login1Error> {
+    let (query, method) = kind.calls();
+    let interactive = check_can(proxy, query).await?;
+
+    proxy
+        .call::<_, _, ()>(method, &(interactive,))
+        .await
+        .map_err(|e| Login1Error::Call {
+            method,
+            detail: e.to_string(),
+        })
+}
+
+/// Suspend through `org.freedesktop.login1.Manager.Suspend`.
+///
+/// `CanSuspend` is consulted first so "na"/"no" surface as a clear error
+/// instead of a generic D-Bus failure.
+pub async fn suspend(conn: &Connection) -> Result<(), Login1Error> {
+    let proxy = manager_proxy(conn).await?;
+    request_sleep(&proxy, SleepKind::Suspend).await
+}
+
+/// Request `kind` through its login1 `Can*`/method pair.
+///
+/// If login1 reports the operation as unavailable (no swap for hibernate,
+/// polkit "no", ...), falls back to plain suspend. Returns the kind that was
+/// actually requested so callers can report the fallback.
+pub async fn sleep(conn: &Connection, kind: SleepKind) -> Result<SleepKind, Login1Error> {
+    let proxy = manager_proxy(conn).await?;
+
+    match request_sleep(&proxy, kind).await {
+        Ok(()) => Ok(kind),
+        Err(e @ Login1Error::Unavailable { .. }) if kind != SleepKind::Suspend => {
+            eventline::warn!("{kind}: {e}; falling back to suspend");
+            request_sleep(&proxy, SleepKind::Suspend).await?;
+            Ok(SleepKind::Suspend)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// One entry of `Manager.ListInhibitors`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inhibitor {
+    /// Colon-separated lock types, e.g. "idle:sleep".
+    pub what: String,
+    pub who: String,
+    pub why: String,
+    /// "block" or "delay".
+    pub mode: String,
+    pub uid: u32,
+    pub pid: u32,
+}
+
+impl Inhibitor {
+    pub fn inhibits(&self, what: &str) -> bool {
+        self.what.split(':').any(|w| w == what)
+    }
+}
+
+pub async fn list_inhibitors(conn: &Connection) -> Result<Vec<Inhibitor>, Login1Error> {
+    let proxy = manager_proxy(conn).await?;
+    let raw: Vec<(String, String, String, String, u32, u32)> = proxy
+        .call("ListInhibitors", &())
+        .await
+        .map_err(|e| Login1Error::Call {
+            method: "ListInhibitors",
+            detail: e.to_string(),
+        })?;
+
+    Ok(raw
+        .into_iter()
+        .map(|(what, who, why, mode, uid, pid)| Inhibitor {
+            what,
+            who,
+            why,
+            mode,
+            uid,
+            pid,
+        })
+        .collect())
+}
+
+/// A login1 inhibitor lock, held for as long as its fd is open. Clones share
+/// the same lock.
+#[derive(Clone)]
+pub struct InhibitLock {
+    what: &'static str,
+    why: &'static str,
+    mode: &'static str,
+    fd: Arc<Mutex<Option<OwnedFd>>>,
+}
+
+impl InhibitLock {
+    fn new(what: &'static str, why: &'static str, mode: &'static str) -> Self {
+        Self {
+            what,
+            why,
+            mode,
+            fd: Arc::default(),
+        }
+    }
+
+    /// `delay` lock on "sleep": logind waits (up to `InhibitDelayMaxSec`)
+    /// after emitting PrepareForSleep before actually sleeping.
+    pub fn sleep_delay() -> Self {
+        Self::new("sleep", "Locking the session before sleep", "delay")
+    }
+
+    /// `block` lock on "handle-lid-switch": logind leaves the lid to us, so
+    /// `lid_policy "suspend"` doesn't suspend a second time.
+    pub fn lid_switch() -> Self {
+        Self::new("handle-lid-switch", "Handling the lid switch itself", "block")
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.fd.lock().unwrap().is_some()
+    }
+
+    /// Take the lock through `Manager.Inhibit` (no-op if already held).
+    pub async fn acquire(&self, conn: &Connection) -> Result<(), Login1Error> {
+        if self.is_held() {
+            return Ok(());
+        }
+
+        let proxy = manager_proxy(conn).await?;
+        let fd: OwnedFd = proxy
+            .call("Inhibit", &(self.what, "Stasis", self.why, self.mode))
+            .await
+            .map_err(|e| Login1Error::Call {
+                method: "Inhibit",
+                detail: e.to_string(),
+            })?;
+
+        *self.fd.lock().unwrap() = Some(fd);
+        Ok(())
+    }
+
+    /// Close the fd so logind may proceed. Returns whether a lock was held.
+    pub fn release(&self) -> bool {
+        self.fd.lock().unwrap().take().is_some()
+    }
+}
+
+/// Our login1 session (`org.freedesktop.login1.Session`).
+pub struct Session {
+    conn: Connection,
+    path: OwnedObjectPath,
+}
+
+impl Session {
+    pub fn new(conn: Connection, path: OwnedObjectPath) -> Self {
+        Self { conn, path }
+    }
+
+    /// Resolve the current session the same way the D-Bus listeners do.
+    pub async fn current() -> Result<Self, Login1Error> {
+        let conn = system_connection().await?;
+        let path = crate::services::dbus::get_current_session_path(&conn)
+            .await
+            .map_err(|e| Login1Error::Session(e.to_string()))?;
+        Ok(Self::new(conn, path))
+    }
+
+    async fn proxy(&self) -> Result<Proxy<'_>, Login1Error> {
+        Proxy::new(&self.conn, LOGIN1_DEST, &self.path, LOGIN1_SESSION_IFACE)
+            .await
+            .map_err(|e| Login1Error::Connect(e.to_string()))
+    }
+
+    /// `Session.Lock`: asks login1 to emit `Lock` so registered lockers engage.
+    pub async fn lock(&self) -> Result<(), Login1Error> {
+        self.proxy()
+            .await?
+            .call::<_, _, ()>("Lock", &())
+            .await
+            .map_err(|e| Login1Error::Call {
+                method: "Lock",
+                detail: e.to_string(),
+            })
+    }
+
+    /// `Session.SetLockedHint`: tells login1 whether the session is currently locked.
+    pub async fn set_locked_hint(&self, locked: bool) -> Result<(), Login1Error> {
+        self.proxy()
+            .await?
+            .call::<_, _, ()>("SetLockedHint", &(locked,))
+            .await
+            .map_err(|e| Login1Error::Call {
+                method: "SetLockedHint",
+                detail: e.to_string(),
+            })
+    }
+}
@@ -0,0 +1,246 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::sync::{Arc, Mutex};
+
+use zbus::{connection, zvariant::OwnedObjectPath, Connection, Guid};
+
+use crate::services::login1::{self, Login1Error, SleepKind, LOGIN1_PATH};
+
+/// Minimal stand-in for org.freedesktop.login1.Manager.
+struct MockManager {
+    can_suspend: String,
+    can_hibernate: String,
+    deny_suspend: bool,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+#[zbus::interface(name = "org.freedesktop.login1.Manager")]
+impl MockManager {
+    fn can_suspend(&self) -> String {
+        self.can_suspend.clone()
+    }
+
+    fn can_hibernate(&self) -> String {
+        self.can_hibernate.clone()
+    }
+
+    fn hibernate(&self, interactive: bool) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("Hibernate(interactive={interactive})"));
+    }
+
+    fn inhibit(
+        &self,
+        what: &str,
+        _who: &str,
+        _why: &str,
+        mode: &str,
+    ) -> zbus::fdo::Result<zbus::zvariant::OwnedFd> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("Inhibit({what}, {mode})"));
+        let file = std::fs::File::open("/dev/null")
+            .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+        Ok(std::os::fd::OwnedFd::from(file).into())
+    }
+
+    fn list_inhibitors(&self) -> Vec<(String, String, String, String, u32, u32)> {
+        vec![(
+            "idle:sleep".to_string(),
+            "mpv".to_string(),
+            "Playing video".to_string(),
+            "block".to_string(),
+            1000,
+            4242,
+        )]
+    }
+
+    fn suspend(&self, interactive: bool) -> zbus::fdo::Result<()> {
+        if self.deny_suspend {
+            return Err(zbus::fdo::Error::AccessDenied(
+                "Access denied by polkit".to_string(),
+            ));
+        }
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("Suspend(interactive={interactive})"));
+        Ok(())
+    }
+}
+
+/// Minimal stand-in for org.freedesktop.login1.Session.
+struct MockSession {
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+#[zbus::interface(name = "org.freedesktop.login1.Session")]
+impl MockSession {
+    fn lock(&self) {
+        self.calls.lock().unwrap().push("Lock".to_string());
+    }
+
+    fn set_locked_hint(&self, locked: bool) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("SetLockedHint({locked})"));
+    }
+}
+
+const SESSION_PATH: &str = "/org/freedesktop/login1/session/_31";
+
+/// Serve `mock` on one end of a private peer-to-peer bus and return both ends.
+/// The server connection must be kept alive for the duration of the test.
+async fn private_login1(mock: MockManager) -> (Connection, Connection) {
+    let (a, b) = tokio::net::UnixStream::pair().unwrap();
+    let session = MockSession {
+        calls: mock.calls.clone(),
+    };
+
+    let server = connection::Builder::unix_stream(a)
+        .server(Guid::generate())
+        .unwrap()
+        .p2p()
+        .serve_at(LOGIN1_PATH, mock)
+        .unwrap()
+        .serve_at(SESSION_PATH, session)
+        .unwrap()
+        .build();
+    let client = connection::Builder::unix_stream(b).p2p().build();
+
+    futures::try_join!(server, client).unwrap()
+}
+
+fn mock(can_suspend: &str, deny_suspend: bool) -> (MockManager, Arc<Mutex<Vec<String>>>) {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let m = MockManager {
+        can_suspend: can_suspend.to_string(),
+        can_hibernate: "yes".to_string(),
+        deny_suspend,
+        calls: calls.clone(),
+    };
+    (m, calls)
+}
+
+#[tokio::test]
+async fn suspend_calls_login1_when_allowed() {
+    let (m, calls) = mock("yes", false);
+    let (_server, client) = private_login1(m).await;
+
+    login1::suspend(&client).await.unwrap();
+
+    assert_eq!(*calls.lock().unwrap(), vec!["Suspend(interactive=false)"]);
+}
+
+#[tokio::test]
+async fn suspend_is_interactive_on_challenge() {
+    let (m, calls) = mock("challenge", false);
+    let (_server, client) = private_login1(m).await;
+
+    login1::suspend(&client).await.unwrap();
+
+    assert_eq!(*calls.lock().unwrap(), vec!["Suspend(interactive=true)"]);
+}
+
+#[tokio::test]
+async fn suspend_reports_unsupported() {
+    let (m, calls) = mock("na", false);
+    let (_server, client) = private_login1(m).await;
+
+    let err = login1::suspend(&client).await.unwrap_err();
+
+    assert!(matches!(
+        err,
+        Login1Error::Unavailable { query: "CanSuspend", ref answer } if answer == "na"
+    ));
+    assert!(calls.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn suspend_reports_polkit_denial() {
+    let (m, _calls) = mock("yes", true);
+    let (_server, client) = private_login1(m).await;
+
+    let err = login1::suspend(&client).await.unwrap_err();
+
+    assert!(matches!(err, Login1Error::Call { method: "Suspend", .. }));
+    assert!(err.to_string().contains("Access denied"));
+}
+
+#[tokio::test]
+async fn hibernate_uses_matching_login1_method() {
+    let (m, calls) = mock("yes", false);
+    let (_server, client) = private_login1(m).await;
+
+    let used = login1::sleep(&client, SleepKind::Hibernate).await.unwrap();
+
+    assert_eq!(used, SleepKind::Hibernate);
+    assert_eq!(*calls.lock().unwrap(), vec!["Hibernate(interactive=false)"]);
+}
+
+#[tokio::test]
+async fn hibernate_falls_back_to_suspend_when_unavailable() {
+    let (mut m, calls) = mock("yes", false);
+    m.can_hibernate = "na".to_string();
+    let (_server, client) = private_login1(m).await;
+
+    let used = login1::sleep(&client, SleepKind::Hibernate).await.unwrap();
+
+    assert_eq!(used, SleepKind::Suspend);
+    assert_eq!(*calls.lock().unwrap(), vec!["Suspend(interactive=false)"]);
+}
+
+#[tokio::test]
+async fn session_lock_and_locked_hint_hit_session_object() {
+    let (m, calls) = mock("yes", false);
+    let (_server, client) = private_login1(m).await;
+
+    let session = login1::Session::new(
+        client,
+        OwnedObjectPath::try_from(SESSION_PATH).unwrap(),
+    );
+    session.lock().await.unwrap();
+    session.set_locked_hint(true).await.unwrap();
+    session.set_locked_hint(false).await.unwrap();
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["Lock", "SetLockedHint(true)", "SetLockedHint(false)"]
+    );
+}
+
+#[tokio::test]
+async fn sleep_delay_takes_delay_lock_once_and_releases() {
+    let (m, calls) = mock("yes", false);
+    let (_server, client) = private_login1(m).await;
+
+    let delay = login1::InhibitLock::sleep_delay();
+    delay.acquire(&client).await.unwrap();
+    delay.acquire(&client).await.unwrap();
+
+    assert!(delay.is_held());
+    assert_eq!(*calls.lock().unwrap(), vec!["Inhibit(sleep, delay)"]);
+
+    assert!(delay.release());
+    assert!(!delay.is_held());
+    assert!(!delay.release());
+}
+
+#[tokio::test]
+async fn list_inhibitors_decodes_entries() {
+    let (m, _calls) = mock("yes", false);
+    let (_server, client) = private_login1(m).await;
+
+    let list = login1::list_inhibitors(&client).await.unwrap();
+
+    assert_eq!(list.len(), 1);
+    assert!(list[0].inhibits("idle"));
+    assert!(list[0].inhibits("sleep"));
+    assert!(!list[0].inhibits("shutdown"));
+    assert_eq!((list[0].who.as_str(), list[0].pid), ("mpv", 4242));
+}
@@ -0,0 +1,156 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::time::Duration;
+
+use futures::StreamExt;
+use tokio::sync::{mpsc, watch};
+use zbus::{fdo::PropertiesProxy, Connection};
+
+use crate::core::events::Event;
+use crate::core::manager_msg::ManagerMsg;
+use crate::services::login1::{self, Inhibitor, LOGIN1_DEST, LOGIN1_PATH};
+
+/// Poll interval without PropertiesChanged.
+const POLL: Duration = Duration::from_secs(2);
+/// Safety-net poll next to PropertiesChanged, for logind versions that don't
+/// signal every inhibitor change.
+const FALLBACK_POLL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct LogindInhibitRules {
+    pub epoch: u64, // forces watch::changed() on profile/reload even if values are identical
+    /// Also honor "delay" mode idle locks (default: "block" only).
+    pub honor_delay: bool,
+}
+
+/// Number of logind "idle" inhibitor locks Stasis should honor.
+pub fn count_idle_inhibitors(inhibitors: &[Inhibitor], honor_delay: bool) -> u64 {
+    inhibitors
+        .iter()
+        .filter(|i| i.inhibits("idle"))
+        .filter(|i| i.mode == "block" || (honor_delay && i.mode == "delay"))
+        .count() as u64
+}
+
+/// Spawnable task: tracks logind "idle" inhibitors (e.g. `systemd-inhibit --what=idle`).
+///
+/// Refreshes on BlockInhibited/DelayInhibited PropertiesChanged when logind emits
+/// them, with a slow fallback poll (not every logind version signals changes), and
+/// on a short poll when the signal can't be subscribed.
+pub async fn run_logind_inhibit(
+    tx: mpsc::Sender<ManagerMsg>,
+    mut rules_rx: watch::Receiver<LogindInhibitRules>,
+) {
+    let conn = match login1::system_connection().await {
+        Ok(c) => c,
+        Err(e) => {
+            eventline::warn!("logind-inhibit: {e}");
+            return;
+        }
+    };
+
+    let mut changes = match properties_changed(&conn).await {
+        Ok(s) => Some(s),
+        Err(e) => {
+            eventline::warn!("logind-inhibit: PropertiesChanged unavailable ({e}); polling only");
+            None
+        }
+    };
+
+    let poll = if changes.is_some() { FALLBACK_POLL } else { POLL };
+
+    let mut honor_delay = rules_rx.borrow().honor_delay;
+    let mut last: Option<u64> = None;
+
+    eventline::info!("logind-inhibit: started (honor_delay={})", honor_delay);
+
+    if !refresh(&conn, honor_delay, &mut last, &tx).await {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            changed = rules_rx.changed() => {
+                if changed.is_err() {
+                    return; // sender dropped => shutting down
+                }
+
+                let rules = rules_rx.borrow().clone();
+                eventline::debug!("logind-inhibit: rules epoch={} honor_delay={}", rules.epoch, rules.honor_delay);
+                honor_delay = rules.honor_delay;
+
+                // Re-seed the manager after profile/reload.
+                last = None;
+            }
+
+            Some(sig) = async {
+                match changes.as_mut() {
+                    Some(s) => s.next().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                let Ok(args) = sig.args() else { continue };
+                let relevant = args.changed_properties().contains_key("BlockInhibited")
+                    || args.changed_properties().contains_key("DelayInhibited");
+                if !relevant {
+                    continue;
+                }
+            }
+
+            _ = tokio::time::sleep(poll) => {}
+        }
+
+        if !refresh(&conn, honor_delay, &mut last, &tx).await {
+            return;
+        }
+    }
+}
+
+async fn properties_changed(
+    conn: &Connection,
+) -> zbus::Result<zbus::fdo::PropertiesChangedStream> {
+    let proxy = PropertiesProxy::builder(conn)
+        .destination(LOGIN1_DEST)?
+        .path(LOGIN1_PATH)?
+        .build()
+        .await?;
+    proxy.receive_properties_changed().await
+}
+
+/// Emit the honored count if it changed (or `last` was reset). Returns false once
+/// the manager is gone.
+async fn refresh(
+    conn: &Connection,
+    honor_delay: bool,
+    last: &mut Option<u64>,
+    tx: &mpsc::Sender<ManagerMsg>,
+) -> bool {
+    let inhibitors = match login1::list_inhibitors(conn).await {
+        Ok(v) => v,
+        Err(e) => {
+            eventline::debug!("logind-inhibit: {e}");
+            return true;
+        }
+    };
+
+    let count = count_idle_inhibitors(&inhibitors, honor_delay);
+    if *last == Some(count) {
+        return true;
+    }
+
+    if count != last.unwrap_or(0) {
+        eventline::info!("logind-inhibit: count {} -> {}", last.unwrap_or(0), count);
+        for i in inhibitors.iter().filter(|i| i.inhibits("idle")) {
+            eventline::debug!("logind-inhibit: {} ({}, {}): {}", i.who, i.mode, i.pid, i.why);
+        }
+    }
+    *last = Some(count);
+
+    tx.send(ManagerMsg::Event(Event::LogindInhibitorCount {
+        count,
+        now_ms: crate::core::utils::now_ms(),
+    }))
+    .await
+    .is_ok()
+}
@@ -0,0 +1,36 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use crate::services::login1::Inhibitor;
+use crate::services::logind_inhibit::count_idle_inhibitors;
+
+fn inhibitor(what: &str, mode: &str) -> Inhibitor {
+    Inhibitor {
+        what: what.to_string(),
+        who: "test".to_string(),
+        why: "because".to_string(),
+        mode: mode.to_string(),
+        uid: 1000,
+        pid: 42,
+    }
+}
+
+#[test]
+fn only_idle_locks_are_counted() {
+    let list = vec![
+        inhibitor("sleep", "block"),
+        inhibitor("handle-lid-switch", "block"),
+        inhibitor("idle:sleep", "block"),
+        inhibitor("idle", "block"),
+    ];
+
+    assert_eq!(count_idle_inhibitors(&list, false), 2);
+}
+
+#[test]
+fn delay_mode_is_opt_in() {
+    let list = vec![inhibitor("idle", "block"), inhibitor("idle", "delay")];
+
+    assert_eq!(count_idle_inhibitors(&list, false), 1);
+    assert_eq!(count_idle_inhibitors(&list, true), 2);
+}
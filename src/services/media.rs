@@ -3,19 +3,22 @@
 
 use std::collections::HashSet;
 use std::process::Command;
+use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::sync::{mpsc, watch};
+use tokio::sync::{mpsc, watch, Notify};
 
-use crate::core::config::Pattern;
+use crate::core::config::{MediaBackend, Pattern};
 use crate::core::events::{Event, MediaState};
 use crate::core::manager_msg::ManagerMsg;
+use crate::services::mpris::{self, MprisPlayer, MprisState};
 
 #[derive(Debug, Clone)]
 pub struct MediaRules {
     pub epoch: u64, // forces watch::changed() on profile/reload even if values are identical
     pub monitor_media: bool,
     pub ignore_remote_media: bool,
+    pub media_backend: MediaBackend,
     pub media_blacklist: Vec<Pattern>,
 }
 
@@ -30,16 +33,31 @@ pub async fn run_media(tx: mpsc::Sender<ManagerMsg>, mut rules_rx: watch::Receiv
 
     let mut ignore_first_epoch_bump = true;
 
+    let mpris_state = MprisState::default();
+    let mpris_changed = Arc::new(Notify::new());
+    let mut mpris_started = false;
+
     let mut svc = MediaService::new(initial.ignore_remote_media, initial.media_blacklist.clone())
+        .with_backend(initial.media_backend, mpris_state.clone())
         .with_poll_interval_ms(1000);
 
     eventline::info!(
-        "media: started (monitor_media={}, ignore_remote_media={}, blacklist_len={})",
+        "media: started (monitor_media={}, ignore_remote_media={}, backend={}, blacklist_len={})",
         initial.monitor_media,
         initial.ignore_remote_media,
+        initial.media_backend.as_str(),
         svc.blacklist_len(),
     );
 
+    // The MPRIS watcher only runs once something actually asks for it.
+    let mut ensure_mpris = |monitor_media: bool, backend: MediaBackend| {
+        if monitor_media && backend.uses_mpris() && !mpris_started {
+            mpris_started = true;
+            tokio::spawn(mpris::run_mpris(mpris_state.clone(), mpris_changed.clone()));
+        }
+    };
+    ensure_mpris(initial.monitor_media, initial.media_backend);
+
     // Seed core immediately on startup.
     if initial.monitor_media {
         svc.force_emit_next(); // baseline refresh log if non-zero
@@ -80,7 +98,7 @@ pub async fn run_media(tx: mpsc::Sender<ManagerMsg>, mut rules_rx: watch::Receiv
 
                 // IMPORTANT: clone rules so we don't hold a watch::Ref across await
                 let rules = rules_rx.borrow().clone();
-                let MediaRules { epoch, monitor_media, ignore_remote_media, media_blacklist } = rules;
+                let MediaRules { epoch, monitor_media, ignore_remote_media, media_backend, media_blacklist } = rules;
 
                 // Detect epoch bump
                 let epoch_bumped = epoch != last_epoch;
@@ -89,7 +107,8 @@ pub async fn run_media(tx: mpsc::Sender<ManagerMsg>, mut rules_rx: watch::Receiv
                 }
 
                 // Apply semantics changes; if they changed, svc will force refresh.
-                svc.reconfigure(ignore_remote_media, media_blacklist.clone());
+                svc.reconfigure(ignore_remote_media, media_backend, media_blacklist.clone());
+                ensure_mpris(monitor_media, media_backend);
 
                 // monitor_media toggle handling
                 if monitor_media != last_enabled {
@@ -137,6 +156,23 @@ pub async fn run_media(tx: mpsc::Sender<ManagerMsg>, mut rules_rx: watch::Receiv
                 }
             }
 
+            // --- MPRIS player started/stopped/appeared/vanished ---
+            _ = mpris_changed.notified() => {
+                if !rules_rx.borrow().monitor_media {
+                    continue;
+                }
+
+                svc.poll_now();
+                let now_ms = crate::core::utils::now_ms();
+                if let Some(evs) = svc.poll(now_ms) {
+                    for ev in evs {
+                        if tx.send(ManagerMsg::Event(ev)).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+
             // --- periodic tick ---
             _ = tokio::time::sleep(Duration::from_millis(sleep_ms)) => {
                 let rules = rules_rx.borrow().clone();
@@ -164,6 +200,9 @@ pub struct MediaService {
     ignore_remote_media: bool,
     media_blacklist: Vec<Pattern>,
 
+    backend: MediaBackend,
+    mpris: MprisState,
+
     poll_interval_ms: u64,
     last_poll_ms: u64,
 
@@ -180,6 +219,9 @@ impl MediaService {
             ignore_remote_media,
             media_blacklist,
 
+            backend: MediaBackend::Pactl,
+            mpris: MprisState::default(),
+
             poll_interval_ms: 1000,
             last_poll_ms: 0,
 
@@ -190,6 +232,13 @@ impl MediaService {
         }
     }
 
+    /// Select the backend; `mpris` is the player table a `run_mpris` task keeps current.
+    pub fn with_backend(mut self, backend: MediaBackend, mpris: MprisState) -> Self {
+        self.backend = backend;
+        self.mpris = mpris;
+        self
+    }
+
     pub fn with_poll_interval_ms(mut self, ms: u64) -> Self {
         self.poll_interval_ms = ms.max(100);
         self
@@ -201,18 +250,26 @@ impl MediaService {
 
     /// Apply updated semantics (profile switch / reload config).
     /// If semantics changed, force a baseline refresh emission on next poll.
-    pub fn reconfigure(&mut self, ignore_remote_media: bool, media_blacklist: Vec<Pattern>) {
+    pub fn reconfigure(
+        &mut self,
+        ignore_remote_media: bool,
+        backend: MediaBackend,
+        media_blacklist: Vec<Pattern>,
+    ) {
         let changed = self.ignore_remote_media != ignore_remote_media
+            || self.backend != backend
             || !patterns_same(&self.media_blacklist, &media_blacklist);
 
         self.ignore_remote_media = ignore_remote_media;
+        self.backend = backend;
         self.media_blacklist = media_blacklist;
 
         if changed {
             self.force_emit_next();
             eventline::info!(
-                "media: reconfigured (ignore_remote_media={}, blacklist_len={})",
+                "media: reconfigured (ignore_remote_media={}, backend={}, blacklist_len={})",
                 self.ignore_remote_media,
+                self.backend.as_str(),
                 self.media_blacklist.len()
             );
         }
@@ -225,6 +282,11 @@ impl MediaService {
         self.last_poll_ms = 0;
     }
 
+    /// Allow the next poll() to run immediately (without forcing an emission).
+    pub fn poll_now(&mut self) {
+        self.last_poll_ms = 0;
+    }
+
     /// Poll once. Returns events on:
     /// - first poll
     /// - real change
@@ -235,11 +297,17 @@ impl MediaService {
         }
         self.last_poll_ms = now_ms;
 
-        let snapshot = match read_pactl_snapshot(&self.media_blacklist) {
-            Ok(s) => s,
-            Err(_e) => MediaSnapshot::default(),
+        let mut snapshot = if self.backend.uses_pactl() {
+            read_pactl_snapshot(&self.media_blacklist).unwrap_or_default()
+        } else {
+            MediaSnapshot::default()
         };
 
+        if self.backend.uses_mpris() {
+            let players = self.mpris.lock().unwrap().playing();
+            snapshot.merge(mpris_snapshot(&players, &self.media_blacklist));
+        }
+
         let local = snapshot.local_keys.len() as u64;
         let remote = snapshot.remote_keys.len() as u64;
 
@@ -313,6 +381,43 @@ struct MediaSnapshot {
     remote_keys: HashSet<String>,
 }
 
+impl MediaSnapshot {
+    /// Union with another backend's view. A pid seen locally by either side wins
+    /// over the other calling it remote.
+    fn merge(&mut self, other: MediaSnapshot) {
+        self.local_keys.extend(other.local_keys);
+        self.remote_keys.extend(other.remote_keys);
+        self.remote_keys.retain(|k| !self.local_keys.contains(k));
+    }
+}
+
+fn mpris_snapshot(players: &[MprisPlayer], media_blacklist: &[Pattern]) -> MediaSnapshot {
+    let mut snapshot = MediaSnapshot::default();
+
+    for p in players.iter().filter(|p| p.playing) {
+        // e.g. "firefox.instance_1_42" out of the well-known name
+        let short = p.bus_name.strip_prefix(mpris::MPRIS_PREFIX).unwrap_or(&p.bus_name);
+
+        if is_blacklisted(media_blacklist, &p.identity, short, &p.desktop_entry, "") {
+            continue;
+        }
+
+        // Same key shape as pactl so `media_backend "both"` counts a player once.
+        let key = match p.pid {
+            Some(pid) => format!("pid:{pid}"),
+            None => format!("mpris:{}", short.to_lowercase()),
+        };
+
+        if is_remote_stream(&p.identity, short, &p.desktop_entry, "", "") {
+            snapshot.remote_keys.insert(key);
+        } else {
+            snapshot.local_keys.insert(key);
+        }
+    }
+
+    snapshot
+}
+
 fn read_pactl_snapshot(media_blacklist: &[Pattern]) -> Result<MediaSnapshot, String> {
     let out = Command::new("sh")
         .arg("-lc")
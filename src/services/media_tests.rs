@@ -0,0 +1,113 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use crate::core::config::{MediaBackend, Pattern};
+use crate::core::events::{Event, MediaState};
+use crate::services::media::MediaService;
+use crate::services::mpris::{MprisPlayer, MprisState};
+
+fn player(bus_name: &str, identity: &str, pid: Option<u32>, playing: bool) -> MprisPlayer {
+    MprisPlayer {
+        bus_name: format!("org.mpris.MediaPlayer2.{bus_name}"),
+        identity: identity.to_string(),
+        desktop_entry: String::new(),
+        pid,
+        playing,
+    }
+}
+
+fn count_and_state(evs: &[Event]) -> (u64, MediaState) {
+    let mut count = None;
+    let mut state = None;
+    for ev in evs {
+        match ev {
+            Event::MediaInhibitorCount { count: c, .. } => count = Some(*c),
+            Event::MediaStateChanged { state: s, .. } => state = Some(*s),
+            _ => {}
+        }
+    }
+    (count.unwrap(), state.unwrap())
+}
+
+fn mpris_service(ignore_remote: bool, blacklist: Vec<Pattern>) -> (MediaService, MprisState) {
+    let state = MprisState::default();
+    let svc = MediaService::new(ignore_remote, blacklist)
+        .with_backend(MediaBackend::Mpris, state.clone());
+    (svc, state)
+}
+
+#[test]
+fn mpris_players_count_once_per_process() {
+    let (mut svc, state) = mpris_service(false, Vec::new());
+    {
+        let mut s = state.lock().unwrap();
+        // Two browser tabs from the same process, plus a paused player.
+        s.upsert(":1.10", player("firefox.instance_1_1", "Mozilla Firefox", Some(100), true));
+        s.upsert(":1.11", player("firefox.instance_1_2", "Mozilla Firefox", Some(100), true));
+        s.upsert(":1.12", player("mpv", "mpv", Some(200), false));
+    }
+
+    let evs = svc.poll(10_000).unwrap();
+    assert_eq!(count_and_state(&evs), (1, MediaState::PlayingLocal));
+
+    assert!(state.lock().unwrap().set_playing(":1.12", true));
+    svc.poll_now();
+    let evs = svc.poll(10_100).unwrap();
+    assert_eq!(count_and_state(&evs), (2, MediaState::PlayingLocal));
+
+    assert!(state.lock().unwrap().remove_bus_name("org.mpris.MediaPlayer2.mpv"));
+    svc.poll_now();
+    let evs = svc.poll(10_200).unwrap();
+    assert_eq!(count_and_state(&evs), (1, MediaState::PlayingLocal));
+}
+
+#[test]
+fn mpris_tracks_every_name_of_one_owner_but_not_playerctld() {
+    let (mut svc, state) = mpris_service(false, vec![]);
+    {
+        let mut s = state.lock().unwrap();
+        // KDE Connect exports one player per paired device.
+        s.upsert(":1.30", player("kdeconnect.mpris_phone", "Phone", Some(400), true));
+        s.upsert(":1.30", player("kdeconnect.mpris_tablet", "Tablet", Some(400), false));
+        s.upsert(":1.31", player("mpv", "mpv", Some(500), true));
+        s.upsert(":1.32", player("playerctld", "mpv", Some(600), true));
+    }
+    assert_eq!(
+        state.lock().unwrap().names_owned_by(":1.30"),
+        vec![
+            "org.mpris.MediaPlayer2.kdeconnect.mpris_phone".to_string(),
+            "org.mpris.MediaPlayer2.kdeconnect.mpris_tablet".to_string(),
+        ]
+    );
+    assert!(state.lock().unwrap().names_owned_by(":1.32").is_empty());
+
+    let evs = svc.poll(10_000).unwrap();
+    assert_eq!(count_and_state(&evs), (2, MediaState::PlayingLocal));
+
+    {
+        let mut s = state.lock().unwrap();
+        assert!(s.remove_bus_name("org.mpris.MediaPlayer2.kdeconnect.mpris_phone"));
+        assert_eq!(s.names_owned_by(":1.30").len(), 1);
+    }
+    svc.poll_now();
+    let evs = svc.poll(10_100).unwrap();
+    assert_eq!(count_and_state(&evs), (1, MediaState::PlayingLocal));
+}
+
+#[test]
+fn mpris_identity_feeds_blacklist_and_remote_detection() {
+    let (mut svc, state) = mpris_service(true, vec![Pattern::Literal("mpv".into())]);
+    {
+        let mut s = state.lock().unwrap();
+        s.upsert(":1.20", player("mpv", "mpv", Some(300), true));
+        s.upsert(":1.21", player("spotify", "Spotify", None, true));
+    }
+
+    // mpv is blacklisted; Spotify is remote and ignored for the count.
+    let evs = svc.poll(10_000).unwrap();
+    assert_eq!(count_and_state(&evs), (0, MediaState::PlayingRemote));
+
+    svc.reconfigure(false, MediaBackend::Mpris, vec![Pattern::Literal("mpv".into())]);
+    let evs = svc.poll(10_100).unwrap();
+    assert_eq!(count_and_state(&evs), (1, MediaState::PlayingRemote));
+}
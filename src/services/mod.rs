@@ -2,8 +2,29 @@
 // License: MIT
 
 pub mod app_inhibit;
+pub mod battery;
+pub mod capture;
 pub mod dbus;
+pub mod login1;
+pub mod logind_inhibit;
 pub mod media;
+pub mod mpris;
 pub mod power;
+pub mod screensaver;
 pub mod ticker;
 pub mod wayland;
+
+#[cfg(test)]
+mod battery_tests;
+#[cfg(test)]
+mod capture_tests;
+#[cfg(test)]
+mod login1_tests;
+#[cfg(test)]
+mod logind_inhibit_tests;
+#[cfg(test)]
+mod media_tests;
+#[cfg(test)]
+mod power_tests;
+#[cfg(test)]
+mod screensaver_tests;
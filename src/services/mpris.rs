@@ -0,0 +1,281 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use tokio::sync::Notify;
+use zbus::{
+    fdo::DBusProxy, names::BusName, proxy::CacheProperties, zvariant::Value, Connection,
+    MatchRule, Proxy,
+};
+
+pub const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+/// playerctld re-exports whichever player is active under its own name.
+const PLAYERCTLD: &str = "org.mpris.MediaPlayer2.playerctld";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const MPRIS_ROOT_IFACE: &str = "org.mpris.MediaPlayer2";
+const MPRIS_PLAYER_IFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MprisPlayer {
+    /// Well-known name, e.g. "org.mpris.MediaPlayer2.firefox.instance_1_42".
+    pub bus_name: String,
+    /// `Identity` ("Mozilla Firefox", "mpv", ...); what media_blacklist matches against.
+    pub identity: String,
+    pub desktop_entry: String,
+    /// Owning process, so a player also seen through pactl is counted once.
+    pub pid: Option<u32>,
+    pub playing: bool,
+}
+
+/// Well-known names worth tracking: MPRIS players, minus playerctld's proxy
+/// of another one (it would count the same playback twice).
+pub fn is_player_name(name: &str) -> bool {
+    name.starts_with(MPRIS_PREFIX) && name != PLAYERCTLD
+}
+
+/// MPRIS players currently on the bus, keyed by well-known name. One
+/// connection may own several (KDE Connect, ...), and PropertiesChanged
+/// arrives from its unique name, hence the owner index.
+#[derive(Debug, Default)]
+pub struct MprisPlayers {
+    players: HashMap<String, MprisPlayer>,
+    owners: HashMap<String, BTreeSet<String>>,
+}
+
+pub type MprisState = Arc<Mutex<MprisPlayers>>;
+
+impl MprisPlayers {
+    pub fn upsert(&mut self, owner: &str, player: MprisPlayer) {
+        if !is_player_name(&player.bus_name) {
+            return;
+        }
+        self.remove_bus_name(&player.bus_name);
+        self.owners
+            .entry(owner.to_string())
+            .or_default()
+            .insert(player.bus_name.clone());
+        self.players.insert(player.bus_name.clone(), player);
+    }
+
+    /// Well-known names `owner` holds.
+    pub fn names_owned_by(&self, owner: &str) -> Vec<String> {
+        self.owners
+            .get(owner)
+            .map(|names| names.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Set every player `owner` holds. Returns whether any actually changed.
+    pub fn set_playing(&mut self, owner: &str, playing: bool) -> bool {
+        let mut flipped = false;
+        for name in self.owners.get(owner).into_iter().flatten() {
+            if let Some(p) = self.players.get_mut(name)
+                && p.playing != playing
+            {
+                p.playing = playing;
+                flipped = true;
+            }
+        }
+        flipped
+    }
+
+    pub fn remove_bus_name(&mut self, bus_name: &str) -> bool {
+        self.owners.retain(|_, names| {
+            names.remove(bus_name);
+            !names.is_empty()
+        });
+        self.players.remove(bus_name).is_some()
+    }
+
+    pub fn playing(&self) -> Vec<MprisPlayer> {
+        self.players.values().filter(|p| p.playing).cloned().collect()
+    }
+}
+
+fn is_playing(status: &str) -> bool {
+    status.eq_ignore_ascii_case("Playing")
+}
+
+/// Spawnable task: follows org.mpris.MediaPlayer2.* players on the session bus
+/// and pokes `changed` whenever one starts/stops playing or comes and goes.
+pub async fn run_mpris(state: MprisState, changed: Arc<Notify>) {
+    let conn = match Connection::session().await {
+        Ok(c) => c,
+        Err(e) => {
+            eventline::warn!("mpris: session bus unavailable: {e}");
+            return;
+        }
+    };
+
+    let dbus = match DBusProxy::new(&conn).await {
+        Ok(p) => p,
+        Err(e) => {
+            eventline::warn!("mpris: org.freedesktop.DBus proxy unavailable: {e}");
+            return;
+        }
+    };
+
+    let mut owner_changes = match dbus.receive_name_owner_changed().await {
+        Ok(s) => s,
+        Err(e) => {
+            eventline::warn!("mpris: NameOwnerChanged unavailable: {e}");
+            return;
+        }
+    };
+
+    let mut props = match properties_changed(&conn).await {
+        Ok(s) => s,
+        Err(e) => {
+            eventline::warn!("mpris: PropertiesChanged unavailable: {e}");
+            return;
+        }
+    };
+
+    // Subscribe first, then list, so a player appearing in between isn't missed.
+    match dbus.list_names().await {
+        Ok(names) => {
+            for name in names.iter().filter(|n| is_player_name(n)) {
+                track(&conn, &dbus, &state, name.as_str()).await;
+            }
+        }
+        Err(e) => eventline::warn!("mpris: ListNames failed: {e}"),
+    }
+
+    eventline::info!(
+        "mpris: started ({} player(s))",
+        state.lock().unwrap().players.len()
+    );
+    changed.notify_one();
+
+    loop {
+        tokio::select! {
+            Some(sig) = owner_changes.next() => {
+                let Ok(args) = sig.args() else { continue };
+                let name = args.name().to_string();
+                if !is_player_name(&name) {
+                    continue;
+                }
+
+                let removed = state.lock().unwrap().remove_bus_name(&name);
+                if removed {
+                    eventline::debug!("mpris: {} went away", name);
+                }
+                if args.new_owner().is_some() {
+                    track(&conn, &dbus, &state, &name).await;
+                }
+                changed.notify_one();
+            }
+
+            Some(msg) = props.next() => {
+                let Ok(msg) = msg else { continue };
+                let hdr = msg.header();
+                let Some(sender) = hdr.sender().map(|s| s.to_string()) else { continue };
+
+                let body = msg.body();
+                let Ok((iface, props, _invalidated)) =
+                    body.deserialize::<(String, HashMap<String, Value>, Vec<String>)>()
+                else {
+                    continue;
+                };
+                if iface != MPRIS_PLAYER_IFACE {
+                    continue;
+                }
+
+                let Some(status) = props.get("PlaybackStatus") else { continue };
+                let Ok(status) = String::try_from(status.clone()) else { continue };
+
+                // Can't tell which of several players this was; re-read them all.
+                let names = state.lock().unwrap().names_owned_by(&sender);
+                if names.len() > 1 {
+                    for name in &names {
+                        track(&conn, &dbus, &state, name).await;
+                    }
+                    changed.notify_one();
+                    continue;
+                }
+
+                let flipped = state.lock().unwrap().set_playing(&sender, is_playing(&status));
+                if flipped {
+                    eventline::debug!("mpris: {} is now {}", sender, status);
+                    changed.notify_one();
+                }
+            }
+
+            else => return,
+        }
+    }
+}
+
+async fn properties_changed(conn: &Connection) -> zbus::Result<zbus::MessageStream> {
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface("org.freedesktop.DBus.Properties")?
+        .member("PropertiesChanged")?
+        .path(MPRIS_PATH)?
+        .build();
+
+    zbus::MessageStream::for_match_rule(rule, conn, None).await
+}
+
+/// Read a player's identity/status and remember it under its current owner.
+async fn track(conn: &Connection, dbus: &DBusProxy<'_>, state: &MprisState, name: &str) {
+    let Ok(bus) = BusName::try_from(name) else { return };
+
+    let owner = match dbus.get_name_owner(bus.clone()).await {
+        Ok(o) => o.to_string(),
+        Err(e) => {
+            eventline::debug!("mpris: {name} has no owner: {e}");
+            return;
+        }
+    };
+
+    let pid = dbus.get_connection_unix_process_id(bus).await.ok();
+    let identity = get_string(conn, name, MPRIS_ROOT_IFACE, "Identity")
+        .await
+        .unwrap_or_default();
+    let desktop_entry = get_string(conn, name, MPRIS_ROOT_IFACE, "DesktopEntry")
+        .await
+        .unwrap_or_default();
+    let playing = get_string(conn, name, MPRIS_PLAYER_IFACE, "PlaybackStatus")
+        .await
+        .is_some_and(|s| is_playing(&s));
+
+    eventline::debug!(
+        "mpris: tracking {} ({:?}, pid={:?}, playing={})",
+        name,
+        identity,
+        pid,
+        playing
+    );
+
+    state.lock().unwrap().upsert(
+        &owner,
+        MprisPlayer {
+            bus_name: name.to_string(),
+            identity,
+            desktop_entry,
+            pid,
+            playing,
+        },
+    );
+}
+
+async fn get_string(conn: &Connection, dest: &str, iface: &str, prop: &str) -> Option<String> {
+    let proxy: Proxy<'_> = zbus::proxy::Builder::new(conn)
+        .destination(dest.to_string())
+        .ok()?
+        .path(MPRIS_PATH)
+        .ok()?
+        .interface(iface.to_string())
+        .ok()?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await
+        .ok()?;
+
+    proxy.get_property::<String>(prop).await.ok()
+}
+
@@ -1,12 +1,21 @@
 // Author: Dustin Pilgrim
 // License: MIT
 
+use std::collections::HashMap;
+
+use futures::StreamExt;
 use tokio::sync::mpsc::Sender;
 use tokio::time::{sleep, Duration};
+use zbus::{zvariant::OwnedValue, Connection, MatchRule, Proxy};
 
-use crate::core::events::{Event, PowerState};
+use crate::core::events::{BatteryStatus, Event, PowerState};
 use crate::core::manager_msg::ManagerMsg;
 use crate::core::utils;
+use crate::services::battery::{self, DISPLAY_DEVICE_PATH, UPOWER_DEST, UPOWER_DEVICE_IFACE};
+use crate::services::dbus;
+
+pub const UPOWER_PATH: &str = "/org/freedesktop/UPower";
+pub const UPOWER_IFACE: &str = "org.freedesktop.UPower";
 
 pub async fn run_power(tx: Sender<ManagerMsg>) {
     // Desktop? Do nothing forever.
@@ -17,50 +26,173 @@ pub async fn run_power(tx: Sender<ManagerMsg>) {
 
     eventline::info!("power: laptop detected, starting power monitor");
 
-    // Initial state
-    let mut on_ac = utils::is_on_ac_power();
-    let initial = if on_ac {
-        PowerState::OnAC
-    } else {
-        PowerState::OnBattery
-    };
+    match dbus::system_bus().await {
+        Ok(conn) => match watch_upower(&conn, &tx).await {
+            Ok(()) => return,
+            Err(e) => eventline::warn!("power: UPower unavailable ({e}); polling sysfs"),
+        },
+        Err(e) => eventline::warn!("power: no system bus ({e}); polling sysfs"),
+    }
+
+    poll_sysfs(tx).await;
+}
+
+/// Follow UPower's `OnBattery` (plan source) and DisplayDevice (charge) through
+/// PropertiesChanged. Returns `Ok` once the manager is gone; errors when UPower
+/// can't be reached or its signals stop, so the caller can fall back to sysfs.
+pub async fn watch_upower(conn: &Connection, tx: &Sender<ManagerMsg>) -> zbus::Result<()> {
+    // Subscribe before the first read so no change slips in between.
+    let mut changes = properties_changed(conn).await?;
+
+    let mut power = None;
+    if !update_power(tx, &mut power, power_state(on_battery(conn).await?)).await {
+        return Ok(());
+    }
+    eventline::info!("power: following UPower OnBattery");
+
+    let mut charge = None;
+    if !update_battery(tx, &mut charge, display_battery(conn).await).await {
+        return Ok(());
+    }
+
+    while let Some(msg) = changes.next().await {
+        let Ok(msg) = msg else { continue };
 
-    let _ = tx
-        .send(ManagerMsg::Event(Event::PowerChanged {
-            state: initial,
-            now_ms: utils::now_ms(),
-        }))
-        .await;
+        let header = msg.header();
+        let Some(path) = header.path() else { continue };
+        let Ok((iface, changed, _invalidated)) = msg
+            .body()
+            .deserialize::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+        else {
+            continue;
+        };
+
+        let alive = match (iface.as_str(), path.as_str()) {
+            (UPOWER_IFACE, UPOWER_PATH) => {
+                let Some(on_battery) = changed
+                    .get("OnBattery")
+                    .and_then(|v| v.downcast_ref::<bool>().ok())
+                else {
+                    continue;
+                };
+                update_power(tx, &mut power, power_state(on_battery)).await
+            }
+            (UPOWER_DEVICE_IFACE, DISPLAY_DEVICE_PATH) => {
+                update_battery(tx, &mut charge, display_battery(conn).await).await
+            }
+            _ => continue,
+        };
+
+        if !alive {
+            return Ok(());
+        }
+    }
+
+    Err(zbus::Error::Failure("UPower signal stream closed".into()))
+}
+
+/// Fallback without UPower: poll sysfs for AC and battery charge.
+async fn poll_sysfs(tx: Sender<ManagerMsg>) {
+    let mut power = None;
+    let mut charge = None;
 
-    // Poll loop
     loop {
+        let state = power_state(!utils::is_on_ac_power());
+        if !update_power(&tx, &mut power, state).await {
+            break;
+        }
+        if !update_battery(&tx, &mut charge, battery::read(None).await).await {
+            break;
+        }
+
         sleep(Duration::from_secs(5)).await;
+    }
+}
 
-        let now_on_ac = utils::is_on_ac_power();
-        if now_on_ac != on_ac {
-            on_ac = now_on_ac;
-
-            let state = if on_ac {
-                PowerState::OnAC
-            } else {
-                PowerState::OnBattery
-            };
-
-            eventline::info!(
-                "power: source changed -> {}",
-                if on_ac { "AC" } else { "Battery" }
-            );
-
-            if tx
-                .send(ManagerMsg::Event(Event::PowerChanged {
-                    state,
-                    now_ms: utils::now_ms(),
-                }))
-                .await
-                .is_err()
-            {
-                break;
-            }
+async fn properties_changed(conn: &Connection) -> zbus::Result<zbus::MessageStream> {
+    let rule = MatchRule::builder()
+        .msg_type(zbus::message::Type::Signal)
+        .interface("org.freedesktop.DBus.Properties")?
+        .member("PropertiesChanged")?
+        .path_namespace(UPOWER_PATH)?
+        .build();
+
+    zbus::MessageStream::for_match_rule(rule, conn, None).await
+}
+
+async fn on_battery(conn: &Connection) -> zbus::Result<bool> {
+    let proxy = Proxy::new(
+        conn,
+        UPOWER_DEST,
+        UPOWER_PATH,
+        "org.freedesktop.DBus.Properties",
+    )
+    .await?;
+    let value: OwnedValue = proxy.call("Get", &(UPOWER_IFACE, "OnBattery")).await?;
+    Ok(value.downcast_ref::<bool>()?)
+}
+
+async fn display_battery(conn: &Connection) -> Option<BatteryStatus> {
+    match battery::display_device(conn).await {
+        Ok(status) => status,
+        Err(e) => {
+            eventline::debug!("power: DisplayDevice read failed: {e}");
+            None
         }
     }
 }
+
+fn power_state(on_battery: bool) -> PowerState {
+    if on_battery {
+        PowerState::OnBattery
+    } else {
+        PowerState::OnAC
+    }
+}
+
+/// Send PowerChanged if `state` differs from the last one sent (it restarts
+/// the idle cycle). Returns false once the manager is gone.
+async fn update_power(
+    tx: &Sender<ManagerMsg>,
+    last: &mut Option<PowerState>,
+    state: PowerState,
+) -> bool {
+    if *last == Some(state) {
+        return true;
+    }
+    if last.is_some() {
+        eventline::info!(
+            "power: source changed -> {}",
+            match state {
+                PowerState::OnAC => "AC",
+                PowerState::OnBattery => "Battery",
+            }
+        );
+    }
+    *last = Some(state);
+
+    tx.send(ManagerMsg::Event(Event::PowerChanged {
+        state,
+        now_ms: utils::now_ms(),
+    }))
+    .await
+    .is_ok()
+}
+
+async fn update_battery(
+    tx: &Sender<ManagerMsg>,
+    last: &mut Option<BatteryStatus>,
+    battery: Option<BatteryStatus>,
+) -> bool {
+    let Some(battery) = battery.filter(|b| *last != Some(*b)) else {
+        return true;
+    };
+    *last = Some(battery);
+
+    tx.send(ManagerMsg::Event(Event::BatteryChanged {
+        battery,
+        now_ms: utils::now_ms(),
+    }))
+    .await
+    .is_ok()
+}
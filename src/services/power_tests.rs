@@ -0,0 +1,183 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use zbus::{Connection, Guid, connection};
+
+use crate::core::events::{BatteryStatus, Event, PowerState};
+use crate::core::manager_msg::ManagerMsg;
+use crate::services::battery::DISPLAY_DEVICE_PATH;
+use crate::services::power::{self, UPOWER_PATH};
+
+/// Minimal stand-in for org.freedesktop.UPower.
+struct MockUPower {
+    on_battery: bool,
+}
+
+#[zbus::interface(name = "org.freedesktop.UPower")]
+impl MockUPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> bool {
+        self.on_battery
+    }
+}
+
+struct MockDisplayDevice {
+    percentage: f64,
+    state: u32,
+}
+
+#[zbus::interface(name = "org.freedesktop.UPower.Device")]
+impl MockDisplayDevice {
+    #[zbus(property)]
+    fn is_present(&self) -> bool {
+        true
+    }
+
+    #[zbus(property)]
+    fn percentage(&self) -> f64 {
+        self.percentage
+    }
+
+    #[zbus(property)]
+    fn state(&self) -> u32 {
+        self.state
+    }
+
+    #[zbus(property)]
+    fn time_to_empty(&self) -> i64 {
+        0
+    }
+}
+
+async fn private_upower() -> (Connection, Connection) {
+    let (a, b) = tokio::net::UnixStream::pair().unwrap();
+
+    let server = connection::Builder::unix_stream(a)
+        .server(Guid::generate())
+        .unwrap()
+        .p2p()
+        .serve_at(UPOWER_PATH, MockUPower { on_battery: false })
+        .unwrap()
+        .serve_at(
+            DISPLAY_DEVICE_PATH,
+            MockDisplayDevice {
+                percentage: 80.0,
+                state: 1,
+            },
+        )
+        .unwrap()
+        .build();
+    let client = connection::Builder::unix_stream(b).p2p().build();
+
+    futures::try_join!(server, client).unwrap()
+}
+
+async fn next_event(rx: &mut mpsc::Receiver<ManagerMsg>) -> Event {
+    let msg = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("no event from the power service")
+        .expect("power service hung up");
+    match msg {
+        ManagerMsg::Event(ev) => ev,
+        _ => panic!("power service sent a non-event message"),
+    }
+}
+
+#[tokio::test]
+async fn upower_signals_drive_power_and_battery_events() {
+    let (server, client) = private_upower().await;
+    let (tx, mut rx) = mpsc::channel(8);
+
+    let watcher = tokio::spawn(async move { power::watch_upower(&client, &tx).await });
+
+    assert!(matches!(
+        next_event(&mut rx).await,
+        Event::PowerChanged {
+            state: PowerState::OnAC,
+            ..
+        }
+    ));
+    assert!(matches!(
+        next_event(&mut rx).await,
+        Event::BatteryChanged {
+            battery: BatteryStatus {
+                percent: 80,
+                discharging: false,
+                ..
+            },
+            ..
+        }
+    ));
+
+    // Unplug: UPower flips OnBattery and the DisplayDevice starts discharging.
+    let upower = server
+        .object_server()
+        .interface::<_, MockUPower>(UPOWER_PATH)
+        .await
+        .unwrap();
+    upower.get_mut().await.on_battery = true;
+    upower
+        .get()
+        .await
+        .on_battery_changed(upower.signal_emitter())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        next_event(&mut rx).await,
+        Event::PowerChanged {
+            state: PowerState::OnBattery,
+            ..
+        }
+    ));
+
+    let device = server
+        .object_server()
+        .interface::<_, MockDisplayDevice>(DISPLAY_DEVICE_PATH)
+        .await
+        .unwrap();
+    device.get_mut().await.state = 2;
+    device
+        .get()
+        .await
+        .state_changed(device.signal_emitter())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        next_event(&mut rx).await,
+        Event::BatteryChanged {
+            battery: BatteryStatus {
+                percent: 80,
+                discharging: true,
+                ..
+            },
+            ..
+        }
+    ));
+
+    // An unchanged value must not restart the idle cycle.
+    upower
+        .get()
+        .await
+        .on_battery_changed(upower.signal_emitter())
+        .await
+        .unwrap();
+    let quiet = tokio::time::timeout(Duration::from_millis(200), rx.recv()).await;
+    assert!(quiet.is_err(), "repeated OnBattery must not emit PowerChanged");
+
+    // Manager gone: the watcher returns cleanly on the next change.
+    drop(rx);
+    upower.get_mut().await.on_battery = false;
+    upower
+        .get()
+        .await
+        .on_battery_changed(upower.signal_emitter())
+        .await
+        .unwrap();
+    let done = tokio::time::timeout(Duration::from_secs(2), watcher).await;
+    assert!(matches!(done, Ok(Ok(Ok(())))));
+}
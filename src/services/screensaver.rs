@@ -0,0 +1,273 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use futures::StreamExt;
+use tokio::sync::{mpsc, watch};
+use zbus::{
+    fdo::{DBusProxy, RequestNameFlags, RequestNameReply},
+    message::Header,
+    Connection,
+};
+
+use crate::core::events::{Event, InhibitHolder};
+use crate::core::manager_msg::ManagerMsg;
+
+pub const SCREENSAVER_NAME: &str = "org.freedesktop.ScreenSaver";
+
+/// Clients use either the spec path or the older KDE-style one.
+const SCREENSAVER_PATHS: [&str; 2] = ["/org/freedesktop/ScreenSaver", "/ScreenSaver"];
+
+#[derive(Debug, Clone)]
+pub struct ScreenSaverRules {
+    pub epoch: u64, // forces watch::changed() on profile/reload even if values are identical
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Cookie {
+    sender: String,
+    holder: InhibitHolder,
+}
+
+/// Inhibit cookies handed out over org.freedesktop.ScreenSaver, keyed by cookie
+/// and remembering which unique bus name took each one.
+#[derive(Debug, Default)]
+pub struct Registry {
+    next_cookie: u32,
+    cookies: BTreeMap<u32, Cookie>,
+}
+
+impl Registry {
+    pub fn inhibit(&mut self, sender: &str, app: &str, reason: &str) -> u32 {
+        // 0 is never handed out; some clients treat it as "no cookie".
+        loop {
+            self.next_cookie = self.next_cookie.wrapping_add(1);
+            if self.next_cookie != 0 && !self.cookies.contains_key(&self.next_cookie) {
+                break;
+            }
+        }
+
+        self.cookies.insert(
+            self.next_cookie,
+            Cookie {
+                sender: sender.to_string(),
+                holder: InhibitHolder {
+                    app: app.to_string(),
+                    reason: reason.to_string(),
+                },
+            },
+        );
+
+        self.next_cookie
+    }
+
+    /// Drop `cookie` if it belongs to `sender`. Returns whether anything changed.
+    pub fn uninhibit(&mut self, sender: &str, cookie: u32) -> bool {
+        match self.cookies.get(&cookie) {
+            Some(c) if c.sender == sender => {
+                self.cookies.remove(&cookie);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop every cookie taken by `sender` (its unique name left the bus).
+    pub fn drop_sender(&mut self, sender: &str) -> bool {
+        let before = self.cookies.len();
+        self.cookies.retain(|_, c| c.sender != sender);
+        self.cookies.len() != before
+    }
+
+    pub fn clear(&mut self) -> bool {
+        let had_any = !self.cookies.is_empty();
+        self.cookies.clear();
+        had_any
+    }
+
+    pub fn holders(&self) -> Vec<InhibitHolder> {
+        self.cookies.values().map(|c| c.holder.clone()).collect()
+    }
+}
+
+#[derive(Clone)]
+struct ScreenSaver {
+    registry: Arc<Mutex<Registry>>,
+    tx: mpsc::Sender<ManagerMsg>,
+}
+
+fn sender_of(hdr: &Header<'_>) -> String {
+    hdr.sender().map(|s| s.to_string()).unwrap_or_default()
+}
+
+async fn publish(registry: &Mutex<Registry>, tx: &mpsc::Sender<ManagerMsg>) {
+    let holders = registry.lock().unwrap().holders();
+    let _ = tx
+        .send(ManagerMsg::Event(Event::ScreenSaverInhibitors {
+            holders,
+            now_ms: crate::core::utils::now_ms(),
+        }))
+        .await;
+}
+
+#[zbus::interface(name = "org.freedesktop.ScreenSaver")]
+impl ScreenSaver {
+    async fn inhibit(
+        &self,
+        application_name: String,
+        reason_for_inhibit: String,
+        #[zbus(header)] hdr: Header<'_>,
+    ) -> u32 {
+        let sender = sender_of(&hdr);
+        let cookie = self.registry.lock().unwrap().inhibit(
+            &sender,
+            &application_name,
+            &reason_for_inhibit,
+        );
+
+        eventline::info!(
+            "screensaver: inhibit #{} by {} ({}): {}",
+            cookie,
+            application_name,
+            sender,
+            reason_for_inhibit
+        );
+
+        publish(&self.registry, &self.tx).await;
+        cookie
+    }
+
+    async fn un_inhibit(&self, cookie: u32, #[zbus(header)] hdr: Header<'_>) {
+        let sender = sender_of(&hdr);
+        let removed = self.registry.lock().unwrap().uninhibit(&sender, cookie);
+
+        if removed {
+            eventline::info!("screensaver: uninhibit #{} by {}", cookie, sender);
+            publish(&self.registry, &self.tx).await;
+        } else {
+            eventline::debug!("screensaver: ignoring unknown cookie #{} from {}", cookie, sender);
+        }
+    }
+}
+
+/// Spawnable task: serves org.freedesktop.ScreenSaver on the session bus while
+/// `screensaver_inhibit` is enabled and reports the current holders to the manager.
+pub async fn run_screensaver(
+    tx: mpsc::Sender<ManagerMsg>,
+    mut rules_rx: watch::Receiver<ScreenSaverRules>,
+) {
+    let conn = match Connection::session().await {
+        Ok(c) => c,
+        Err(e) => {
+            eventline::warn!("screensaver: session bus unavailable: {e}");
+            return;
+        }
+    };
+
+    let registry = Arc::new(Mutex::new(Registry::default()));
+    let iface = ScreenSaver {
+        registry: registry.clone(),
+        tx: tx.clone(),
+    };
+
+    for path in SCREENSAVER_PATHS {
+        if let Err(e) = conn.object_server().at(path, iface.clone()).await {
+            eventline::warn!("screensaver: could not serve {path}: {e}");
+            return;
+        }
+    }
+
+    let mut owner_changes = match DBusProxy::new(&conn).await {
+        Ok(p) => match p.receive_name_owner_changed().await {
+            Ok(s) => s,
+            Err(e) => {
+                eventline::warn!("screensaver: NameOwnerChanged unavailable: {e}");
+                return;
+            }
+        },
+        Err(e) => {
+            eventline::warn!("screensaver: org.freedesktop.DBus proxy unavailable: {e}");
+            return;
+        }
+    };
+
+    let mut owned = false;
+    let enabled = rules_rx.borrow().enabled;
+    set_owned(&conn, &registry, &tx, &mut owned, enabled).await;
+
+    loop {
+        tokio::select! {
+            changed = rules_rx.changed() => {
+                if changed.is_err() {
+                    return; // sender dropped => shutting down
+                }
+                let rules = rules_rx.borrow().clone();
+                eventline::debug!("screensaver: rules epoch={} enabled={}", rules.epoch, rules.enabled);
+                set_owned(&conn, &registry, &tx, &mut owned, rules.enabled).await;
+            }
+
+            Some(sig) = owner_changes.next() => {
+                let Ok(args) = sig.args() else { continue };
+
+                // Only unique names ("x.y" names are just ownership moving around).
+                if args.new_owner().is_some() || !args.name().starts_with(':') {
+                    continue;
+                }
+
+                let gone = args.name().to_string();
+                let dropped = registry.lock().unwrap().drop_sender(&gone);
+                if dropped {
+                    eventline::info!("screensaver: {} left the bus; dropping its cookies", gone);
+                    publish(&registry, &tx).await;
+                }
+            }
+        }
+    }
+}
+
+async fn set_owned(
+    conn: &Connection,
+    registry: &Mutex<Registry>,
+    tx: &mpsc::Sender<ManagerMsg>,
+    owned: &mut bool,
+    enabled: bool,
+) {
+    if enabled == *owned {
+        return;
+    }
+
+    if enabled {
+        match conn
+            .request_name_with_flags(SCREENSAVER_NAME, RequestNameFlags::DoNotQueue.into())
+            .await
+        {
+            Ok(RequestNameReply::PrimaryOwner | RequestNameReply::AlreadyOwner) => {
+                eventline::info!("screensaver: owning {}", SCREENSAVER_NAME);
+                *owned = true;
+            }
+            Ok(reply) => {
+                eventline::warn!(
+                    "screensaver: {} is owned by another process ({:?}); not serving",
+                    SCREENSAVER_NAME,
+                    reply
+                );
+            }
+            Err(e) => eventline::warn!("screensaver: could not own {}: {e}", SCREENSAVER_NAME),
+        }
+        return;
+    }
+
+    if let Err(e) = conn.release_name(SCREENSAVER_NAME).await {
+        eventline::warn!("screensaver: could not release {}: {e}", SCREENSAVER_NAME);
+    }
+    *owned = false;
+    eventline::info!("screensaver: released {}", SCREENSAVER_NAME);
+
+    let had_any = registry.lock().unwrap().clear();
+    if had_any {
+        publish(registry, tx).await;
+    }
+}
@@ -0,0 +1,48 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use crate::core::events::InhibitHolder;
+use crate::services::screensaver::Registry;
+
+fn holder(app: &str, reason: &str) -> InhibitHolder {
+    InhibitHolder {
+        app: app.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+#[test]
+fn cookies_are_unique_and_nonzero() {
+    let mut r = Registry::default();
+
+    let a = r.inhibit(":1.10", "firefox", "video playing");
+    let b = r.inhibit(":1.10", "firefox", "video playing");
+
+    assert_ne!(a, 0);
+    assert_ne!(a, b);
+    assert_eq!(r.holders().len(), 2);
+}
+
+#[test]
+fn uninhibit_only_honors_the_owning_sender() {
+    let mut r = Registry::default();
+    let cookie = r.inhibit(":1.10", "mpv", "playing");
+
+    assert!(!r.uninhibit(":1.99", cookie));
+    assert_eq!(r.holders(), vec![holder("mpv", "playing")]);
+
+    assert!(r.uninhibit(":1.10", cookie));
+    assert!(r.holders().is_empty());
+}
+
+#[test]
+fn vanished_sender_drops_all_its_cookies() {
+    let mut r = Registry::default();
+    r.inhibit(":1.10", "chromium", "WebRTC");
+    r.inhibit(":1.10", "chromium", "video");
+    r.inhibit(":1.20", "impress", "presentation");
+
+    assert!(r.drop_sender(":1.10"));
+    assert!(!r.drop_sender(":1.10"));
+    assert_eq!(r.holders(), vec![holder("impress", "presentation")]);
+}
@@ -3,17 +3,52 @@
 
 use crate::core::events::Event;
 use crate::core::manager_msg::ManagerMsg;
+use crate::core::utils;
 
-use tokio::sync::mpsc::Sender;
+use tokio::sync::{mpsc::Sender, watch};
 use tokio::time::{sleep, Duration};
 
-pub async fn run_ticker(tx: Sender<ManagerMsg>) {
+/// Deadlines are wall-clock but sleeps are monotonic (and stop during
+/// suspend); waking at least this often re-checks against the clock.
+const MAX_SLEEP_MS: u64 = 60_000;
+
+/// Send `Tick` when the manager's next deadline (ms since epoch, from
+/// `deadlines`) comes due, and not otherwise. Each deadline ticks once: if the
+/// manager answers with the same one, wait for it to publish another.
+pub async fn run_ticker(tx: Sender<ManagerMsg>, mut deadlines: watch::Receiver<Option<u64>>) {
     eventline::info!("ticker started");
 
+    let mut fired: Option<u64> = None;
+
     loop {
-        sleep(Duration::from_millis(200)).await;
+        let deadline = (*deadlines.borrow_and_update()).filter(|d| fired != Some(*d));
+
+        let wait = async {
+            match deadline {
+                Some(d) => {
+                    let ms = d.saturating_sub(utils::now_ms()).min(MAX_SLEEP_MS);
+                    sleep(Duration::from_millis(ms)).await;
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            changed = deadlines.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+                continue;
+            }
+            _ = wait => {}
+        }
+
+        let now_ms = utils::now_ms();
+        let Some(d) = deadline.filter(|d| *d <= now_ms) else {
+            continue;
+        };
+        fired = Some(d);
 
-        let now_ms = now_ms();
         // If the daemon is gone, stop.
         if tx.send(ManagerMsg::Event(Event::Tick { now_ms })).await.is_err() {
             eventline::warn!("ticker stopping (receiver dropped)");
@@ -21,11 +56,3 @@ pub async fn run_ticker(tx: Sender<ManagerMsg>) {
         }
     }
 }
-
-fn now_ms() -> u64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let d = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| std::time::Duration::from_secs(0));
-    d.as_millis() as u64
-}
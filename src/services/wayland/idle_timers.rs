@@ -0,0 +1,104 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use tokio::sync::watch;
+
+use wayland_client::{Connection, Dispatch, Proxy, QueueHandle, protocol::wl_seat::WlSeat};
+use wayland_protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1::{Event as IdleEvent, ExtIdleNotificationV1},
+    ext_idle_notifier_v1::ExtIdleNotifierV1,
+};
+
+use crate::core::events::Event;
+use crate::core::manager_msg::ManagerMsg;
+
+use super::WaylandState;
+
+/// Per-step idle timers the daemon wants from the compositor
+/// (`compositor_idle_timers`), for the manager's current cycle.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IdleTimers {
+    pub epoch: u64,
+    /// `(plan step, idle ms)`; empty when the mode is off.
+    pub steps: Vec<(usize, u64)>,
+    /// Let surface idle inhibitors hold the timers back (`compositor_inhibit`).
+    /// Ignoring them needs ext-idle-notify v2.
+    pub honor_inhibitors: bool,
+}
+
+/// User data of one step's idle notification.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct StepTimer {
+    epoch: u64,
+    step: usize,
+}
+
+/// Hold one idle notification per published step timer, replacing them all
+/// whenever the timers or the cycle change. A new notification counts from
+/// its creation, so a cycle restarted without input (profile, plan source,
+/// wake) restarts the compositor's timers with it.
+pub(super) async fn run(
+    conn: Connection,
+    qh: QueueHandle<WaylandState>,
+    notifier: ExtIdleNotifierV1,
+    seat: WlSeat,
+    mut timers: watch::Receiver<IdleTimers>,
+) {
+    let mut live: Vec<ExtIdleNotificationV1> = Vec::new();
+
+    loop {
+        let current = timers.borrow_and_update().clone();
+
+        if !(live.is_empty() && current.steps.is_empty()) {
+            for notification in live.drain(..) {
+                notification.destroy();
+            }
+            for &(step, idle_ms) in &current.steps {
+                let timeout_ms = u32::try_from(idle_ms).unwrap_or(u32::MAX);
+                let timer = StepTimer {
+                    epoch: current.epoch,
+                    step,
+                };
+                let notification = if current.honor_inhibitors || notifier.version() < 2 {
+                    notifier.get_idle_notification(timeout_ms, &seat, &qh, timer)
+                } else {
+                    notifier.get_input_idle_notification(timeout_ms, &seat, &qh, timer)
+                };
+                live.push(notification);
+            }
+            if let Err(e) = conn.flush() {
+                eventline::warn!("wayland: idle timers stopped: {}", e);
+                return;
+            }
+        }
+
+        if timers.changed().await.is_err() {
+            break;
+        }
+    }
+
+    for notification in live {
+        notification.destroy();
+    }
+    let _ = conn.flush();
+}
+
+impl Dispatch<ExtIdleNotificationV1, StepTimer> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &ExtIdleNotificationV1,
+        event: IdleEvent,
+        timer: &StepTimer,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // "Resumed" is left to the activity notification.
+        if let IdleEvent::Idled = event {
+            let _ = state.tx.try_send(ManagerMsg::Event(Event::IdleReached {
+                epoch: timer.epoch,
+                step: timer.step,
+                now_ms: crate::core::utils::now_ms(),
+            }));
+        }
+    }
+}
@@ -0,0 +1,390 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use tokio::sync::{mpsc, watch};
+
+use wayland_client::{
+    protocol::{wl_output::WlOutput, wl_registry, wl_seat::WlSeat},
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols::ext::idle_notify::v1::client::{
+    ext_idle_notifier_v1::ExtIdleNotifierV1,
+    ext_idle_notification_v1::{Event as IdleEvent, ExtIdleNotificationV1},
+};
+
+use crate::core::events::{ActivityKind, Event};
+use crate::core::manager_msg::ManagerMsg;
+
+pub mod idle_timers;
+pub mod output;
+pub mod reconnect;
+pub mod surface_inhibit;
+pub mod toplevel;
+
+pub use idle_timers::IdleTimers;
+pub use toplevel::Toplevels;
+use output::OutputTracker;
+use surface_inhibit::{InhibitProbe, SurfaceInhibit};
+use toplevel::{ToplevelGlobals, ToplevelTracker};
+
+#[cfg(test)]
+mod output_tests;
+#[cfg(test)]
+mod reconnect_tests;
+#[cfg(test)]
+mod surface_inhibit_tests;
+#[cfg(test)]
+mod toplevel_tests;
+
+#[derive(Debug)]
+pub enum WaylandError {
+    Connect(String),
+    Roundtrip(String),
+}
+
+impl std::fmt::Display for WaylandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaylandError::Connect(s) => write!(f, "wayland connect failed: {s}"),
+            WaylandError::Roundtrip(s) => write!(f, "wayland roundtrip failed: {s}"),
+        }
+    }
+}
+
+impl std::error::Error for WaylandError {}
+
+struct WaylandState {
+    tx: mpsc::Sender<ManagerMsg>,
+
+    idle_notifier: Option<ExtIdleNotifierV1>,
+    seat: Option<WlSeat>,
+    notification: Option<ExtIdleNotificationV1>,
+    /// v2 only: inhibitor-respecting twin of the input-only `notification`.
+    probe: Option<ExtIdleNotificationV1>,
+    surface_inhibit: SurfaceInhibit,
+
+    idle_timeout_ms: u32,
+
+    toplevel_globals: ToplevelGlobals,
+    toplevels: ToplevelTracker,
+
+    outputs: OutputTracker,
+    output_proxies: HashMap<u32, WlOutput>,
+}
+
+impl WaylandState {
+    fn new(
+        tx: mpsc::Sender<ManagerMsg>,
+        idle_timeout_ms: u32,
+        toplevels: watch::Sender<Toplevels>,
+    ) -> Self {
+        Self {
+            tx,
+            idle_notifier: None,
+            seat: None,
+            notification: None,
+            probe: None,
+            surface_inhibit: SurfaceInhibit::default(),
+            idle_timeout_ms,
+            toplevel_globals: ToplevelGlobals::default(),
+            toplevels: ToplevelTracker::new(toplevels),
+            outputs: OutputTracker::default(),
+            output_proxies: HashMap::new(),
+        }
+    }
+
+    fn emit_activity(&self) {
+        let now_ms = crate::core::utils::now_ms();
+        let _ = self.tx.try_send(ManagerMsg::Event(Event::UserActivity {
+            kind: ActivityKind::Any,
+            now_ms,
+        }));
+    }
+}
+
+// ---------------- Registry binding ----------------
+
+impl Dispatch<wl_registry::WlRegistry, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wl_registry::Event::GlobalRemove { name } = event {
+            output::unbind(state, name);
+            return;
+        }
+
+        if let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        {
+            match interface.as_str() {
+                "ext_idle_notifier_v1" => {
+                    // v2 adds input-only notifications, which let us tell
+                    // surface idle inhibitors apart from real input.
+                    state.idle_notifier = Some(registry.bind::<ExtIdleNotifierV1, _, _>(
+                        name,
+                        version.min(2),
+                        qh,
+                        (),
+                    ));
+                }
+                "wl_seat" => {
+                    state.seat = Some(registry.bind::<WlSeat, _, _>(name, 1, qh, ()));
+                }
+                // Connected displays, for docked mode (lid shut on external monitors).
+                output::WL_OUTPUT => output::bind(registry, state, qh, name, version),
+                // Bound after the roundtrip, once we know which of the two exists.
+                toplevel::EXT_TOPLEVEL_LIST => {
+                    state.toplevel_globals.ext = Some((name, version));
+                }
+                toplevel::WLR_TOPLEVEL_MANAGER => {
+                    state.toplevel_globals.wlr = Some((name, version));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Dispatch<ExtIdleNotifierV1, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &ExtIdleNotifierV1,
+        _: <ExtIdleNotifierV1 as wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // no-op
+    }
+}
+
+impl Dispatch<WlSeat, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &WlSeat,
+        _: wayland_client::protocol::wl_seat::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // no-op
+    }
+}
+
+// ---------------- Idle notifications ----------------
+
+impl Dispatch<ExtIdleNotificationV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &ExtIdleNotificationV1,
+        event: IdleEvent,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // We only need "Resumed" as a clean "some input happened" signal.
+        // The manager's logic already treats UserActivity as "reset idle cycle".
+        match event {
+            IdleEvent::Resumed => {
+                state.emit_activity();
+            }
+            // Step timing uses the manager's deadlines or, with
+            // compositor_idle_timers, the per-step notifications. With the v2
+            // probe, this is where a surface inhibitor shows.
+            IdleEvent::Idled if state.probe.is_some() => {
+                let change = state.surface_inhibit.input_idled();
+                surface_inhibit::report(state, change);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Supervised Wayland service.
+///
+/// Runs one connection at a time (see `run_connection`). When the compositor
+/// goes away the manager is told there is no activity source, and the service
+/// reconnects with backoff (`reconnect::delay`) until shutdown.
+pub async fn run_wayland(
+    tx: mpsc::Sender<ManagerMsg>,
+    mut shutdown: watch::Receiver<bool>,
+    toplevels: watch::Sender<Toplevels>,
+    idle_timers: watch::Receiver<IdleTimers>,
+) {
+    let mut attempt: u32 = 0;
+
+    loop {
+        match run_connection(&tx, &shutdown, &toplevels, &idle_timers).await {
+            Ok(()) => attempt = 0,
+            // Only the first of a run of failures is worth a warning.
+            Err(e) if attempt == 0 => eventline::warn!("wayland: {}", e),
+            Err(e) => eventline::debug!("wayland: {}", e),
+        }
+
+        // Stale windows would keep app inhibitors alive.
+        toplevels.send_replace(Toplevels::default());
+        for ev in reconnect::disconnected_events(crate::core::utils::now_ms()) {
+            let _ = tx.try_send(ManagerMsg::Event(ev));
+        }
+
+        if *shutdown.borrow() {
+            break;
+        }
+
+        let wait = reconnect::delay(attempt);
+        eventline::info!("wayland: reconnecting in {}s", wait.as_secs());
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            changed = shutdown.changed() => {
+                if changed.is_err() || *shutdown.borrow() {
+                    break;
+                }
+            }
+        }
+
+        attempt = attempt.saturating_add(1);
+    }
+
+    eventline::info!("wayland: stopped");
+}
+
+/// One Wayland connection, until it drops or shutdown.
+///
+/// - Connects to Wayland from env
+/// - Sets up ext_idle_notifier_v1 if available
+/// - Publishes open toplevels (ext-foreign-toplevel-list, else wlr) to `toplevels`
+/// - Reports external `wl_output`s (docked mode) as `ExternalOutputs`
+/// - Keeps one idle notification per step in `idle_timers`
+/// - Runs a blocking dispatch loop in a blocking task
+///
+/// Errors only if the connection can't be set up.
+async fn run_connection(
+    tx: &mpsc::Sender<ManagerMsg>,
+    shutdown: &watch::Receiver<bool>,
+    toplevels: &watch::Sender<Toplevels>,
+    idle_timers: &watch::Receiver<IdleTimers>,
+) -> Result<(), WaylandError> {
+    // Small timeout gives fast "Resumed" events after any inactivity.
+    // (It does not spam; it's transition-based.)
+    let idle_timeout_ms: u32 = 250;
+
+    eventline::info!("wayland: starting (idle_timeout_ms={})", idle_timeout_ms);
+
+    let conn = Connection::connect_to_env().map_err(|e| WaylandError::Connect(e.to_string()))?;
+    let mut event_queue = conn.new_event_queue();
+    let qh = event_queue.handle();
+    let display = conn.display();
+
+    let mut state = WaylandState::new(tx.clone(), idle_timeout_ms, toplevels.clone());
+
+    // Bind globals
+    let registry = display.get_registry(&qh, ());
+    event_queue
+        .roundtrip(&mut state)
+        .map_err(|e| WaylandError::Roundtrip(e.to_string()))?;
+
+    // Enable idle notifications if supported
+    let mut timers_task = None;
+    if let (Some(notifier), Some(seat)) = (&state.idle_notifier, &state.seat) {
+        if notifier.version() >= 2 {
+            // Input-only, so activity still shows while a surface inhibits.
+            let notification =
+                notifier.get_input_idle_notification(state.idle_timeout_ms, seat, &qh, ());
+            state.notification = Some(notification);
+            state.probe = Some(notifier.get_idle_notification(
+                surface_inhibit::PROBE_TIMEOUT_MS,
+                seat,
+                &qh,
+                InhibitProbe,
+            ));
+            eventline::info!("wayland: ext_idle_notifier_v1 v2 active");
+        } else {
+            let notification =
+                notifier.get_idle_notification(state.idle_timeout_ms, seat, &qh, ());
+            state.notification = Some(notification);
+            eventline::info!(
+                "wayland: ext_idle_notifier_v1 v1 active; compositor idle inhibitors not visible"
+            );
+        }
+
+        timers_task = Some(tokio::spawn(idle_timers::run(
+            conn.clone(),
+            qh.clone(),
+            notifier.clone(),
+            seat.clone(),
+            idle_timers.clone(),
+        )));
+        send_idle_notifier(tx, true);
+    } else {
+        eventline::warn!(
+            "wayland: ext_idle_notifier_v1 or wl_seat missing; activity events disabled"
+        );
+    }
+
+    toplevel::bind(&registry, &mut state, &qh);
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop2 = Arc::clone(&stop);
+
+    // Shutdown watcher
+    let mut shutdown = shutdown.clone();
+    let watcher = tokio::spawn(async move {
+        loop {
+            if *shutdown.borrow() {
+                stop2.store(true, Ordering::Relaxed);
+                break;
+            }
+            if shutdown.changed().await.is_err() {
+                stop2.store(true, Ordering::Relaxed);
+                break;
+            }
+        }
+    });
+
+    // Run Wayland dispatch in a blocking task.
+    let dispatch = tokio::task::spawn_blocking(move || {
+        while !stop.load(Ordering::Relaxed) {
+            if let Err(e) = event_queue.blocking_dispatch(&mut state) {
+                let msg = e.to_string();
+                // Non-fatal: the supervisor reconnects.
+                eventline::error!("wayland: dispatch error: {}", msg);
+                break;
+            }
+        }
+
+        let change = state.surface_inhibit.clear();
+        surface_inhibit::report(&state, change);
+    });
+
+    if let Err(e) = dispatch.await {
+        eventline::error!("wayland: dispatch task failed: {}", e);
+    }
+    watcher.abort();
+    if let Some(task) = timers_task {
+        task.abort();
+    }
+
+    eventline::info!("wayland: disconnected");
+    Ok(())
+}
+
+fn send_idle_notifier(tx: &mpsc::Sender<ManagerMsg>, available: bool) {
+    let _ = tx.try_send(ManagerMsg::Event(Event::IdleNotifierChanged {
+        available,
+        now_ms: crate::core::utils::now_ms(),
+    }));
+}
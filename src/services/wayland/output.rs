@@ -0,0 +1,116 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashMap;
+
+use wayland_client::{
+    Connection, Dispatch, Proxy, QueueHandle,
+    protocol::{wl_output, wl_registry},
+};
+
+use crate::core::events::Event;
+use crate::core::manager_msg::ManagerMsg;
+
+use super::WaylandState;
+
+pub const WL_OUTPUT: &str = "wl_output";
+
+/// Connector names (wl_output v4) of built-in laptop panels.
+const INTERNAL_CONNECTORS: &[&str] = &["edp", "lvds", "dsi"];
+
+pub fn is_internal(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    INTERNAL_CONNECTORS.iter().any(|c| name.starts_with(c))
+}
+
+/// Counts external outputs by registry global. Outputs stay unnamed until the
+/// compositor sends a v4 `name`; those are assumed to be the panel, so an old
+/// compositor never makes a closed laptop look docked.
+#[derive(Debug, Default)]
+pub struct OutputTracker {
+    names: HashMap<u32, Option<String>>,
+    reported: Option<usize>,
+}
+
+impl OutputTracker {
+    pub fn added(&mut self, global: u32) {
+        self.names.insert(global, None);
+    }
+
+    pub fn named(&mut self, global: u32, name: String) {
+        if let Some(slot) = self.names.get_mut(&global) {
+            *slot = Some(name);
+        }
+    }
+
+    /// Returns whether `global` was an output.
+    pub fn removed(&mut self, global: u32) -> bool {
+        self.names.remove(&global).is_some()
+    }
+
+    pub fn external_count(&self) -> usize {
+        self.names
+            .values()
+            .filter(|name| name.as_deref().is_some_and(|n| !is_internal(n)))
+            .count()
+    }
+
+    /// The external count, if it differs from the last one taken.
+    pub fn take_change(&mut self) -> Option<usize> {
+        let count = self.external_count();
+        (self.reported != Some(count)).then(|| {
+            self.reported = Some(count);
+            count
+        })
+    }
+}
+
+pub(super) fn bind(
+    registry: &wl_registry::WlRegistry,
+    state: &mut WaylandState,
+    qh: &QueueHandle<WaylandState>,
+    global: u32,
+    version: u32,
+) {
+    let output = registry.bind::<wl_output::WlOutput, _, _>(global, version.min(4), qh, global);
+    state.outputs.added(global);
+    state.output_proxies.insert(global, output);
+}
+
+pub(super) fn unbind(state: &mut WaylandState, global: u32) {
+    if let Some(output) = state.output_proxies.remove(&global)
+        && output.version() >= 3
+    {
+        output.release();
+    }
+    if state.outputs.removed(global) {
+        report(state);
+    }
+}
+
+fn report(state: &mut WaylandState) {
+    if let Some(count) = state.outputs.take_change() {
+        eventline::debug!("wayland: {} external output(s)", count);
+        let _ = state.tx.try_send(ManagerMsg::Event(Event::ExternalOutputs {
+            count,
+            now_ms: crate::core::utils::now_ms(),
+        }));
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, u32> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &wl_output::WlOutput,
+        event: wl_output::Event,
+        global: &u32,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            wl_output::Event::Name { name } => state.outputs.named(*global, name),
+            wl_output::Event::Done => report(state),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use super::output::{OutputTracker, is_internal};
+
+#[test]
+fn built_in_panels_are_internal() {
+    for name in ["eDP-1", "LVDS-1", "DSI-1"] {
+        assert!(is_internal(name), "{name}");
+    }
+    for name in ["HDMI-A-1", "DP-3", "DVI-D-1"] {
+        assert!(!is_internal(name), "{name}");
+    }
+}
+
+#[test]
+fn external_count_follows_named_outputs() {
+    let mut t = OutputTracker::default();
+
+    t.added(10);
+    t.named(10, "eDP-1".into());
+    assert_eq!(t.take_change(), Some(0));
+
+    // Unnamed until the compositor says otherwise.
+    t.added(11);
+    assert_eq!(t.take_change(), None);
+    t.named(11, "DP-1".into());
+    assert_eq!(t.take_change(), Some(1));
+    assert_eq!(t.take_change(), None);
+
+    assert!(t.removed(11));
+    assert!(!t.removed(99));
+    assert_eq!(t.take_change(), Some(0));
+}
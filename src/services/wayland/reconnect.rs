@@ -0,0 +1,30 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use tokio::time::Duration;
+
+use crate::core::events::Event;
+
+/// First wait before reconnecting; doubles per failed attempt up to the max.
+pub const RECONNECT_MIN: Duration = Duration::from_secs(1);
+pub const RECONNECT_MAX: Duration = Duration::from_secs(30);
+
+/// What the manager is told once a connection is gone: no activity source,
+/// and no external outputs (a shut lid must not stay docked meanwhile).
+pub fn disconnected_events(now_ms: u64) -> [Event; 2] {
+    [
+        Event::IdleNotifierChanged {
+            available: false,
+            now_ms,
+        },
+        Event::ExternalOutputs { count: 0, now_ms },
+    ]
+}
+
+/// Wait before reconnect attempt `attempt`, counted from 0 since the last
+/// connection that came up.
+pub fn delay(attempt: u32) -> Duration {
+    1u32.checked_shl(attempt)
+        .and_then(|factor| RECONNECT_MIN.checked_mul(factor))
+        .map_or(RECONNECT_MAX, |d| d.min(RECONNECT_MAX))
+}
@@ -0,0 +1,42 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use tokio::time::Duration;
+
+use crate::core::events::Event;
+
+use super::reconnect::{RECONNECT_MAX, RECONNECT_MIN, delay, disconnected_events};
+
+#[test]
+fn delay_doubles_from_min() {
+    assert_eq!(delay(0), RECONNECT_MIN);
+    let waits: Vec<u64> = (0..5).map(|n| delay(n).as_secs()).collect();
+    assert_eq!(waits, vec![1, 2, 4, 8, 16]);
+}
+
+#[test]
+fn delay_caps_at_max() {
+    assert_eq!(delay(5), RECONNECT_MAX);
+    assert_eq!(delay(31), RECONNECT_MAX);
+    assert_eq!(delay(u32::MAX), RECONNECT_MAX);
+    assert!((0..64).all(|n| delay(n) <= Duration::from_secs(30)));
+}
+
+#[test]
+fn disconnect_drops_activity_source_and_outputs() {
+    let [idle, outputs] = disconnected_events(5000);
+    assert!(matches!(
+        idle,
+        Event::IdleNotifierChanged {
+            available: false,
+            now_ms: 5000
+        }
+    ));
+    assert!(matches!(
+        outputs,
+        Event::ExternalOutputs {
+            count: 0,
+            now_ms: 5000
+        }
+    ));
+}
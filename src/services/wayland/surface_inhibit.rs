@@ -0,0 +1,95 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use wayland_client::{Connection, Dispatch, QueueHandle};
+use wayland_protocols::ext::idle_notify::v1::client::ext_idle_notification_v1::{
+    Event as IdleEvent, ExtIdleNotificationV1,
+};
+
+use crate::core::events::Event;
+use crate::core::manager_msg::ManagerMsg;
+
+use super::WaylandState;
+
+/// Timeout of the probe notification. Shorter than the input-only activity
+/// notification, so with nothing inhibiting the probe always idles first.
+pub const PROBE_TIMEOUT_MS: u32 = 200;
+
+/// User data of the probe: an inhibitor-respecting notification next to the
+/// input-only one (ext-idle-notify v2).
+#[derive(Debug, Clone, Copy)]
+pub(super) struct InhibitProbe;
+
+/// Infers a surface idle inhibitor from the two notifications: input went
+/// idle but the probe, which the compositor holds back while a surface
+/// inhibits, did not. Sticky until the probe idles, since nothing can be
+/// seen while the user is active.
+#[derive(Debug, Default)]
+pub struct SurfaceInhibit {
+    probe_idle: bool,
+    inhibited: bool,
+}
+
+impl SurfaceInhibit {
+    /// The input-only notification idled. Returns the new state on a change.
+    pub fn input_idled(&mut self) -> Option<bool> {
+        self.set(!self.probe_idle)
+    }
+
+    pub fn probe_idled(&mut self) -> Option<bool> {
+        self.probe_idle = true;
+        self.set(false)
+    }
+
+    pub fn probe_resumed(&mut self) {
+        self.probe_idle = false;
+    }
+
+    /// The notifications are gone (disconnect); stop claiming an inhibitor.
+    pub fn clear(&mut self) -> Option<bool> {
+        self.set(false)
+    }
+
+    fn set(&mut self, inhibited: bool) -> Option<bool> {
+        (self.inhibited != inhibited).then(|| {
+            self.inhibited = inhibited;
+            inhibited
+        })
+    }
+}
+
+pub(super) fn report(state: &WaylandState, change: Option<bool>) {
+    let Some(active) = change else {
+        return;
+    };
+    eventline::info!(
+        "wayland: compositor idle inhibitor {}",
+        if active { "active" } else { "released" }
+    );
+    let _ = state
+        .tx
+        .try_send(ManagerMsg::Event(Event::CompositorInhibitor {
+            active,
+            now_ms: crate::core::utils::now_ms(),
+        }));
+}
+
+impl Dispatch<ExtIdleNotificationV1, InhibitProbe> for WaylandState {
+    fn event(
+        state: &mut Self,
+        _: &ExtIdleNotificationV1,
+        event: IdleEvent,
+        _: &InhibitProbe,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            IdleEvent::Idled => {
+                let change = state.surface_inhibit.probe_idled();
+                report(state, change);
+            }
+            IdleEvent::Resumed => state.surface_inhibit.probe_resumed(),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,33 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use super::surface_inhibit::SurfaceInhibit;
+
+#[test]
+fn input_idle_without_probe_means_inhibited() {
+    let mut s = SurfaceInhibit::default();
+
+    // Nothing inhibiting: the probe idles first.
+    assert_eq!(s.probe_idled(), None);
+    assert_eq!(s.input_idled(), None);
+
+    // Input again, then only the input-only notification idles.
+    s.probe_resumed();
+    assert_eq!(s.input_idled(), Some(true));
+
+    // Stays put while the user is active.
+    s.probe_resumed();
+    assert_eq!(s.input_idled(), None);
+
+    // The inhibitor went away while idle.
+    assert_eq!(s.probe_idled(), Some(false));
+}
+
+#[test]
+fn clear_reports_only_a_held_inhibitor() {
+    let mut s = SurfaceInhibit::default();
+    assert_eq!(s.clear(), None);
+
+    assert_eq!(s.input_idled(), Some(true));
+    assert_eq!(s.clear(), Some(false));
+}
@@ -0,0 +1,278 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use std::collections::HashMap;
+
+use tokio::sync::watch;
+use wayland_client::{
+    Connection, Dispatch, Proxy, QueueHandle, event_created_child, protocol::wl_registry,
+};
+use wayland_protocols::ext::foreign_toplevel_list::v1::client::{
+    ext_foreign_toplevel_handle_v1::{self, ExtForeignToplevelHandleV1},
+    ext_foreign_toplevel_list_v1::{self, ExtForeignToplevelListV1},
+};
+
+use self::wlr::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+use super::WaylandState;
+
+/// wlr-foreign-toplevel-management isn't in wayland-protocols; generate it here.
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    unused_imports
+)]
+#[allow(missing_docs, clippy::all)]
+pub mod wlr {
+    use wayland_client;
+    use wayland_client::protocol::*;
+
+    pub mod __interfaces {
+        use wayland_client::backend as wayland_backend;
+        use wayland_client::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!(
+            "protocols/wlr-foreign-toplevel-management-unstable-v1.xml"
+        );
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!(
+        "protocols/wlr-foreign-toplevel-management-unstable-v1.xml"
+    );
+}
+
+pub const EXT_TOPLEVEL_LIST: &str = "ext_foreign_toplevel_list_v1";
+pub const WLR_TOPLEVEL_MANAGER: &str = "zwlr_foreign_toplevel_manager_v1";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Toplevel {
+    pub app_id: String,
+    pub title: String,
+    /// Only wlr-foreign-toplevel reports state; `None` under ext-foreign-toplevel-list.
+    pub fullscreen: Option<bool>,
+    pub focused: Option<bool>,
+}
+
+/// Open toplevels as the compositor reports them.
+#[derive(Debug, Clone, Default)]
+pub struct Toplevels {
+    /// Protocol feeding the list; `None` until one is bound.
+    pub protocol: Option<&'static str>,
+    pub windows: Vec<Toplevel>,
+}
+
+/// Double-buffers per-handle title/app_id until `done`, then publishes the
+/// whole set. Handles are keyed by their protocol object id.
+#[derive(Debug, Default)]
+pub struct ToplevelTracker {
+    tx: Option<watch::Sender<Toplevels>>,
+    protocol: Option<&'static str>,
+    pending: HashMap<u32, Toplevel>,
+    live: HashMap<u32, Toplevel>,
+}
+
+impl ToplevelTracker {
+    pub fn new(tx: watch::Sender<Toplevels>) -> Self {
+        Self {
+            tx: Some(tx),
+            ..Self::default()
+        }
+    }
+
+    pub fn set_protocol(&mut self, protocol: &'static str) {
+        self.protocol = Some(protocol);
+        self.publish();
+    }
+
+    pub fn set_title(&mut self, id: u32, title: String) {
+        self.pending_for(id).title = title;
+    }
+
+    pub fn set_app_id(&mut self, id: u32, app_id: String) {
+        self.pending_for(id).app_id = app_id;
+    }
+
+    pub fn set_state(&mut self, id: u32, fullscreen: bool, focused: bool) {
+        let pending = self.pending_for(id);
+        pending.fullscreen = Some(fullscreen);
+        pending.focused = Some(focused);
+    }
+
+    pub fn done(&mut self, id: u32) {
+        let next = self.pending_for(id).clone();
+        if self.live.get(&id) != Some(&next) {
+            self.live.insert(id, next);
+            self.publish();
+        }
+    }
+
+    pub fn closed(&mut self, id: u32) {
+        self.pending.remove(&id);
+        if self.live.remove(&id).is_some() {
+            self.publish();
+        }
+    }
+
+    pub fn snapshot(&self) -> Toplevels {
+        let mut windows: Vec<Toplevel> = self.live.values().cloned().collect();
+        windows.sort_by(|a, b| (&a.app_id, &a.title).cmp(&(&b.app_id, &b.title)));
+        Toplevels {
+            protocol: self.protocol,
+            windows,
+        }
+    }
+
+    /// Later events only carry what changed, so start from the last committed state.
+    fn pending_for(&mut self, id: u32) -> &mut Toplevel {
+        let live = &self.live;
+        self.pending
+            .entry(id)
+            .or_insert_with(|| live.get(&id).cloned().unwrap_or_default())
+    }
+
+    fn publish(&self) {
+        if let Some(tx) = &self.tx {
+            tx.send_replace(self.snapshot());
+        }
+    }
+}
+
+/// Toplevel-list globals seen during the initial registry roundtrip.
+#[derive(Debug, Default)]
+pub(super) struct ToplevelGlobals {
+    pub ext: Option<(u32, u32)>,
+    pub wlr: Option<(u32, u32)>,
+}
+
+/// Bind ext-foreign-toplevel-list if advertised, else wlr-foreign-toplevel.
+pub(super) fn bind(
+    registry: &wl_registry::WlRegistry,
+    state: &mut WaylandState,
+    qh: &QueueHandle<WaylandState>,
+) {
+    if let Some((name, _)) = state.toplevel_globals.ext {
+        registry.bind::<ExtForeignToplevelListV1, _, _>(name, 1, qh, ());
+        state.toplevels.set_protocol(EXT_TOPLEVEL_LIST);
+    } else if let Some((name, version)) = state.toplevel_globals.wlr {
+        registry.bind::<ZwlrForeignToplevelManagerV1, _, _>(name, version.min(3), qh, ());
+        state.toplevels.set_protocol(WLR_TOPLEVEL_MANAGER);
+    } else {
+        eventline::info!("wayland: no foreign-toplevel protocol advertised");
+        return;
+    }
+
+    eventline::info!(
+        "wayland: tracking toplevels via {}",
+        state.toplevels.protocol.unwrap_or_default()
+    );
+}
+
+// ---------------- ext-foreign-toplevel-list-v1 ----------------
+
+impl Dispatch<ExtForeignToplevelListV1, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &ExtForeignToplevelListV1,
+        event: ext_foreign_toplevel_list_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let ext_foreign_toplevel_list_v1::Event::Finished = event {
+            eventline::warn!("wayland: compositor finished the toplevel list");
+        }
+    }
+
+    event_created_child!(WaylandState, ExtForeignToplevelListV1, [
+        ext_foreign_toplevel_list_v1::EVT_TOPLEVEL_OPCODE => (ExtForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ExtForeignToplevelHandleV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        handle: &ExtForeignToplevelHandleV1,
+        event: ext_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let id = handle.id().protocol_id();
+        match event {
+            ext_foreign_toplevel_handle_v1::Event::Title { title } => {
+                state.toplevels.set_title(id, title)
+            }
+            ext_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                state.toplevels.set_app_id(id, app_id)
+            }
+            ext_foreign_toplevel_handle_v1::Event::Done => state.toplevels.done(id),
+            ext_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels.closed(id);
+                handle.destroy();
+            }
+            _ => {}
+        }
+    }
+}
+
+// ---------------- wlr-foreign-toplevel-management-unstable-v1 ----------------
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for WaylandState {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        if let zwlr_foreign_toplevel_manager_v1::Event::Finished = event {
+            eventline::warn!("wayland: compositor finished the toplevel manager");
+        }
+    }
+
+    event_created_child!(WaylandState, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for WaylandState {
+    fn event(
+        state: &mut Self,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let id = handle.id().protocol_id();
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::Title { title } => {
+                state.toplevels.set_title(id, title)
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                state.toplevels.set_app_id(id, app_id)
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: raw } => {
+                let states: Vec<u32> = raw
+                    .chunks_exact(4)
+                    .map(|c| u32::from_ne_bytes([c[0], c[1], c[2], c[3]]))
+                    .collect();
+                state.toplevels.set_state(
+                    id,
+                    states.contains(&(zwlr_foreign_toplevel_handle_v1::State::Fullscreen as u32)),
+                    states.contains(&(zwlr_foreign_toplevel_handle_v1::State::Activated as u32)),
+                );
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => state.toplevels.done(id),
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevels.closed(id);
+                handle.destroy();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,56 @@
+// Author: Dustin Pilgrim
+// License: MIT
+
+use tokio::sync::watch;
+
+use super::toplevel::{EXT_TOPLEVEL_LIST, Toplevel, ToplevelTracker, Toplevels};
+
+fn window(app_id: &str, title: &str) -> Toplevel {
+    Toplevel {
+        app_id: app_id.to_string(),
+        title: title.to_string(),
+        ..Toplevel::default()
+    }
+}
+
+#[test]
+fn changes_publish_only_on_done() {
+    let (tx, rx) = watch::channel(Toplevels::default());
+    let mut t = ToplevelTracker::new(tx);
+    t.set_protocol(EXT_TOPLEVEL_LIST);
+
+    t.set_app_id(7, "mpv".into());
+    t.set_title(7, "movie.mkv".into());
+    assert!(rx.borrow().windows.is_empty());
+
+    t.done(7);
+    assert_eq!(rx.borrow().protocol, Some(EXT_TOPLEVEL_LIST));
+    assert_eq!(rx.borrow().windows, vec![window("mpv", "movie.mkv")]);
+
+    // A title-only update keeps the app_id it had.
+    t.set_title(7, "next.mkv".into());
+    t.done(7);
+    assert_eq!(rx.borrow().windows, vec![window("mpv", "next.mkv")]);
+}
+
+#[test]
+fn closed_toplevels_drop_out() {
+    let (tx, rx) = watch::channel(Toplevels::default());
+    let mut t = ToplevelTracker::new(tx);
+
+    for (id, app) in [(1, "firefox"), (2, "mpv")] {
+        t.set_app_id(id, app.into());
+        t.done(id);
+    }
+    assert_eq!(rx.borrow().windows.len(), 2);
+
+    t.closed(1);
+    assert_eq!(rx.borrow().windows, vec![window("mpv", "")]);
+
+    // Closing something never committed doesn't publish a new snapshot.
+    let mut rx2 = rx.clone();
+    rx2.mark_unchanged();
+    t.set_app_id(3, "foot".into());
+    t.closed(3);
+    assert!(!rx2.has_changed().unwrap());
+}